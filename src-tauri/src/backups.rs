@@ -0,0 +1,631 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDateTime};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::busy::BusyStore;
+use crate::error::{Error, Result};
+use crate::history::{HistoryQuery, TaskUpdate};
+use crate::persist::{
+    self, DataFiles, BUSY_FILE, PLANNER_FILE, SETTINGS_FILE, STRIKES_FILE, UPDATES_FILE,
+    USED_MESSAGES_FILE, WIDGETS_FILE,
+};
+use crate::planner::PlannerStore;
+use crate::settings::AppSettings;
+use crate::storage::Storage;
+use crate::strikes::StrikeEntry;
+use crate::tasks::{Task, TaskStore};
+use crate::time::now_ms;
+
+/// Directory (inside the data directory) holding the backups.
+pub const BACKUPS_DIR: &str = "backups";
+
+const BACKUP_EXT: &str = ".json.gz";
+const STAMP_FORMAT: &str = "%Y%m%dT%H%M%S%3fZ";
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// Data files the storage backend does not own; they are copied as they are.
+pub const LOOSE_FILES: &[&str] = &[USED_MESSAGES_FILE, WIDGETS_FILE, PLANNER_FILE, BUSY_FILE];
+
+/// The stores a restore or import writes through, so that every write takes the
+/// same lock and sends the same event as the app's own edits.
+pub struct Stores<'a> {
+    pub tasks: &'a TaskStore,
+    pub files: &'a DataFiles,
+    pub planner: &'a PlannerStore,
+    pub busy: &'a BusyStore,
+}
+
+impl Stores<'_> {
+    /// Writes one of [`LOOSE_FILES`]: the schedule and calendar through their stores,
+    /// which check and announce them, the rest as plain data files.
+    pub fn write_loose(&self, name: &str, value: &Value) -> Result<()> {
+        match name {
+            PLANNER_FILE => self.planner.replace(Vec::deserialize(value)?),
+            BUSY_FILE => self.busy.replace(Vec::deserialize(value)?),
+            _ => self.files.write(name, value),
+        }
+    }
+}
+
+/// How often the scheduler looks for a due backup.
+const CHECK_EVERY: Duration = Duration::from_secs(60 * 60);
+
+/// Why a backup was taken; also decides how many of its kind are kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupKind {
+    Daily,
+    Weekly,
+    Monthly,
+    Manual,
+    /// Taken automatically just before a restore.
+    Safety,
+}
+
+impl BackupKind {
+    const SCHEDULED: [BackupKind; 3] = [BackupKind::Daily, BackupKind::Weekly, BackupKind::Monthly];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            BackupKind::Daily => "daily",
+            BackupKind::Weekly => "weekly",
+            BackupKind::Monthly => "monthly",
+            BackupKind::Manual => "manual",
+            BackupKind::Safety => "safety",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "daily" => Some(BackupKind::Daily),
+            "weekly" => Some(BackupKind::Weekly),
+            "monthly" => Some(BackupKind::Monthly),
+            "manual" => Some(BackupKind::Manual),
+            "safety" => Some(BackupKind::Safety),
+            _ => None,
+        }
+    }
+
+    /// Newest backups of this kind that survive rotation.
+    fn keep(self) -> usize {
+        match self {
+            BackupKind::Daily => 7,
+            BackupKind::Weekly => 4,
+            BackupKind::Monthly => 12,
+            BackupKind::Manual => 10,
+            BackupKind::Safety => 5,
+        }
+    }
+
+    /// The UTC calendar period a scheduled backup covers; one is due whenever the
+    /// current period has none yet.
+    fn period(self, ms: i64) -> String {
+        let format = match self {
+            BackupKind::Daily => "%Y-%m-%d",
+            BackupKind::Weekly => "%G-W%V",
+            BackupKind::Monthly => "%Y-%m",
+            BackupKind::Manual | BackupKind::Safety => return String::new(),
+        };
+        DateTime::from_timestamp_millis(ms)
+            .unwrap_or_default()
+            .format(format)
+            .to_string()
+    }
+}
+
+/// One backup file, as listed to the settings page.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupInfo {
+    /// File name without the extension, e.g. `daily-20250301T090000000Z`.
+    pub id: String,
+    pub kind: BackupKind,
+    /// Epoch milliseconds.
+    pub created_at: i64,
+    /// Compressed size in bytes.
+    pub size: u64,
+    /// Loose files left out because they could not be read. Only known when the
+    /// backup is taken; listed backups leave it empty.
+    pub skipped: Vec<String>,
+}
+
+/// Everything the app stores, as written (gzipped) to a backup file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataSnapshot {
+    pub version: u32,
+    pub created_at: i64,
+    pub tasks: Vec<Task>,
+    pub strikes: Vec<StrikeEntry>,
+    #[serde(default)]
+    pub settings: Option<AppSettings>,
+    pub updates: Vec<TaskUpdate>,
+    /// Loose data files by name.
+    #[serde(default)]
+    pub files: BTreeMap<String, Value>,
+    /// Loose files that could not be read, and are missing from `files`.
+    #[serde(skip)]
+    pub skipped: Vec<String>,
+}
+
+/// A task as named in a restore preview.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskSummary {
+    pub id: String,
+    pub title: String,
+}
+
+/// What restoring a backup would do to the current data. Tasks are matched by id;
+/// strikes by task and timestamp.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestorePreview {
+    pub backup: Option<BackupInfo>,
+    /// In the backup but not in the current data.
+    pub tasks_added: Vec<TaskSummary>,
+    /// In the current data but not in the backup.
+    pub tasks_removed: Vec<TaskSummary>,
+    /// In both, with different contents; the summary carries the backup's title.
+    pub tasks_changed: Vec<TaskSummary>,
+    pub strikes_current: usize,
+    pub strikes_in_backup: usize,
+    pub strikes_added: usize,
+    pub strikes_removed: usize,
+    pub updates_current: usize,
+    pub updates_in_backup: usize,
+    pub settings_changed: bool,
+}
+
+impl RestorePreview {
+    fn between(current: &DataSnapshot, backup: &DataSnapshot) -> Self {
+        let summary = |t: &Task| TaskSummary {
+            id: t.id.clone(),
+            title: t.title.clone(),
+        };
+        let find = |tasks: &[Task], id: &str| tasks.iter().position(|t| t.id == id);
+        let strike_keys = |s: &DataSnapshot| -> HashSet<(String, i64)> {
+            s.strikes
+                .iter()
+                .map(|s| (s.task_id.clone(), s.ts))
+                .collect()
+        };
+        let (now, then) = (strike_keys(current), strike_keys(backup));
+        Self {
+            backup: None,
+            tasks_added: backup
+                .tasks
+                .iter()
+                .filter(|t| find(&current.tasks, &t.id).is_none())
+                .map(summary)
+                .collect(),
+            tasks_removed: current
+                .tasks
+                .iter()
+                .filter(|t| find(&backup.tasks, &t.id).is_none())
+                .map(summary)
+                .collect(),
+            tasks_changed: backup
+                .tasks
+                .iter()
+                .filter(|t| find(&current.tasks, &t.id).is_some_and(|i| current.tasks[i] != **t))
+                .map(summary)
+                .collect(),
+            strikes_current: current.strikes.len(),
+            strikes_in_backup: backup.strikes.len(),
+            strikes_added: then.difference(&now).count(),
+            strikes_removed: now.difference(&then).count(),
+            updates_current: current.updates.len(),
+            updates_in_backup: backup.updates.len(),
+            settings_changed: backup.settings.is_some() && backup.settings != current.settings,
+        }
+    }
+}
+
+/// Compressed snapshots of all data in `backups/`, taken daily, weekly and monthly
+/// by a background thread and on demand, with older ones rotated out per kind.
+#[derive(Clone)]
+pub struct Backups {
+    data_dir: PathBuf,
+    dir: PathBuf,
+    storage: Arc<dyn Storage>,
+    lock: Arc<Mutex<()>>,
+}
+
+impl Backups {
+    pub fn new(data_dir: &Path, storage: Arc<dyn Storage>) -> Self {
+        Self {
+            data_dir: data_dir.to_path_buf(),
+            dir: data_dir.join(BACKUPS_DIR),
+            storage,
+            lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Takes any due scheduled backups now and then once an hour, for the life of
+    /// the process.
+    pub fn schedule(&self) {
+        let backups = self.clone();
+        thread::spawn(move || loop {
+            if let Err(e) = backups.run_due(now_ms()) {
+                eprintln!("scheduled backup failed: {e}");
+            }
+            thread::sleep(CHECK_EVERY);
+        });
+    }
+
+    /// Newest first. Files that do not look like backups are ignored.
+    pub fn list(&self) -> Result<Vec<BackupInfo>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut backups = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            let name = entry.file_name();
+            let Some(id) = name.to_str().and_then(|n| n.strip_suffix(BACKUP_EXT)) else {
+                continue;
+            };
+            if let Some((kind, created_at)) = parse_id(id) {
+                backups.push(BackupInfo {
+                    id: id.to_string(),
+                    kind,
+                    created_at,
+                    size: entry.metadata()?.len(),
+                    skipped: Vec::new(),
+                });
+            }
+        }
+        backups.sort_by_key(|b| Reverse(b.created_at));
+        Ok(backups)
+    }
+
+    /// Writes a backup of `kind` as of `now` and rotates that kind.
+    pub fn create(&self, kind: BackupKind, now: i64) -> Result<BackupInfo> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let snapshot = self.snapshot(now)?;
+        let bytes = compress(&snapshot)?;
+        let info = self.write(kind, now, &bytes, &snapshot.skipped)?;
+        self.rotate(kind)?;
+        Ok(info)
+    }
+
+    /// Takes the scheduled backups whose period has none yet. One snapshot serves
+    /// all of them.
+    pub fn run_due(&self, now: i64) -> Result<Vec<BackupInfo>> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let existing = self.list()?;
+        let due: Vec<_> = BackupKind::SCHEDULED
+            .into_iter()
+            .filter(|kind| {
+                let period = kind.period(now);
+                !existing
+                    .iter()
+                    .any(|b| b.kind == *kind && kind.period(b.created_at) == period)
+            })
+            .collect();
+        if due.is_empty() {
+            return Ok(Vec::new());
+        }
+        let snapshot = self.snapshot(now)?;
+        for name in &snapshot.skipped {
+            eprintln!("scheduled backup left out {name}, which could not be read");
+        }
+        let bytes = compress(&snapshot)?;
+        let mut written = Vec::new();
+        for kind in due {
+            written.push(self.write(kind, now, &bytes, &snapshot.skipped)?);
+            self.rotate(kind)?;
+        }
+        Ok(written)
+    }
+
+    /// The contents of backup `id`.
+    pub fn read(&self, id: &str) -> Result<DataSnapshot> {
+        if parse_id(id).is_none() {
+            return Err(Error::Invalid(format!("{id} is not a backup")));
+        }
+        let bytes = persist::read_file(&self.path(id))?
+            .ok_or_else(|| Error::Invalid(format!("backup {id} does not exist")))?;
+        Ok(serde_json::from_reader(GzDecoder::new(&bytes[..]))?)
+    }
+
+    /// What [`Backups::restore`] would change, without changing anything.
+    pub fn preview(&self, id: &str) -> Result<RestorePreview> {
+        let backup = self.read(id)?;
+        let info = self.list()?.into_iter().find(|b| b.id == id);
+        Ok(RestorePreview {
+            backup: info,
+            ..RestorePreview::between(&self.snapshot(now_ms())?, &backup)
+        })
+    }
+
+    /// Replaces all data with backup `id`, after taking a safety backup of what is
+    /// there now (returned, so it can be restored in turn). Writes go through the
+    /// stores so every window hears about them; the history goes back first so the
+    /// restore is recorded on top of it. Settings and loose files missing from the
+    /// backup are left as they are.
+    pub fn restore(&self, id: &str, stores: &Stores) -> Result<BackupInfo> {
+        let backup = self.read(id)?;
+        let safety = self.create(BackupKind::Safety, now_ms())?;
+        let files = stores.files;
+        files.write(UPDATES_FILE, &serde_json::to_value(backup.updates)?)?;
+        stores.tasks.replace_all(backup.tasks)?;
+        files.write(STRIKES_FILE, &serde_json::to_value(backup.strikes)?)?;
+        if let Some(settings) = backup.settings {
+            files.write(SETTINGS_FILE, &serde_json::to_value(settings)?)?;
+        }
+        for (name, value) in &backup.files {
+            if LOOSE_FILES.contains(&name.as_str()) {
+                stores.write_loose(name, value)?;
+            }
+        }
+        Ok(safety)
+    }
+
+    /// Everything the app stores, as of `now`. A loose file that cannot be read is
+    /// left out and named in `skipped` rather than failing the whole snapshot.
+    pub fn snapshot(&self, now: i64) -> Result<DataSnapshot> {
+        let mut files = BTreeMap::new();
+        let mut skipped = Vec::new();
+        for name in LOOSE_FILES {
+            match persist::read_json::<Value>(&self.data_dir.join(name)) {
+                Ok(Some(value)) => {
+                    files.insert(name.to_string(), value);
+                }
+                Ok(None) => {}
+                Err(_) => skipped.push(name.to_string()),
+            }
+        }
+        Ok(DataSnapshot {
+            version: SNAPSHOT_VERSION,
+            created_at: now,
+            tasks: self.storage.load_tasks()?,
+            strikes: self.storage.load_strikes()?,
+            settings: self.storage.load_settings()?,
+            updates: self.storage.load_updates(&HistoryQuery::default())?,
+            files,
+            skipped,
+        })
+    }
+
+    fn write(
+        &self,
+        kind: BackupKind,
+        now: i64,
+        bytes: &[u8],
+        skipped: &[String],
+    ) -> Result<BackupInfo> {
+        let id = format!("{}-{}", kind.as_str(), stamp(now));
+        persist::atomic_write(&self.path(&id), bytes)?;
+        Ok(BackupInfo {
+            id,
+            kind,
+            created_at: now,
+            size: bytes.len() as u64,
+            skipped: skipped.to_vec(),
+        })
+    }
+
+    fn rotate(&self, kind: BackupKind) -> Result<()> {
+        for old in self
+            .list()?
+            .into_iter()
+            .filter(|b| b.kind == kind)
+            .skip(kind.keep())
+        {
+            fs::remove_file(self.path(&old.id))?;
+        }
+        Ok(())
+    }
+
+    fn path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{id}{BACKUP_EXT}"))
+    }
+}
+
+fn stamp(ms: i64) -> String {
+    DateTime::from_timestamp_millis(ms)
+        .unwrap_or_default()
+        .format(STAMP_FORMAT)
+        .to_string()
+}
+
+/// `daily-20250301T090000000Z` -> (Daily, its epoch milliseconds)
+fn parse_id(id: &str) -> Option<(BackupKind, i64)> {
+    let (kind, stamp) = id.split_once('-')?;
+    let time = NaiveDateTime::parse_from_str(stamp, STAMP_FORMAT).ok()?;
+    Some((BackupKind::parse(kind)?, time.and_utc().timestamp_millis()))
+}
+
+fn compress(snapshot: &DataSnapshot) -> Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&serde_json::to_vec(snapshot)?)?;
+    Ok(encoder.finish()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::events::{self, Events, Recorded};
+    use crate::storage::MemoryStorage;
+
+    fn loose_stores(dir: &Path, events: Arc<dyn Events>) -> (PlannerStore, BusyStore) {
+        (
+            PlannerStore::new(dir.into(), events.clone()),
+            BusyStore::new(dir.into(), events),
+        )
+    }
+
+    const DAY: i64 = 24 * 60 * 60 * 1000;
+    // 2025-03-03T09:00Z, a Monday
+    const MONDAY: i64 = 1_740_992_400_000;
+
+    #[test]
+    fn scheduled_backups_rotate_per_kind() {
+        let dir = tempfile::tempdir().unwrap();
+        let backups = Backups::new(dir.path(), Arc::new(MemoryStorage::default()));
+
+        let kinds = |written: Vec<BackupInfo>| -> Vec<BackupKind> {
+            written.into_iter().map(|b| b.kind).collect()
+        };
+        assert_eq!(
+            kinds(backups.run_due(MONDAY).unwrap()),
+            BackupKind::SCHEDULED
+        );
+        assert!(backups.run_due(MONDAY + 60_000).unwrap().is_empty());
+        assert_eq!(
+            kinds(backups.run_due(MONDAY + DAY).unwrap()),
+            [BackupKind::Daily]
+        );
+
+        for day in 2..40 {
+            backups.run_due(MONDAY + day * DAY).unwrap();
+        }
+        backups
+            .create(BackupKind::Manual, MONDAY + 40 * DAY)
+            .unwrap();
+        let count = |kind| {
+            backups
+                .list()
+                .unwrap()
+                .iter()
+                .filter(|b| b.kind == kind)
+                .count()
+        };
+        assert_eq!(count(BackupKind::Daily), 7);
+        assert_eq!(count(BackupKind::Weekly), 4);
+        assert_eq!(count(BackupKind::Monthly), 2);
+        assert_eq!(count(BackupKind::Manual), 1);
+
+        let newest = &backups.list().unwrap()[0];
+        assert_eq!(newest.kind, BackupKind::Manual);
+        assert_eq!(
+            parse_id(&newest.id),
+            Some((BackupKind::Manual, MONDAY + 40 * DAY))
+        );
+    }
+
+    #[test]
+    fn restore_previews_then_swaps_in_the_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage: Arc<dyn Storage> = Arc::new(MemoryStorage::default());
+        let tasks = TaskStore::new(storage.clone(), Arc::new(()));
+        let files = DataFiles::new(dir.path().into(), storage.clone(), Arc::new(()));
+        let (planner, busy) = loose_stores(dir.path(), Arc::new(()));
+        let stores = Stores {
+            tasks: &tasks,
+            files: &files,
+            planner: &planner,
+            busy: &busy,
+        };
+        let backups = Backups::new(dir.path(), storage.clone());
+        let draft = |title: &str| crate::tasks::TaskDraft {
+            title: title.into(),
+            ..Default::default()
+        };
+
+        let kept = tasks.create(draft("kept")).unwrap();
+        let backup = backups.create(BackupKind::Manual, MONDAY).unwrap();
+        tasks.update(&kept.id, 0, draft("renamed")).unwrap();
+        let added = tasks.create(draft("added later")).unwrap();
+        let strike = serde_json::json!([{ "taskId": added.id, "date": "2025-03-03", "ts": 1 }]);
+        files.write(STRIKES_FILE, &strike).unwrap();
+
+        let preview = backups.preview(&backup.id).unwrap();
+        assert_eq!(preview.backup.as_ref(), Some(&backup));
+        assert!(preview.tasks_added.is_empty());
+        assert_eq!(preview.tasks_removed[0].id, added.id);
+        assert_eq!(preview.tasks_changed[0].title, "kept");
+        assert_eq!((preview.strikes_current, preview.strikes_removed), (1, 1));
+        assert_eq!(backups.list().unwrap().len(), 1);
+
+        let safety = backups.restore(&backup.id, &stores).unwrap();
+        assert_eq!(safety.kind, BackupKind::Safety);
+        let restored = tasks.list().unwrap();
+        assert_eq!((restored.len(), restored[0].title.as_str()), (1, "kept"));
+        assert_eq!(restored[0].revision, 2);
+        let history = tasks.history().query(&Default::default()).unwrap();
+        assert!(history.iter().any(|u| u.diff["title"]["new"] == "kept"));
+        assert!(history.last().unwrap().is_deletion());
+        assert!(storage.load_strikes().unwrap().is_empty());
+        let undo = backups.preview(&safety.id).unwrap();
+        assert_eq!(undo.tasks_added[0].id, added.id);
+
+        assert!(backups.read("../tasks").is_err());
+    }
+
+    #[test]
+    fn loose_files_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage: Arc<dyn Storage> = Arc::new(MemoryStorage::default());
+        let events = Arc::new(Recorded::default());
+        let tasks = TaskStore::new(storage.clone(), events.clone());
+        let files = DataFiles::new(dir.path().into(), storage.clone(), events.clone());
+        let (planner_store, busy_store) = loose_stores(dir.path(), events.clone());
+        let stores = Stores {
+            tasks: &tasks,
+            files: &files,
+            planner: &planner_store,
+            busy: &busy_store,
+        };
+        let backups = Backups::new(dir.path(), storage);
+        let planner = serde_json::json!([{ "taskId": "a", "task": {}, "startHour": 9,
+            "startMinute": 0, "durationMinutes": 30, "date": "2025-03-03" }]);
+        let busy = serde_json::json!([{ "uid": "standup", "summary": "Stand-up",
+            "start": "2025-03-03T09:00:00", "tzid": null, "allDay": false,
+            "durationMinutes": 15, "rrule": null, "exdates": [] }]);
+        stores.write_loose(PLANNER_FILE, &planner).unwrap();
+        stores.write_loose(BUSY_FILE, &busy).unwrap();
+
+        let backup = backups.create(BackupKind::Manual, MONDAY).unwrap();
+        assert_eq!(backups.read(&backup.id).unwrap().files.len(), 2);
+        planner_store.replace(Vec::new()).unwrap();
+        busy_store.clear().unwrap();
+
+        events.0.lock().unwrap().clear();
+        backups.restore(&backup.id, &stores).unwrap();
+        assert_eq!(files.read(PLANNER_FILE).unwrap(), Some(planner));
+        assert_eq!(files.read(BUSY_FILE).unwrap(), Some(busy));
+        // Open planner windows hear about it rather than saving over it.
+        let names: Vec<_> = events
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(n, _)| n.clone())
+            .collect();
+        assert!(names.iter().any(|n| n == events::PLANNER_CHANGED));
+        assert!(names.iter().any(|n| n == events::BUSY_CHANGED));
+    }
+
+    #[test]
+    fn unreadable_loose_files_are_skipped_and_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let backups = Backups::new(dir.path(), Arc::new(MemoryStorage::default()));
+        fs::write(dir.path().join(WIDGETS_FILE), "[{").unwrap();
+        fs::write(dir.path().join(USED_MESSAGES_FILE), "[]").unwrap();
+
+        let backup = backups.create(BackupKind::Manual, MONDAY).unwrap();
+        assert_eq!(backup.skipped, [WIDGETS_FILE]);
+        let files = backups.read(&backup.id).unwrap().files;
+        assert_eq!(files.keys().collect::<Vec<_>>(), [USED_MESSAGES_FILE]);
+        assert!(backups.list().unwrap()[0].skipped.is_empty());
+    }
+}
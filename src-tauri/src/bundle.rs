@@ -0,0 +1,644 @@
+//! `export_all` / `import_all`: everything the app stores as one typed, versioned
+//! bundle. Imports are checked record by record; what fails is reported and left out
+//! rather than written.
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::backups::{BackupInfo, BackupKind, Backups, DataSnapshot, Stores, LOOSE_FILES};
+use crate::busy::BusyEvent;
+use crate::crypto::{SealedExport, SEALED_EXPORT_FORMAT};
+use crate::error::{Error, Result};
+use crate::history::TaskUpdate;
+use crate::persist::{
+    BUSY_FILE, PLANNER_FILE, SETTINGS_FILE, STRIKES_FILE, UPDATES_FILE, USED_MESSAGES_FILE,
+};
+use crate::planner::ScheduledTask;
+use crate::schema;
+use crate::settings::AppSettings;
+use crate::strikes::StrikeEntry;
+use crate::tasks::{is_valid_date, Task, TaskDraft, TASKS_FILE};
+use crate::time::timezone;
+
+pub const BUNDLE_FORMAT: &str = "shakshuka-export";
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// The export file. Sections hold the records at the current schema version.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bundle {
+    /// Always [`BUNDLE_FORMAT`].
+    pub format: String,
+    pub version: u32,
+    /// Epoch milliseconds.
+    pub exported_at: i64,
+    pub tasks: Vec<Task>,
+    pub strikes: Vec<StrikeEntry>,
+    #[serde(default)]
+    pub settings: Option<AppSettings>,
+    pub updates: Vec<TaskUpdate>,
+    /// Loose data files by name.
+    #[serde(default)]
+    pub files: BTreeMap<String, Value>,
+}
+
+impl From<DataSnapshot> for Bundle {
+    fn from(snapshot: DataSnapshot) -> Self {
+        Self {
+            format: BUNDLE_FORMAT.into(),
+            version: BUNDLE_VERSION,
+            exported_at: snapshot.created_at,
+            tasks: snapshot.tasks,
+            strikes: snapshot.strikes,
+            settings: snapshot.settings,
+            updates: snapshot.updates,
+            files: snapshot.files,
+        }
+    }
+}
+
+/// What `export_all` hands back: the bundle itself, or sealed with the passphrase
+/// when encryption is on.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum ExportFile {
+    Plain(Bundle),
+    Sealed(SealedExport),
+}
+
+impl ExportFile {
+    pub fn new(bundle: Bundle, passphrase: Option<&str>) -> Result<Self> {
+        Ok(match passphrase {
+            Some(passphrase) => Self::Sealed(SealedExport::seal(
+                passphrase,
+                &serde_json::to_vec(&bundle)?,
+            )?),
+            None => Self::Plain(bundle),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+    /// Adds to the current data. A task already present is taken from the bundle
+    /// only if it was edited there more recently; settings are left alone.
+    Merge,
+    /// Replaces the current data with the bundle's valid records.
+    Replace,
+}
+
+/// One record left out of an import, and why.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rejected {
+    /// `tasks`, `strikes`, `updates`, `settings`, `planner` or `files`.
+    pub section: String,
+    /// Position in its section; `None` for settings.
+    pub index: Option<usize>,
+    /// The record's id (or file name), where it has one.
+    pub id: Option<String>,
+    pub reason: String,
+}
+
+/// The valid part of a bundle, with everything that was left out.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Checked {
+    pub tasks: Vec<Task>,
+    pub strikes: Vec<StrikeEntry>,
+    pub settings: Option<AppSettings>,
+    pub updates: Vec<TaskUpdate>,
+    pub files: BTreeMap<String, Value>,
+    pub rejected: Vec<Rejected>,
+}
+
+/// What `import_all` did.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub mode: ImportMode,
+    /// Tasks added or replaced.
+    pub tasks_imported: usize,
+    pub strikes_imported: usize,
+    pub updates_imported: usize,
+    pub settings_imported: bool,
+    pub files_imported: Vec<String>,
+    pub rejected: Vec<Rejected>,
+    /// Taken of the data as it was before the import.
+    pub backup: BackupInfo,
+}
+
+/// Opens `file` (decrypting it with `passphrase` if it is sealed) and checks every
+/// record. Only a file that is not an export at all is an error. Files written by the
+/// old frontend export (`"version": "1.0"`, `usedMessages`) are accepted too.
+pub fn check(file: Value, passphrase: Option<&str>) -> Result<Checked> {
+    let file = if file.get("format").and_then(Value::as_str) == Some(SEALED_EXPORT_FORMAT) {
+        let sealed: SealedExport = serde_json::from_value(file)?;
+        let passphrase = passphrase.ok_or(Error::Locked)?;
+        serde_json::from_slice(&sealed.open(passphrase)?)?
+    } else {
+        file
+    };
+    let Value::Object(mut doc) = file else {
+        return Err(Error::Invalid("not a Shakshuka export".into()));
+    };
+    let legacy = match (
+        doc.get("format").and_then(Value::as_str),
+        doc.get("version"),
+    ) {
+        (Some(BUNDLE_FORMAT), Some(v)) => {
+            let version = v.as_u64().unwrap_or(u64::MAX);
+            if version > BUNDLE_VERSION as u64 {
+                return Err(Error::UnsupportedVersion {
+                    file: "export".into(),
+                    version,
+                });
+            }
+            false
+        }
+        (None, Some(Value::String(_))) if doc.get("tasks").is_some_and(Value::is_array) => true,
+        _ => return Err(Error::Invalid("not a Shakshuka export".into())),
+    };
+
+    // Old exports hold whatever the files held; bring each section up to date the
+    // way the loader would.
+    let mut section = |key: &str, file: &str| -> Result<Value> {
+        let value = doc.remove(key).unwrap_or(Value::Null);
+        if legacy && !value.is_null() {
+            Ok(schema::upgrade(file, value)?.1)
+        } else {
+            Ok(value)
+        }
+    };
+    let tasks = section("tasks", TASKS_FILE)?;
+    let strikes = section("strikes", STRIKES_FILE)?;
+    let settings = section("settings", SETTINGS_FILE)?;
+    let updates = section("updates", UPDATES_FILE)?;
+    let files = if legacy {
+        let mut files = serde_json::Map::new();
+        if let Some(used) = doc.remove("usedMessages").filter(|v| !v.is_null()) {
+            files.insert(USED_MESSAGES_FILE.into(), used);
+        }
+        Value::Object(files)
+    } else {
+        doc.remove("files").unwrap_or(Value::Null)
+    };
+
+    let mut checked = Checked::default();
+    let mut seen = HashSet::new();
+    for (index, value) in records(&mut checked, "tasks", tasks) {
+        let id = string_field(&value, "id");
+        let result = serde_json::from_value::<Task>(value)
+            .map_err(|e| e.to_string())
+            .and_then(check_task)
+            .and_then(|task| unique(&mut seen, &task.id).map(|_| task));
+        match result {
+            Ok(task) => checked.tasks.push(task),
+            Err(reason) => checked.reject("tasks", Some(index), id, reason),
+        }
+    }
+
+    let mut seen = HashSet::new();
+    for (index, value) in records(&mut checked, "strikes", strikes) {
+        let id = string_field(&value, "taskId");
+        let result = serde_json::from_value::<StrikeEntry>(value)
+            .map_err(|e| e.to_string())
+            .and_then(check_strike)
+            .and_then(|s| unique(&mut seen, &format!("{}@{}", s.task_id, s.ts)).map(|_| s));
+        match result {
+            Ok(strike) => checked.strikes.push(strike),
+            Err(reason) => checked.reject("strikes", Some(index), id, reason),
+        }
+    }
+
+    let mut seen = HashSet::new();
+    for (index, value) in records(&mut checked, "updates", updates) {
+        let id = string_field(&value, "updateId");
+        let result = serde_json::from_value::<TaskUpdate>(value)
+            .map_err(|e| e.to_string())
+            .and_then(|u| unique(&mut seen, &u.update_id).map(|_| u));
+        match result {
+            Ok(update) => checked.updates.push(update),
+            Err(reason) => checked.reject("updates", Some(index), id, reason),
+        }
+    }
+
+    if !settings.is_null() {
+        match serde_json::from_value::<AppSettings>(settings)
+            .map_err(|e| e.to_string())
+            .and_then(check_settings)
+        {
+            Ok(settings) => checked.settings = Some(settings),
+            Err(reason) => checked.reject("settings", None, None, reason),
+        }
+    }
+
+    match files {
+        Value::Null => {}
+        Value::Object(files) => {
+            for (name, value) in files {
+                if name == PLANNER_FILE {
+                    let mut blocks = Vec::new();
+                    for (index, value) in records(&mut checked, "planner", value) {
+                        let id = string_field(&value, "taskId");
+                        match serde_json::from_value::<ScheduledTask>(value.clone())
+                            .map_err(Error::from)
+                            .and_then(|b| b.validate())
+                        {
+                            Ok(()) => blocks.push(value),
+                            Err(e) => checked.reject("planner", Some(index), id, e.to_string()),
+                        }
+                    }
+                    checked.files.insert(name, Value::Array(blocks));
+                } else if name == BUSY_FILE {
+                    match Vec::<BusyEvent>::deserialize(&value) {
+                        Ok(_) => {
+                            checked.files.insert(name, value);
+                        }
+                        Err(e) => checked.reject("files", None, Some(name), e.to_string()),
+                    }
+                } else if LOOSE_FILES.contains(&name.as_str()) {
+                    checked.files.insert(name, value);
+                } else {
+                    let reason = format!("{name} is not a data file");
+                    checked.reject("files", None, Some(name), reason);
+                }
+            }
+        }
+        _ => checked.reject("files", None, None, "expected an object".into()),
+    }
+    Ok(checked)
+}
+
+impl Checked {
+    fn reject(&mut self, section: &str, index: Option<usize>, id: Option<String>, reason: String) {
+        self.rejected.push(Rejected {
+            section: section.into(),
+            index,
+            id,
+            reason,
+        });
+    }
+
+    /// Writes the checked records over (`Replace`) or into (`Merge`) the current data,
+    /// after a safety backup. Writes go through the stores so every window hears.
+    pub fn apply(
+        self,
+        mode: ImportMode,
+        now: i64,
+        backups: &Backups,
+        stores: &Stores,
+    ) -> Result<ImportReport> {
+        let (tasks, files) = (stores.tasks, stores.files);
+        let current = backups.snapshot(now)?;
+        let backup = backups.create(BackupKind::Safety, now)?;
+        let mut report = ImportReport {
+            mode,
+            tasks_imported: 0,
+            strikes_imported: 0,
+            updates_imported: 0,
+            settings_imported: false,
+            files_imported: Vec::new(),
+            rejected: self.rejected,
+            backup,
+        };
+
+        let (new_tasks, new_strikes, new_updates, new_files) = match mode {
+            ImportMode::Replace => {
+                report.tasks_imported = self.tasks.len();
+                report.strikes_imported = self.strikes.len();
+                report.updates_imported = self.updates.len();
+                if let Some(settings) = &self.settings {
+                    files.write(SETTINGS_FILE, &serde_json::to_value(settings)?)?;
+                    report.settings_imported = true;
+                }
+                (self.tasks, self.strikes, self.updates, self.files)
+            }
+            ImportMode::Merge => {
+                let mut merged = current.tasks;
+                for task in self.tasks {
+                    match merged.iter_mut().find(|t| t.id == task.id) {
+                        Some(old) if old.updated_at < task.updated_at => *old = task,
+                        Some(_) => continue,
+                        None => merged.push(task),
+                    }
+                    report.tasks_imported += 1;
+                }
+
+                let mut strikes = current.strikes;
+                let keys: HashSet<_> = strikes.iter().map(|s| (s.task_id.clone(), s.ts)).collect();
+                for strike in self.strikes {
+                    if !keys.contains(&(strike.task_id.clone(), strike.ts)) {
+                        strikes.push(strike);
+                        report.strikes_imported += 1;
+                    }
+                }
+                strikes.sort_by_key(|s| s.ts);
+
+                let mut updates = current.updates;
+                let ids: HashSet<_> = updates.iter().map(|u| u.update_id.clone()).collect();
+                for update in self.updates {
+                    if !ids.contains(&update.update_id) {
+                        updates.push(update);
+                        report.updates_imported += 1;
+                    }
+                }
+                updates.sort_by_key(|u| u.timestamp);
+
+                let loose = self
+                    .files
+                    .into_iter()
+                    .filter(|(name, _)| !current.files.contains_key(name))
+                    .collect();
+                (merged, strikes, updates, loose)
+            }
+        };
+        // History first, so the task changes below are recorded on top of it;
+        // `replace_all` also moves every changed task past its current revision.
+        files.write(UPDATES_FILE, &serde_json::to_value(new_updates)?)?;
+        tasks.replace_all(new_tasks)?;
+        files.write(STRIKES_FILE, &serde_json::to_value(new_strikes)?)?;
+        for (name, value) in new_files {
+            stores.write_loose(&name, &value)?;
+            report.files_imported.push(name);
+        }
+        Ok(report)
+    }
+}
+
+/// The elements of an array section, numbered; anything else is rejected whole.
+fn records(checked: &mut Checked, section: &str, value: Value) -> Vec<(usize, Value)> {
+    match value {
+        Value::Null => Vec::new(),
+        Value::Array(items) => items.into_iter().enumerate().collect(),
+        _ => {
+            checked.reject(section, None, None, "expected an array".into());
+            Vec::new()
+        }
+    }
+}
+
+fn string_field(value: &Value, key: &str) -> Option<String> {
+    value.get(key).and_then(Value::as_str).map(str::to_string)
+}
+
+fn unique(seen: &mut HashSet<String>, key: &str) -> std::result::Result<(), String> {
+    if seen.insert(key.to_string()) {
+        Ok(())
+    } else {
+        Err(format!("duplicate of an earlier record ({key})"))
+    }
+}
+
+/// The checks `create_task` applies to the editable fields, plus an id.
+pub fn check_task(task: Task) -> std::result::Result<Task, String> {
+    if task.id.trim().is_empty() {
+        return Err("id is empty".into());
+    }
+    let draft = TaskDraft {
+        title: task.title.clone(),
+        notes: task.notes.clone(),
+        due_hour: task.due_hour,
+        due_date: task.due_date.clone(),
+        tags: task.tags.clone(),
+    }
+    .validate()
+    .map_err(|e| e.to_string())?;
+    Ok(Task {
+        title: draft.title,
+        notes: draft.notes,
+        due_hour: draft.due_hour,
+        due_date: draft.due_date,
+        tags: draft.tags,
+        ..task
+    })
+}
+
+pub fn check_strike(strike: StrikeEntry) -> std::result::Result<StrikeEntry, String> {
+    if strike.task_id.trim().is_empty() {
+        return Err("taskId is empty".into());
+    }
+    if !is_valid_date(&strike.date) {
+        return Err(format!("date {:?} is not YYYY-MM-DD", strike.date));
+    }
+    Ok(strike)
+}
+
+pub fn check_settings(settings: AppSettings) -> std::result::Result<AppSettings, String> {
+    if settings.reset_hour > 23 {
+        return Err(format!("resetHour {} is not in 0-23", settings.reset_hour));
+    }
+    if timezone(&settings.timezone).is_none() {
+        return Err(format!(
+            "timezone {:?} is not an IANA timezone",
+            settings.timezone
+        ));
+    }
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::busy::BusyStore;
+    use crate::events::{self, Recorded};
+    use crate::persist::DataFiles;
+    use crate::planner::PlannerStore;
+    use crate::storage::MemoryStorage;
+    use crate::tasks::TaskStore;
+
+    fn task(id: &str, title: &str, updated_at: i64) -> Value {
+        json!({
+            "id": id, "revision": 0, "title": title, "completed": false,
+            "createdAt": 1, "updatedAt": updated_at,
+        })
+    }
+
+    #[test]
+    fn invalid_records_are_reported_and_left_out() {
+        let file = json!({
+            "format": BUNDLE_FORMAT,
+            "version": 1,
+            "exportedAt": 0,
+            "tasks": [
+                task("a", "Write", 1),
+                task("a", "Duplicate", 1),
+                task("b", "  ", 1),
+                { "id": "c", "title": "No revision" },
+                { "id": "d", "revision": 0, "title": "Late", "completed": false,
+                  "createdAt": 1, "updatedAt": 1, "dueHour": 24 },
+            ],
+            "strikes": [
+                { "taskId": "a", "date": "2025-03-01", "ts": 5 },
+                { "taskId": "a", "date": "03/01/2025", "ts": 6 },
+                { "taskId": "a", "date": "2025-03-01", "ts": 5 },
+            ],
+            "settings": { "resetHour": 6, "timezone": "Mars/Olympus" },
+            "updates": [],
+            "files": { "used-messages.json": [1], "tasks.json": [] },
+        });
+        let checked = check(file, None).unwrap();
+        assert_eq!(checked.tasks.len(), 1);
+        assert_eq!(checked.strikes.len(), 1);
+        assert_eq!(checked.settings, None);
+        assert_eq!(
+            checked.files.keys().collect::<Vec<_>>(),
+            [USED_MESSAGES_FILE]
+        );
+        let rejected: Vec<_> = checked
+            .rejected
+            .iter()
+            .map(|r| (r.section.as_str(), r.index))
+            .collect();
+        assert_eq!(
+            rejected,
+            [
+                ("tasks", Some(1)),
+                ("tasks", Some(2)),
+                ("tasks", Some(3)),
+                ("tasks", Some(4)),
+                ("strikes", Some(1)),
+                ("strikes", Some(2)),
+                ("settings", None),
+                ("files", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn legacy_and_sealed_exports_are_read() {
+        let legacy = json!({
+            "version": "1.0",
+            "exportedAt": "2025-03-01T00:00:00.000Z",
+            "tasks": [{ "id": "a", "title": "Old", "completed": false, "createdAt": 1, "updatedAt": 1 }],
+            "settings": { "resetHour": 9, "timezone": "Europe/Paris" },
+            "usedMessages": [3],
+        });
+        let checked = check(legacy, None).unwrap();
+        assert_eq!(checked.tasks[0].revision, 0);
+        assert_eq!(checked.settings.unwrap().timezone, "Europe/Paris");
+        assert!(checked.files.contains_key(USED_MESSAGES_FILE));
+
+        let bundle: Bundle = serde_json::from_value(json!({
+            "format": BUNDLE_FORMAT, "version": 1, "exportedAt": 0,
+            "tasks": [task("a", "Secret", 1)], "strikes": [], "updates": [],
+        }))
+        .unwrap();
+        let sealed =
+            serde_json::to_value(ExportFile::new(bundle, Some("correct horse")).unwrap()).unwrap();
+        assert!(!sealed.to_string().contains("Secret"));
+        assert!(matches!(check(sealed.clone(), None), Err(Error::Locked)));
+        assert!(matches!(
+            check(sealed.clone(), Some("wrong horse")),
+            Err(Error::BadPassphrase)
+        ));
+        assert_eq!(
+            check(sealed, Some("correct horse")).unwrap().tasks[0].title,
+            "Secret"
+        );
+
+        assert!(check(json!([1, 2]), None).is_err());
+        assert!(matches!(
+            check(json!({ "format": BUNDLE_FORMAT, "version": 2 }), None),
+            Err(Error::UnsupportedVersion { .. })
+        ));
+    }
+
+    #[test]
+    fn merge_keeps_newer_tasks_and_replace_overwrites() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Arc::new(MemoryStorage::default());
+        let events = Arc::new(Recorded::default());
+        let tasks = TaskStore::new(storage.clone(), events.clone());
+        let files = DataFiles::new(dir.path().to_path_buf(), storage.clone(), events.clone());
+        let planner = PlannerStore::new(dir.path().to_path_buf(), events.clone());
+        let busy = BusyStore::new(dir.path().to_path_buf(), events.clone());
+        let stores = Stores {
+            tasks: &tasks,
+            files: &files,
+            planner: &planner,
+            busy: &busy,
+        };
+        let backups = Backups::new(dir.path(), storage);
+        let current: Vec<Task> =
+            serde_json::from_value(json!([task("a", "Mine", 10), task("b", "Keep", 10)])).unwrap();
+        tasks.replace_all(current).unwrap();
+
+        let bundle = |tasks: Value| {
+            json!({
+                "format": BUNDLE_FORMAT, "version": 1, "exportedAt": 0,
+                "tasks": tasks,
+                "strikes": [{ "taskId": "c", "date": "2025-03-01", "ts": 5 }],
+                "updates": [],
+            })
+        };
+        let titles =
+            || -> Vec<String> { tasks.list().unwrap().into_iter().map(|t| t.title).collect() };
+
+        let incoming = bundle(json!([
+            task("a", "Older", 5),
+            task("b", "Newer", 20),
+            task("c", "New", 1)
+        ]));
+        let report = check(incoming.clone(), None)
+            .unwrap()
+            .apply(ImportMode::Merge, 100, &backups, &stores)
+            .unwrap();
+        assert_eq!(report.tasks_imported, 2);
+        assert_eq!(report.strikes_imported, 1);
+        assert_eq!(titles(), ["Mine", "Newer", "New"]);
+        assert_eq!(report.backup.kind, BackupKind::Safety);
+        let merged = tasks.list().unwrap();
+        assert_eq!(
+            merged.iter().map(|t| t.revision).collect::<Vec<_>>(),
+            [0, 1, 0]
+        );
+        // Creations of a and b, then the merge: b changed and c created.
+        let history = tasks.history().query(&Default::default()).unwrap();
+        assert_eq!(history.len(), 4);
+        assert!(history.iter().any(|u| u.is_creation() && u.task_id == "c"));
+        assert!(history.iter().any(|u| u.diff["title"]["old"] == "Keep"));
+
+        // Merging the same strikes again adds nothing.
+        let report = check(incoming, None)
+            .unwrap()
+            .apply(ImportMode::Merge, 200, &backups, &stores)
+            .unwrap();
+        assert_eq!(report.strikes_imported, 0);
+
+        let mut replacement = bundle(json!([task("z", "Only", 1)]));
+        replacement["files"] = json!({ PLANNER_FILE: [{ "taskId": "z", "task": {},
+            "startHour": 9, "startMinute": 0, "durationMinutes": 30, "date": "2025-03-01" }] });
+        events.0.lock().unwrap().clear();
+        check(replacement, None)
+            .unwrap()
+            .apply(ImportMode::Replace, 300, &backups, &stores)
+            .unwrap();
+        assert_eq!(titles(), ["Only"]);
+        assert_eq!(planner.list().unwrap()[0].task_id, "z");
+        let names: Vec<_> = events
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(n, _)| n.clone())
+            .collect();
+        assert!(names.iter().any(|n| n == events::PLANNER_CHANGED));
+        assert_eq!(
+            files
+                .read(STRIKES_FILE)
+                .unwrap()
+                .unwrap()
+                .as_array()
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,899 @@
+//! Busy time from an imported calendar (`.ics`): meetings shown read-only next to
+//! the planner's blocks. Events are kept as the file gives them, in their own
+//! timezone, and recurring ones are expanded on request, so a weekly meeting stays at
+//! its wall-clock time across DST changes wherever the user is.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use chrono::{
+    Datelike, Days, Duration, Months, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Weekday,
+};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::events::{self, Events};
+use crate::ical::{self, ContentLine};
+use crate::persist::{self, BUSY_FILE};
+use crate::time::{local_to_utc, timezone};
+
+/// Most occurrences followed for one recurring event, so a rule that never ends
+/// cannot run away.
+const MAX_OCCURRENCES: usize = 10_000;
+
+/// Widest range `blocks` expands, in days.
+const MAX_RANGE_DAYS: i64 = 366;
+
+const WALL_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// One VEVENT of the imported calendar, as stored in `busy-calendar.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BusyEvent {
+    pub uid: String,
+    pub summary: String,
+    /// Wall-clock start, `YYYY-MM-DDTHH:MM:SS`; midnight for all-day events.
+    pub start: String,
+    /// IANA zone of `start` (`UTC` for `Z` times). `None` for floating times and
+    /// all-day events, which follow the user's timezone.
+    pub tzid: Option<String>,
+    pub all_day: bool,
+    pub duration_minutes: i64,
+    /// The RRULE value, only kept if it can be expanded.
+    pub rrule: Option<String>,
+    /// Starts dropped from the recurrence by EXDATE or replaced by a RECURRENCE-ID
+    /// override, as wall-clock times in the same form and zone as `start`.
+    #[serde(default)]
+    pub exdates: Vec<String>,
+}
+
+/// A piece of busy time on one day, shaped like the planner's `ScheduledTask` so the
+/// day view can lay both out the same way. Events crossing midnight are split.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BusyBlock {
+    pub uid: String,
+    pub summary: String,
+    /// YYYY-MM-DD in the user's timezone.
+    pub date: String,
+    pub start_hour: u8,
+    pub start_minute: u8,
+    pub duration_minutes: u32,
+    pub all_day: bool,
+}
+
+/// What `import` made of a file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BusyImport {
+    pub events: usize,
+    pub recurring: usize,
+    /// Cancelled, free (TRANSP:TRANSPARENT) and zero-length events.
+    pub skipped: usize,
+    /// Things the user should know, e.g. a recurrence rule that could not be expanded.
+    pub notes: Vec<String>,
+}
+
+/// A DATE or DATE-TIME value with the zone it was written in.
+#[derive(Debug, Clone, PartialEq)]
+struct Stamp {
+    local: NaiveDateTime,
+    tzid: Option<String>,
+    all_day: bool,
+}
+
+impl Stamp {
+    /// `value` is `YYYYMMDD`, `YYYYMMDDTHHMMSS` or the same ending in `Z`; `tzid`
+    /// is the already resolved TZID parameter, if any.
+    fn parse(value: &str, tzid: Option<&str>) -> Option<Self> {
+        let value = value.trim();
+        if value.len() == 8 {
+            let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+            return Some(Self {
+                local: date.and_time(NaiveTime::MIN),
+                tzid: None,
+                all_day: true,
+            });
+        }
+        let (value, tzid) = match value.strip_suffix('Z') {
+            Some(utc) => (utc, Some("UTC")),
+            None => (value, tzid),
+        };
+        Some(Self {
+            local: NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?,
+            tzid: tzid.map(str::to_string),
+            all_day: false,
+        })
+    }
+
+    /// This time as wall-clock time in the zone `tzid` (floating: `tz`).
+    fn wall_in(&self, tzid: Option<&str>, tz: Tz) -> NaiveDateTime {
+        if self.all_day || self.tzid.as_deref() == tzid {
+            return self.local;
+        }
+        local_to_utc(zone(self.tzid.as_deref(), tz), self.local)
+            .with_timezone(&zone(tzid, tz))
+            .naive_local()
+    }
+}
+
+fn zone(tzid: Option<&str>, tz: Tz) -> Tz {
+    tzid.and_then(timezone).unwrap_or(tz)
+}
+
+/// A DURATION value (§3.3.6) in whole minutes. Negative durations are not busy time.
+fn parse_duration(value: &str) -> Option<i64> {
+    let rest = value.trim().trim_start_matches('+').strip_prefix('P')?;
+    let mut seconds = 0i64;
+    let mut number = String::new();
+    let mut in_time = false;
+    for c in rest.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' if number.is_empty() => in_time = true,
+            _ => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                seconds += n * match (c, in_time) {
+                    ('W', false) => 7 * 86_400,
+                    ('D', false) => 86_400,
+                    ('H', true) => 3_600,
+                    ('M', true) => 60,
+                    ('S', true) => 1,
+                    _ => return None,
+                };
+            }
+        }
+    }
+    number.is_empty().then_some(seconds / 60)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Freq {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// The subset of RRULE (§3.3.10) calendar apps write for meetings. Anything else is
+/// refused by `parse`, and the event keeps only its first occurrence.
+#[derive(Debug, Clone, PartialEq)]
+struct Rule {
+    freq: Freq,
+    interval: u32,
+    count: Option<usize>,
+    until: Option<Stamp>,
+    /// `(ordinal, day)`; ordinal 0 means every such day in the period.
+    by_day: Vec<(i32, Weekday)>,
+    by_month_day: Vec<i32>,
+    by_month: Vec<u32>,
+    week_start: Weekday,
+}
+
+fn weekday(code: &str) -> Option<Weekday> {
+    Some(match code {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+impl Rule {
+    fn parse(value: &str) -> std::result::Result<Self, String> {
+        let mut rule = Rule {
+            freq: Freq::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            by_month: Vec::new(),
+            week_start: Weekday::Mon,
+        };
+        let mut freq = None;
+        let bad = |part: &str| format!("{part} is not understood");
+        for part in value.trim().split(';').filter(|p| !p.is_empty()) {
+            let (key, val) = part.split_once('=').ok_or_else(|| bad(part))?;
+            let list = || val.split(',').map(str::trim);
+            match key.trim().to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    freq = Some(match val.to_ascii_uppercase().as_str() {
+                        "DAILY" => Freq::Daily,
+                        "WEEKLY" => Freq::Weekly,
+                        "MONTHLY" => Freq::Monthly,
+                        "YEARLY" => Freq::Yearly,
+                        _ => return Err(format!("FREQ={val} is not supported")),
+                    })
+                }
+                "INTERVAL" => {
+                    rule.interval = val
+                        .parse()
+                        .ok()
+                        .filter(|&n| n > 0)
+                        .ok_or_else(|| bad(part))?
+                }
+                "COUNT" => rule.count = Some(val.parse().map_err(|_| bad(part))?),
+                "UNTIL" => rule.until = Some(Stamp::parse(val, None).ok_or_else(|| bad(part))?),
+                "BYDAY" => {
+                    for item in list() {
+                        let split = item.len().checked_sub(2).ok_or_else(|| bad(part))?;
+                        let (ordinal, day) = item.split_at(split);
+                        let day = weekday(&day.to_ascii_uppercase()).ok_or_else(|| bad(part))?;
+                        let ordinal = match ordinal {
+                            "" => 0,
+                            n => n
+                                .parse()
+                                .ok()
+                                .filter(|&n: &i32| n != 0)
+                                .ok_or_else(|| bad(part))?,
+                        };
+                        rule.by_day.push((ordinal, day));
+                    }
+                }
+                "BYMONTHDAY" => {
+                    for item in list() {
+                        let day = item
+                            .parse()
+                            .ok()
+                            .filter(|&d: &i32| d != 0 && d.abs() <= 31)
+                            .ok_or_else(|| bad(part))?;
+                        rule.by_month_day.push(day);
+                    }
+                }
+                "BYMONTH" => {
+                    for item in list() {
+                        let month = item
+                            .parse()
+                            .ok()
+                            .filter(|m| (1..=12).contains(m))
+                            .ok_or_else(|| bad(part))?;
+                        rule.by_month.push(month);
+                    }
+                }
+                "WKST" => {
+                    rule.week_start = weekday(&val.to_ascii_uppercase()).ok_or_else(|| bad(part))?
+                }
+                other => return Err(format!("{other} is not supported")),
+            }
+        }
+        rule.freq = freq.ok_or("FREQ is missing")?;
+        let ordinals = rule.by_day.iter().any(|&(n, _)| n != 0);
+        if ordinals && matches!(rule.freq, Freq::Daily | Freq::Weekly) {
+            return Err(bad("BYDAY with an ordinal"));
+        }
+        let by_days = !rule.by_day.is_empty() || !rule.by_month_day.is_empty();
+        if rule.freq == Freq::Yearly && rule.by_month.is_empty() && by_days {
+            return Err("FREQ=YEARLY without BYMONTH is not supported".into());
+        }
+        Ok(rule)
+    }
+
+    /// Wall-clock starts from `start` on, up to `limit`, in the zone `tzid`.
+    fn expand(
+        &self,
+        start: NaiveDateTime,
+        tzid: Option<&str>,
+        tz: Tz,
+        limit: NaiveDateTime,
+    ) -> Vec<NaiveDateTime> {
+        let until = self.until.as_ref().map(|u| match u.all_day {
+            true => u.local + Duration::days(1) - Duration::seconds(1),
+            false => u.wall_in(tzid, tz),
+        });
+        let cap = self.count.unwrap_or(MAX_OCCURRENCES).min(MAX_OCCURRENCES);
+        // DTSTART is always the first occurrence, whether or not the rule matches it.
+        let mut out = vec![start];
+        'periods: for k in 0.. {
+            let Some((begin, dates)) = self.period(start.date(), k) else {
+                break;
+            };
+            if begin > limit.date() {
+                break;
+            }
+            for date in dates {
+                let at = date.and_time(start.time());
+                if at <= start {
+                    continue;
+                }
+                if out.len() >= cap || until.is_some_and(|u| at > u) || at > limit {
+                    break 'periods;
+                }
+                out.push(at);
+            }
+        }
+        out.truncate(cap);
+        out
+    }
+
+    /// The first day of the `k`th period after the one holding `first`, and the
+    /// dates the rule picks in it, in order.
+    fn period(&self, first: NaiveDate, k: u32) -> Option<(NaiveDate, Vec<NaiveDate>)> {
+        let step = k.checked_mul(self.interval)?;
+        let (begin, mut dates) = match self.freq {
+            Freq::Daily => {
+                let day = first.checked_add_days(Days::new(step.into()))?;
+                (day, vec![day])
+            }
+            Freq::Weekly => {
+                let offset = first.weekday().days_since(self.week_start);
+                let begin = first
+                    .checked_sub_days(Days::new(offset.into()))?
+                    .checked_add_days(Days::new(u64::from(step) * 7))?;
+                let days: Vec<_> = match self.by_day.is_empty() {
+                    true => vec![first.weekday()],
+                    false => self.by_day.iter().map(|&(_, d)| d).collect(),
+                };
+                let dates = (0..7)
+                    .filter_map(|i| begin.checked_add_days(Days::new(i)))
+                    .filter(|d| days.contains(&d.weekday()))
+                    .collect();
+                (begin, dates)
+            }
+            Freq::Monthly => {
+                let begin = first.with_day(1)?.checked_add_months(Months::new(step))?;
+                (begin, self.month_days(begin, first.day()))
+            }
+            Freq::Yearly => {
+                let begin =
+                    NaiveDate::from_ymd_opt(first.year().checked_add_unsigned(step)?, 1, 1)?;
+                let months = match self.by_month.is_empty() {
+                    true => vec![first.month()],
+                    false => self.by_month.clone(),
+                };
+                let dates = months
+                    .iter()
+                    .filter_map(|&m| begin.with_month(m))
+                    .flat_map(|m| self.month_days(m, first.day()))
+                    .collect();
+                (begin, dates)
+            }
+        };
+        if !self.by_month.is_empty() {
+            dates.retain(|d| self.by_month.contains(&d.month()));
+        }
+        if self.freq == Freq::Daily {
+            dates.retain(|d| {
+                (self.by_day.is_empty() || self.by_day.iter().any(|&(_, w)| w == d.weekday()))
+                    && (self.by_month_day.is_empty()
+                        || self
+                            .by_month_day
+                            .iter()
+                            .any(|&n| month_day(*d, n) == Some(d.day())))
+            });
+        }
+        dates.sort();
+        dates.dedup();
+        Some((begin, dates))
+    }
+
+    /// The days BYMONTHDAY and BYDAY pick in the month starting `month`, or
+    /// `default_day` when neither is given.
+    fn month_days(&self, month: NaiveDate, default_day: u32) -> Vec<NaiveDate> {
+        let mut days: Vec<u32> = if !self.by_month_day.is_empty() {
+            let mut days: Vec<_> = self
+                .by_month_day
+                .iter()
+                .filter_map(|&n| month_day(month, n))
+                .collect();
+            if !self.by_day.is_empty() {
+                days.retain(|&d| {
+                    month
+                        .with_day(d)
+                        .is_some_and(|d| self.by_day.iter().any(|&(_, w)| w == d.weekday()))
+                });
+            }
+            days
+        } else if !self.by_day.is_empty() {
+            let mut days = Vec::new();
+            for &(n, day) in &self.by_day {
+                let all: Vec<u32> = (1..=days_in_month(month))
+                    .filter(|&d| month.with_day(d).is_some_and(|d| d.weekday() == day))
+                    .collect();
+                let pick = match n {
+                    0 => {
+                        days.extend(&all);
+                        continue;
+                    }
+                    n if n > 0 => all.get(n as usize - 1),
+                    n => all
+                        .len()
+                        .checked_sub(n.unsigned_abs() as usize)
+                        .and_then(|i| all.get(i)),
+                };
+                days.extend(pick);
+            }
+            days
+        } else {
+            vec![default_day]
+        };
+        days.retain(|&d| d <= days_in_month(month));
+        days.into_iter().filter_map(|d| month.with_day(d)).collect()
+    }
+}
+
+fn days_in_month(date: NaiveDate) -> u32 {
+    date.with_day(1)
+        .and_then(|d| d.checked_add_months(Months::new(1)))
+        .and_then(|d| d.pred_opt())
+        .map_or(31, |d| d.day())
+}
+
+/// BYMONTHDAY `n` in the month of `date`, counting from the end when negative.
+fn month_day(date: NaiveDate, n: i32) -> Option<u32> {
+    let last = days_in_month(date) as i32;
+    let day = if n > 0 { n } else { last + 1 + n };
+    (1..=last).contains(&day).then_some(day as u32)
+}
+
+impl BusyEvent {
+    /// Wall-clock starts, in the event's zone, up to `limit`; excluded dates dropped.
+    fn starts(&self, tz: Tz, limit: NaiveDateTime) -> Vec<NaiveDateTime> {
+        let Ok(start) = NaiveDateTime::parse_from_str(&self.start, WALL_FORMAT) else {
+            return Vec::new();
+        };
+        let mut starts = match self.rrule.as_deref().map(Rule::parse) {
+            Some(Ok(rule)) => rule.expand(start, self.tzid.as_deref(), tz, limit),
+            _ => vec![start],
+        };
+        starts.retain(|s| !self.exdates.contains(&s.format(WALL_FORMAT).to_string()));
+        starts
+    }
+}
+
+/// The busy time of `events` on the days `from..=to` in `tz`, ordered by day and
+/// start.
+pub fn blocks(events: &[BusyEvent], from: NaiveDate, to: NaiveDate, tz: Tz) -> Vec<BusyBlock> {
+    let midnight = |d: NaiveDate| local_to_utc(tz, d.and_time(NaiveTime::MIN));
+    let range_start = midnight(from);
+    let Some(range_end) = to.succ_opt().map(midnight) else {
+        return Vec::new();
+    };
+    let mut out = Vec::new();
+    for event in events {
+        let event_tz = zone(event.tzid.as_deref(), tz);
+        let limit = range_end.with_timezone(&event_tz).naive_local();
+        for start in event.starts(tz, limit) {
+            let (begin, end) = if event.all_day {
+                // Whole days in the user's timezone, however long they are.
+                let end = start + Duration::minutes(event.duration_minutes);
+                (local_to_utc(tz, start), local_to_utc(tz, end))
+            } else {
+                let begin = local_to_utc(event_tz, start);
+                (begin, begin + Duration::minutes(event.duration_minutes))
+            };
+            if end <= range_start || begin >= range_end {
+                continue;
+            }
+            let mut at = begin;
+            while at < end {
+                let local = at.with_timezone(&tz).naive_local();
+                let Some(next_day) = local.date().succ_opt() else {
+                    break;
+                };
+                let piece_end = end.min(midnight(next_day));
+                let minutes = (piece_end - at).num_minutes();
+                if minutes <= 0 {
+                    break;
+                }
+                if (from..=to).contains(&local.date()) {
+                    out.push(BusyBlock {
+                        uid: event.uid.clone(),
+                        summary: event.summary.clone(),
+                        date: local.date().format("%Y-%m-%d").to_string(),
+                        start_hour: local.hour() as u8,
+                        start_minute: local.minute() as u8,
+                        duration_minutes: minutes as u32,
+                        all_day: event.all_day,
+                    });
+                }
+                at = piece_end;
+            }
+        }
+    }
+    out.sort_by(|a, b| {
+        (&a.date, !a.all_day, a.start_hour, a.start_minute).cmp(&(
+            &b.date,
+            !b.all_day,
+            b.start_hour,
+            b.start_minute,
+        ))
+    });
+    out
+}
+
+/// The VEVENTs of an iCalendar document. Floating times in EXDATE and RECURRENCE-ID
+/// are read in `tz`. Fails only if `text` is not iCalendar at all.
+pub fn parse(text: &str, tz: Tz) -> Result<(Vec<BusyEvent>, BusyImport)> {
+    let lines = ical::content_lines(text);
+    if !lines
+        .iter()
+        .any(|l| l.name == "BEGIN" && l.value.trim().eq_ignore_ascii_case("VCALENDAR"))
+    {
+        return Err(Error::Invalid("not an iCalendar file".into()));
+    }
+
+    // Components: VEVENTs (minus their VALARMs) and the TZIDs VTIMEZONEs map to
+    // IANA names, which Outlook writes as X-LIC-LOCATION or not at all.
+    let mut components: Vec<Vec<&ContentLine>> = Vec::new();
+    let mut zones: HashMap<String, String> = HashMap::new();
+    let mut stack: Vec<String> = Vec::new();
+    let mut tz_id = None;
+    for line in &lines {
+        let value = line.value.trim().to_ascii_uppercase();
+        match line.name.as_str() {
+            "BEGIN" => {
+                if value == "VEVENT" && stack.last().is_some_and(|c| c == "VCALENDAR") {
+                    components.push(Vec::new());
+                }
+                stack.push(value);
+                continue;
+            }
+            "END" => {
+                stack.pop();
+                continue;
+            }
+            _ => {}
+        }
+        match stack.last().map(String::as_str) {
+            Some("VEVENT") => {
+                if let Some(event) = components.last_mut() {
+                    event.push(line);
+                }
+            }
+            Some("VTIMEZONE") => match line.name.as_str() {
+                "TZID" => tz_id = Some(line.value.trim().to_string()),
+                "X-LIC-LOCATION" => {
+                    if let Some(id) = tz_id.clone() {
+                        zones.insert(id, line.value.trim().to_string());
+                    }
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    let mut report = BusyImport::default();
+    let mut unknown_zones = Vec::new();
+    let mut resolve = |tzid: &str| -> Option<String> {
+        let name = tzid.trim().trim_start_matches('/');
+        let found = timezone(name)
+            .or_else(|| zones.get(tzid.trim()).and_then(|z| timezone(z)))
+            .map(|z| z.name().to_string());
+        if found.is_none() && !unknown_zones.contains(&tzid.to_string()) {
+            unknown_zones.push(tzid.to_string());
+        }
+        found
+    };
+    let mut stamps = |line: &ContentLine| -> Vec<Stamp> {
+        let tzid = line.param("TZID").and_then(&mut resolve);
+        line.value
+            .split(',')
+            .filter_map(|v| Stamp::parse(v, tzid.as_deref()))
+            .collect()
+    };
+
+    struct Parsed {
+        event: BusyEvent,
+        recurrence_id: Option<Stamp>,
+        busy: bool,
+    }
+    let mut parsed = Vec::new();
+    for (i, props) in components.iter().enumerate() {
+        let get = |name: &str| props.iter().find(|l| l.name == name).copied();
+        let text = |name: &str| get(name).map(|l| ical::unescape_text(l.value.trim()));
+        let summary = text("SUMMARY")
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| "Busy".into());
+        let Some(start) = get("DTSTART").and_then(|l| stamps(l).into_iter().next()) else {
+            report
+                .notes
+                .push(format!("{summary}: no usable DTSTART; skipped"));
+            continue;
+        };
+        let duration = if let Some(end) = get("DTEND").and_then(|l| stamps(l).into_iter().next()) {
+            let instant = |s: &Stamp| local_to_utc(zone(s.tzid.as_deref(), tz), s.local);
+            match start.all_day {
+                true => (end.local - start.local).num_minutes(),
+                false => (instant(&end) - instant(&start)).num_minutes(),
+            }
+        } else if let Some(duration) = get("DURATION") {
+            parse_duration(&duration.value).unwrap_or(0)
+        } else if start.all_day {
+            24 * 60
+        } else {
+            0
+        };
+        let rrule = get("RRULE").map(|l| l.value.trim().to_string());
+        let rrule = match rrule.as_deref().map(Rule::parse) {
+            Some(Err(reason)) => {
+                report.notes.push(format!(
+                    "{summary}: {reason}; only the first occurrence is shown"
+                ));
+                None
+            }
+            _ => rrule,
+        };
+        let tzid = start.tzid.clone();
+        let exdates = props
+            .iter()
+            .filter(|l| l.name == "EXDATE")
+            .flat_map(|l| stamps(l))
+            .map(|s| {
+                s.wall_in(tzid.as_deref(), tz)
+                    .format(WALL_FORMAT)
+                    .to_string()
+            })
+            .collect();
+        let status = text("STATUS").unwrap_or_default();
+        let transp = text("TRANSP").unwrap_or_default();
+        parsed.push(Parsed {
+            event: BusyEvent {
+                uid: text("UID")
+                    .filter(|u| !u.is_empty())
+                    .unwrap_or_else(|| format!("event-{i}")),
+                summary,
+                start: start.local.format(WALL_FORMAT).to_string(),
+                tzid,
+                all_day: start.all_day,
+                duration_minutes: duration,
+                rrule,
+                exdates,
+            },
+            recurrence_id: get("RECURRENCE-ID").and_then(|l| stamps(l).into_iter().next()),
+            busy: !status.eq_ignore_ascii_case("CANCELLED")
+                && !transp.eq_ignore_ascii_case("TRANSPARENT")
+                && duration > 0,
+        });
+    }
+
+    // An override replaces one occurrence of its series, even when it cancels it.
+    let overrides: Vec<(String, Stamp)> = parsed
+        .iter()
+        .filter_map(|p| Some((p.event.uid.clone(), p.recurrence_id.clone()?)))
+        .collect();
+    for (uid, id) in overrides {
+        if let Some(series) = parsed
+            .iter_mut()
+            .find(|p| p.event.uid == uid && p.recurrence_id.is_none())
+        {
+            let series = &mut series.event;
+            let wall = id.wall_in(series.tzid.as_deref(), tz).format(WALL_FORMAT);
+            series.exdates.push(wall.to_string());
+        }
+    }
+
+    let mut events = Vec::new();
+    for p in parsed {
+        if !p.busy {
+            report.skipped += 1;
+            continue;
+        }
+        report.events += 1;
+        if p.event.rrule.is_some() {
+            report.recurring += 1;
+        }
+        events.push(p.event);
+    }
+    for tzid in unknown_zones {
+        report.notes.push(format!(
+            "unknown timezone {tzid:?}; its times are read as local time"
+        ));
+    }
+    Ok((events, report))
+}
+
+/// The imported calendar, kept in `busy-calendar.json`. Each import replaces the
+/// last one.
+#[derive(Clone)]
+pub struct BusyStore {
+    path: PathBuf,
+    events: Arc<dyn Events>,
+    lock: Arc<Mutex<()>>,
+}
+
+impl BusyStore {
+    pub fn new(dir: PathBuf, events: Arc<dyn Events>) -> Self {
+        Self {
+            path: dir.join(BUSY_FILE),
+            events,
+            lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    pub fn list(&self) -> Result<Vec<BusyEvent>> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(persist::load_json(&self.path, self.events.as_ref())?.unwrap_or_default())
+    }
+
+    /// Replaces the stored calendar with the events of `ics`; `tz` is the user's.
+    pub fn import(&self, ics: &str, tz: Tz) -> Result<BusyImport> {
+        let (events, report) = parse(ics, tz)?;
+        self.write(&events, &report)?;
+        Ok(report)
+    }
+
+    pub fn clear(&self) -> Result<()> {
+        self.write(&[], &BusyImport::default())
+    }
+
+    /// Puts back a calendar taken from a backup or export.
+    pub fn replace(&self, events: Vec<BusyEvent>) -> Result<()> {
+        let report = BusyImport {
+            events: events.len(),
+            recurring: events.iter().filter(|e| e.rrule.is_some()).count(),
+            ..Default::default()
+        };
+        self.write(&events, &report)
+    }
+
+    fn write(&self, events: &[BusyEvent], report: &BusyImport) -> Result<()> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        persist::write_json(&self.path, &events)?;
+        self.events
+            .emit(events::BUSY_CHANGED, serde_json::to_value(report)?);
+        Ok(())
+    }
+
+    /// Busy blocks for the days `from..=to` (YYYY-MM-DD) in `tz`.
+    pub fn blocks(&self, from: &str, to: &str, tz: Tz) -> Result<Vec<BusyBlock>> {
+        let date = |s: &str| {
+            NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .map_err(|_| Error::Invalid(format!("date {s:?} is not YYYY-MM-DD")))
+        };
+        let (from, to) = (date(from)?, date(to)?);
+        if to < from || (to - from).num_days() >= MAX_RANGE_DAYS {
+            return Err(Error::Invalid(format!(
+                "{from}..{to} is not a range of 1-{MAX_RANGE_DAYS} days"
+            )));
+        }
+        Ok(blocks(&self.list()?, from, to, tz))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ics(events: &str) -> String {
+        format!("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n{events}END:VCALENDAR\r\n")
+    }
+
+    fn day(events: &[BusyEvent], date: &str, tz: Tz) -> Vec<(String, u8, u8, u32)> {
+        let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap();
+        blocks(events, date, date, tz)
+            .into_iter()
+            .map(|b| (b.summary, b.start_hour, b.start_minute, b.duration_minutes))
+            .collect()
+    }
+
+    #[test]
+    fn weekly_meetings_keep_their_wall_clock_time_across_dst() {
+        let london: Tz = "Europe/London".parse().unwrap();
+        let text = ics(concat!(
+            "BEGIN:VTIMEZONE\r\nTZID:Eastern Standard Time\r\n",
+            "X-LIC-LOCATION:America/New_York\r\nEND:VTIMEZONE\r\n",
+            "BEGIN:VEVENT\r\nUID:standup\r\nSUMMARY:Stand\\, up\r\n",
+            "DTSTART;TZID=Eastern Standard Time:20250303T090000\r\n",
+            "DTEND;TZID=Eastern Standard Time:20250303T093000\r\n",
+            "RRULE:FREQ=WEEKLY;BYDAY=MO,TH;UNTIL=20250404T000000Z\r\n",
+            "EXDATE;TZID=Eastern Standard Time:20250306T090000\r\n",
+            "BEGIN:VALARM\r\nTRIGGER:-PT10M\r\nEND:VALARM\r\nEND:VEVENT\r\n",
+            "BEGIN:VEVENT\r\nUID:standup\r\nSUMMARY:Stand-up (moved)\r\n",
+            "RECURRENCE-ID:20250317T130000Z\r\n",
+            "DTSTART:20250317T150000Z\r\nDURATION:PT1H\r\nEND:VEVENT\r\n",
+        ));
+        let (events, report) = parse(&text, london).unwrap();
+        assert_eq!((report.events, report.recurring, report.skipped), (2, 1, 0));
+        assert!(report.notes.is_empty(), "{:?}", report.notes);
+
+        // EST, GMT: 14:00 in London.
+        assert_eq!(
+            day(&events, "2025-03-03", london),
+            [("Stand, up".into(), 14, 0, 30)]
+        );
+        // Excluded.
+        assert!(day(&events, "2025-03-06", london).is_empty());
+        // New York is on EDT, London still on GMT: 13:00.
+        assert_eq!(
+            day(&events, "2025-03-10", london),
+            [("Stand, up".into(), 13, 0, 30)]
+        );
+        // The override replaces that Monday's occurrence.
+        assert_eq!(
+            day(&events, "2025-03-17", london),
+            [("Stand-up (moved)".into(), 15, 0, 60)]
+        );
+        // Both on summer time: 14:00 again.
+        assert_eq!(
+            day(&events, "2025-03-31", london),
+            [("Stand, up".into(), 14, 0, 30)]
+        );
+        // UNTIL has passed.
+        assert!(day(&events, "2025-04-07", london).is_empty());
+    }
+
+    #[test]
+    fn monthly_rules_counts_and_all_day_events() {
+        let utc: Tz = "UTC".parse().unwrap();
+        let text = ics(concat!(
+            "BEGIN:VEVENT\r\nUID:retro\r\nSUMMARY:Retro\r\nDTSTART:20250131T160000\r\n",
+            "DURATION:PT1H30M\r\nRRULE:FREQ=MONTHLY;BYDAY=-1FR;COUNT=3\r\nEND:VEVENT\r\n",
+            "BEGIN:VEVENT\r\nUID:off\r\nSUMMARY:Offsite\r\nDTSTART;VALUE=DATE:20250212\r\n",
+            "DTEND;VALUE=DATE:20250214\r\nEND:VEVENT\r\n",
+            "BEGIN:VEVENT\r\nUID:gone\r\nSUMMARY:Cancelled\r\nSTATUS:CANCELLED\r\n",
+            "DTSTART:20250212T100000Z\r\nDURATION:PT1H\r\nEND:VEVENT\r\n",
+            "BEGIN:VEVENT\r\nUID:odd\r\nSUMMARY:Odd\r\nDTSTART:20250212T100000Z\r\n",
+            "DURATION:PT1H\r\nRRULE:FREQ=HOURLY\r\nEND:VEVENT\r\n",
+        ));
+        let (events, report) = parse(&text, utc).unwrap();
+        assert_eq!((report.events, report.recurring, report.skipped), (3, 1, 1));
+        assert_eq!(
+            report.notes,
+            ["Odd: FREQ=HOURLY is not supported; only the first occurrence is shown"]
+        );
+
+        let from = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let to = NaiveDate::from_ymd_opt(2025, 12, 31).unwrap();
+        let all = blocks(&events, from, to, utc);
+        let retro: Vec<_> = all
+            .iter()
+            .filter(|b| b.uid == "retro")
+            .map(|b| b.date.as_str())
+            .collect();
+        assert_eq!(retro, ["2025-01-31", "2025-02-28", "2025-03-28"]);
+        let offsite: Vec<_> = all
+            .iter()
+            .filter(|b| b.all_day)
+            .map(|b| (b.date.as_str(), b.duration_minutes))
+            .collect();
+        assert_eq!(offsite, [("2025-02-12", 1440), ("2025-02-13", 1440)]);
+        assert_eq!(all.iter().filter(|b| b.uid == "odd").count(), 1);
+    }
+
+    #[test]
+    fn events_past_midnight_are_split_and_the_store_round_trips() {
+        let tokyo: Tz = "Asia/Tokyo".parse().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let store = BusyStore::new(dir.path().to_path_buf(), Arc::new(()));
+        assert!(store.import("not a calendar", tokyo).is_err());
+
+        let text = ics(concat!(
+            "BEGIN:VEVENT\r\nUID:late\r\nSUMMARY:Launch\r\nDTSTART:20250301T140000Z\r\n",
+            "DTEND:20250301T160000Z\r\nEND:VEVENT\r\n",
+        ));
+        store.import(&text, tokyo).unwrap();
+        // 23:00-01:00 in Tokyo.
+        let got: Vec<_> = store
+            .blocks("2025-03-01", "2025-03-02", tokyo)
+            .unwrap()
+            .into_iter()
+            .map(|b| (b.date, b.start_hour, b.duration_minutes))
+            .collect();
+        assert_eq!(
+            got,
+            [("2025-03-01".into(), 23, 60), ("2025-03-02".into(), 0, 60)]
+        );
+        assert!(store.blocks("2025-03-02", "2025-03-01", tokyo).is_err());
+
+        store.clear().unwrap();
+        assert!(store.list().unwrap().is_empty());
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("PT1H30M"), Some(90));
+        assert_eq!(parse_duration("P1DT2H"), Some(26 * 60));
+        assert_eq!(parse_duration("P1W"), Some(7 * 24 * 60));
+        assert_eq!(parse_duration("-PT15M"), None);
+        assert_eq!(parse_duration("PT"), Some(0));
+    }
+}
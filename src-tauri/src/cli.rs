@@ -0,0 +1,81 @@
+use std::path::PathBuf;
+
+/// Command-line options. Anything unrecognised is ignored, since launchers
+/// (autostart entries, macOS `-psn_*`) pass arguments of their own.
+#[derive(Debug, Default, Clone)]
+pub struct Cli {
+    /// `--data-dir <path>` replaces the platform app data directory.
+    pub data_dir: Option<PathBuf>,
+    /// `--storage json|sqlite|memory` overrides the backend chosen in settings.
+    pub storage: Option<String>,
+    /// `--profile <name>` picks the profile (a subdirectory of the data directory)
+    /// instead of the last one used.
+    pub profile: Option<String>,
+    /// A one-shot maintenance command; the app exits instead of opening a window.
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// `migrate [--check]`: upgrade data files to the current schema, or with
+    /// `--check` only report what would be upgraded.
+    Migrate { check: bool },
+    /// `doctor [--repair]`: report orphaned, duplicate and invalid records, and with
+    /// `--repair` fix them after a backup.
+    Doctor { repair: bool },
+}
+
+impl Cli {
+    pub fn parse() -> Self {
+        Self::from_args(std::env::args().skip(1))
+    }
+
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Self {
+        let mut cli = Cli::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+            let mut value = || inline.clone().or_else(|| args.next());
+            match flag.as_str() {
+                "--data-dir" => cli.data_dir = value().map(PathBuf::from),
+                "--storage" => cli.storage = value(),
+                "--profile" => cli.profile = value(),
+                "migrate" if cli.command.is_none() => {
+                    cli.command = Some(Command::Migrate { check: false })
+                }
+                "doctor" if cli.command.is_none() => {
+                    cli.command = Some(Command::Doctor { repair: false })
+                }
+                "--repair" => {
+                    if let Some(Command::Doctor { repair }) = &mut cli.command {
+                        *repair = true;
+                    }
+                }
+                "--check" => {
+                    if let Some(Command::Migrate { check }) = &mut cli.command {
+                        *check = true;
+                    }
+                }
+                _ => {}
+            }
+        }
+        cli
+    }
+}
+
+/// `args` without `--profile`, for relaunching into the profile that was switched to.
+pub fn without_profile(args: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut kept = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--profile" {
+            args.next();
+        } else if !arg.starts_with("--profile=") {
+            kept.push(arg);
+        }
+    }
+    kept
+}
@@ -0,0 +1,46 @@
+use tauri::State;
+
+use crate::backups::{BackupInfo, BackupKind, Backups, RestorePreview, Stores};
+use crate::busy::BusyStore;
+use crate::error::Result;
+use crate::persist::DataFiles;
+use crate::planner::PlannerStore;
+use crate::tasks::TaskStore;
+use crate::time::now_ms;
+
+#[tauri::command]
+pub async fn list_backups(backups: State<'_, Backups>) -> Result<Vec<BackupInfo>> {
+    backups.list()
+}
+
+#[tauri::command]
+pub async fn create_backup(backups: State<'_, Backups>) -> Result<BackupInfo> {
+    backups.create(BackupKind::Manual, now_ms())
+}
+
+#[tauri::command]
+pub async fn preview_restore(
+    backups: State<'_, Backups>,
+    backup_id: String,
+) -> Result<RestorePreview> {
+    backups.preview(&backup_id)
+}
+
+/// Returns the safety backup taken of the data that was replaced.
+#[tauri::command]
+pub async fn restore_backup(
+    backups: State<'_, Backups>,
+    tasks: State<'_, TaskStore>,
+    files: State<'_, DataFiles>,
+    planner: State<'_, PlannerStore>,
+    busy: State<'_, BusyStore>,
+    backup_id: String,
+) -> Result<BackupInfo> {
+    let stores = Stores {
+        tasks: &tasks,
+        files: &files,
+        planner: &planner,
+        busy: &busy,
+    };
+    backups.restore(&backup_id, &stores)
+}
@@ -0,0 +1,52 @@
+use serde_json::Value;
+use tauri::{AppHandle, Manager, State};
+
+use crate::backups::{Backups, Stores};
+use crate::bundle::{self, Bundle, ExportFile, ImportMode, ImportReport};
+use crate::busy::BusyStore;
+use crate::crypto::Vault;
+use crate::error::{Error, Result};
+use crate::persist::DataFiles;
+use crate::planner::PlannerStore;
+use crate::tasks::TaskStore;
+use crate::time::now_ms;
+
+/// With encryption on, the export is sealed and `passphrase` (the one in use) is
+/// required; without it the call fails with `locked`.
+#[tauri::command]
+pub async fn export_all(
+    vault: State<'_, Vault>,
+    backups: State<'_, Backups>,
+    passphrase: Option<String>,
+) -> Result<ExportFile> {
+    let passphrase = match (vault.status()?.enabled, passphrase) {
+        (false, _) => None,
+        (true, None) => return Err(Error::Locked),
+        (true, Some(passphrase)) => {
+            vault.check(&passphrase)?;
+            Some(passphrase)
+        }
+    };
+    let bundle = Bundle::from(backups.snapshot(now_ms())?);
+    ExportFile::new(bundle, passphrase.as_deref())
+}
+
+/// `passphrase` opens a sealed export; a sealed one without it fails with `locked`.
+#[tauri::command]
+pub async fn import_all(
+    app: AppHandle,
+    backups: State<'_, Backups>,
+    file: Value,
+    mode: ImportMode,
+    passphrase: Option<String>,
+) -> Result<ImportReport> {
+    let (tasks, files) = (app.state::<TaskStore>(), app.state::<DataFiles>());
+    let (planner, busy) = (app.state::<PlannerStore>(), app.state::<BusyStore>());
+    let stores = Stores {
+        tasks: &tasks,
+        files: &files,
+        planner: &planner,
+        busy: &busy,
+    };
+    bundle::check(file, passphrase.as_deref())?.apply(mode, now_ms(), &backups, &stores)
+}
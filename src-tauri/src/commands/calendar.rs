@@ -0,0 +1,17 @@
+use tauri::State;
+
+use crate::error::Result;
+use crate::ical;
+use crate::persist::DataFiles;
+use crate::tasks::TaskStore;
+use crate::time::now_ms;
+
+/// The tasks with a deadline as an `.ics` file of VTODOs, in the user's timezone.
+#[tauri::command]
+pub async fn export_tasks_ics(
+    tasks: State<'_, TaskStore>,
+    files: State<'_, DataFiles>,
+) -> Result<String> {
+    let tz = files.settings()?.tz();
+    Ok(ical::tasks_to_ics(&tasks.list()?, tz, now_ms()))
+}
@@ -0,0 +1,37 @@
+use tauri::{AppHandle, State};
+
+use crate::crypto::{EncryptionStatus, Vault};
+use crate::error::{Error, Result};
+use crate::storage::Backend;
+
+#[tauri::command]
+pub async fn encryption_status(vault: State<'_, Vault>) -> Result<EncryptionStatus> {
+    vault.status()
+}
+
+/// Checks the passphrase and loads the data. A no-op once unlocked; if the data
+/// fails to load, the key is dropped again so unlocking can be retried.
+#[tauri::command]
+pub async fn unlock(app: AppHandle, vault: State<'_, Vault>, passphrase: String) -> Result<()> {
+    if !vault.status()?.locked {
+        return Ok(());
+    }
+    vault.unlock(&passphrase)?;
+    crate::open_data(&app).inspect_err(|_| vault.lock())
+}
+
+/// `current` is the passphrase in use, if any; `new` of `None` turns encryption off.
+#[tauri::command]
+pub async fn change_passphrase(
+    vault: State<'_, Vault>,
+    backend: State<'_, Backend>,
+    current: Option<String>,
+    new: Option<String>,
+) -> Result<()> {
+    if new.is_some() && *backend == Backend::Sqlite {
+        return Err(Error::Invalid(
+            "encryption is not supported with the SQLite backend".into(),
+        ));
+    }
+    vault.change_passphrase(current.as_deref(), new.as_deref())
+}
@@ -0,0 +1,27 @@
+use tauri::State;
+
+use crate::data_dir::DataDir;
+use crate::error::Result;
+use crate::persist::DataFiles;
+
+#[tauri::command]
+pub async fn read_data_file(
+    files: State<'_, DataFiles>,
+    name: String,
+) -> Result<Option<serde_json::Value>> {
+    files.read(&name)
+}
+
+#[tauri::command]
+pub async fn write_data_file(
+    files: State<'_, DataFiles>,
+    name: String,
+    data: serde_json::Value,
+) -> Result<()> {
+    files.write(&name, &data)
+}
+
+#[tauri::command]
+pub fn data_dir(dir: State<'_, DataDir>) -> String {
+    dir.0.display().to_string()
+}
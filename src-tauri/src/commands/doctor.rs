@@ -0,0 +1,22 @@
+use tauri::State;
+
+use crate::backups::Backups;
+use crate::doctor::{Doctor, DoctorReport};
+use crate::error::Result;
+use crate::persist::DataFiles;
+use crate::planner::PlannerStore;
+use crate::tasks::TaskStore;
+use crate::time::now_ms;
+
+/// Checks the data files; with `repair`, fixes what it can after a backup.
+#[tauri::command]
+pub async fn doctor(
+    doctor: State<'_, Doctor>,
+    backups: State<'_, Backups>,
+    tasks: State<'_, TaskStore>,
+    files: State<'_, DataFiles>,
+    planner: State<'_, PlannerStore>,
+    repair: bool,
+) -> Result<DoctorReport> {
+    doctor.run(repair, now_ms(), &backups, &tasks, &files, &planner)
+}
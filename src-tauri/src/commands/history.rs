@@ -0,0 +1,24 @@
+use tauri::State;
+
+use crate::error::Result;
+use crate::history::{CompactionReport, HistoryQuery, HistoryStore, TaskUpdate};
+use crate::tasks::Task;
+use crate::time::now_ms;
+
+#[tauri::command]
+pub async fn query_history(
+    store: State<'_, HistoryStore>,
+    query: HistoryQuery,
+) -> Result<Vec<TaskUpdate>> {
+    store.query(&query)
+}
+
+#[tauri::command]
+pub async fn task_at(store: State<'_, HistoryStore>, id: String, timestamp: i64) -> Result<Task> {
+    store.task_at(&id, timestamp)
+}
+
+#[tauri::command]
+pub async fn compact_history(store: State<'_, HistoryStore>) -> Result<CompactionReport> {
+    store.compact(now_ms())
+}
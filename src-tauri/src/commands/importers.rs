@@ -0,0 +1,47 @@
+use tauri::{AppHandle, Manager, State};
+
+use crate::backups::{Backups, Stores};
+use crate::bundle::ImportReport;
+use crate::busy::BusyStore;
+use crate::error::Result;
+use crate::importers::{Source, TaskImport};
+use crate::persist::DataFiles;
+use crate::planner::PlannerStore;
+use crate::tasks::TaskStore;
+use crate::time::now_ms;
+
+/// What `import_tasks` would write, without writing anything. `name` is the file
+/// name; Todoist exports are named after their project.
+#[tauri::command]
+pub async fn preview_task_import(
+    files: State<'_, DataFiles>,
+    source: Source,
+    text: String,
+    name: Option<String>,
+) -> Result<TaskImport> {
+    let tz = files.settings()?.tz();
+    TaskImport::convert(source, &text, name.as_deref(), tz, now_ms())
+}
+
+/// Converts another tool's export and merges the valid tasks in, after a safety
+/// backup.
+#[tauri::command]
+pub async fn import_tasks(
+    app: AppHandle,
+    backups: State<'_, Backups>,
+    source: Source,
+    text: String,
+    name: Option<String>,
+) -> Result<ImportReport> {
+    let (tasks, files) = (app.state::<TaskStore>(), app.state::<DataFiles>());
+    let (planner, busy) = (app.state::<PlannerStore>(), app.state::<BusyStore>());
+    let tz = files.settings()?.tz();
+    let now = now_ms();
+    let stores = Stores {
+        tasks: &tasks,
+        files: &files,
+        planner: &planner,
+        busy: &busy,
+    };
+    TaskImport::convert(source, &text, name.as_deref(), tz, now)?.apply(now, &backups, &stores)
+}
@@ -0,0 +1,27 @@
+//! Tauri command handlers. Each one is a thin wrapper over a store in the crate root.
+
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Runtime};
+
+use crate::events::Events;
+
+pub mod backups;
+pub mod bundle;
+pub mod calendar;
+pub mod crypto;
+pub mod data;
+pub mod doctor;
+pub mod history;
+pub mod importers;
+pub mod planner;
+pub mod profiles;
+pub mod search;
+pub mod strikes;
+pub mod tasks;
+
+impl<R: Runtime> Events for AppHandle<R> {
+    fn emit(&self, event: &str, payload: Value) {
+        // Delivery is best effort; a closed webview is not an error for the store.
+        let _ = Emitter::emit(self, event, payload);
+    }
+}
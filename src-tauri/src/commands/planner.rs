@@ -0,0 +1,63 @@
+use tauri::State;
+
+use crate::busy::{BusyBlock, BusyImport, BusyStore};
+use crate::error::Result;
+use crate::persist::DataFiles;
+use crate::planner::{self, PlannerStore, ScheduledTask};
+use crate::tasks::TaskStore;
+use crate::time::now_ms;
+
+#[tauri::command]
+pub async fn list_schedule(planner: State<'_, PlannerStore>) -> Result<Vec<ScheduledTask>> {
+    planner.list()
+}
+
+#[tauri::command]
+pub async fn save_schedule(
+    planner: State<'_, PlannerStore>,
+    blocks: Vec<ScheduledTask>,
+) -> Result<()> {
+    planner.replace(blocks)
+}
+
+/// The schedule as an `.ics` file of VEVENTs, in the user's timezone.
+#[tauri::command]
+pub async fn export_planner_ics(
+    planner: State<'_, PlannerStore>,
+    tasks: State<'_, TaskStore>,
+    files: State<'_, DataFiles>,
+) -> Result<String> {
+    let tz = files.settings()?.tz();
+    Ok(planner::to_ics(
+        &planner.list()?,
+        &tasks.list()?,
+        tz,
+        now_ms(),
+    ))
+}
+
+/// Replaces the imported calendar with the events of an `.ics` file.
+#[tauri::command]
+pub async fn import_busy_ics(
+    busy: State<'_, BusyStore>,
+    files: State<'_, DataFiles>,
+    ics: String,
+) -> Result<BusyImport> {
+    busy.import(&ics, files.settings()?.tz())
+}
+
+/// Read-only busy time from the imported calendar for the days `from..=to`.
+#[tauri::command]
+pub async fn busy_blocks(
+    busy: State<'_, BusyStore>,
+    files: State<'_, DataFiles>,
+    from: String,
+    to: String,
+) -> Result<Vec<BusyBlock>> {
+    busy.blocks(&from, &to, files.settings()?.tz())
+}
+
+#[tauri::command]
+pub async fn clear_busy(busy: State<'_, BusyStore>) -> Result<()> {
+    busy.clear()
+}
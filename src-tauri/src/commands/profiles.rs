@@ -0,0 +1,30 @@
+use tauri::{AppHandle, State};
+
+use crate::cli;
+use crate::data_dir::{ProfileList, Profiles};
+use crate::error::Result;
+
+#[tauri::command]
+pub async fn list_profiles(profiles: State<'_, Profiles>) -> Result<ProfileList> {
+    profiles.list()
+}
+
+/// Makes `name` (created if new) the active profile and restarts into it. Every store
+/// is opened on one profile's directory, so switching means starting over.
+#[tauri::command]
+pub async fn switch_profile(
+    app: AppHandle,
+    profiles: State<'_, Profiles>,
+    name: String,
+) -> Result<()> {
+    profiles.select(&name)?;
+    if name == profiles.active() {
+        return Ok(());
+    }
+    // `--profile` would win over the profile just saved.
+    std::process::Command::new(std::env::current_exe()?)
+        .args(cli::without_profile(std::env::args().skip(1)))
+        .spawn()?;
+    app.exit(0);
+    Ok(())
+}
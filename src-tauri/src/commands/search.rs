@@ -0,0 +1,15 @@
+use tauri::State;
+
+use crate::error::Result;
+use crate::search::{SearchHit, SearchIndex};
+
+/// Tasks (including completed and deleted ones) matching every word of `query`,
+/// best first, with the matched text highlighted.
+#[tauri::command]
+pub async fn search(
+    index: State<'_, SearchIndex>,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<SearchHit>> {
+    index.search(&query, limit)
+}
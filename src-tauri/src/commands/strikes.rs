@@ -0,0 +1,61 @@
+use tauri::State;
+
+use crate::error::Result;
+use crate::reports::{self, Period};
+use crate::storage::MonthlyStats;
+use crate::strikes::{StrikeEntry, StrikeStore};
+use crate::tasks::TaskStore;
+
+#[tauri::command]
+pub async fn append_strikes(
+    store: State<'_, StrikeStore>,
+    entries: Vec<StrikeEntry>,
+) -> Result<()> {
+    store.append(&entries)
+}
+
+#[tauri::command]
+pub async fn list_strikes(
+    store: State<'_, StrikeStore>,
+    from: String,
+    to: String,
+) -> Result<Vec<StrikeEntry>> {
+    store.between(&from, &to)
+}
+
+#[tauri::command]
+pub async fn monthly_stats(store: State<'_, StrikeStore>, month: String) -> Result<MonthlyStats> {
+    store.monthly_stats(&month)
+}
+
+/// Entries for the days `from..=to` as CSV: `date,taskId,title,action,note,ts`.
+#[tauri::command]
+pub async fn export_strikes_csv(
+    store: State<'_, StrikeStore>,
+    tasks: State<'_, TaskStore>,
+    from: String,
+    to: String,
+) -> Result<String> {
+    Ok(reports::strikes_csv(
+        &store.between(&from, &to)?,
+        &tasks.list()?,
+    ))
+}
+
+/// A Markdown report of the day, or the Monday-to-Sunday week, holding `date`.
+#[tauri::command]
+pub async fn strike_report(
+    store: State<'_, StrikeStore>,
+    tasks: State<'_, TaskStore>,
+    period: Period,
+    date: String,
+) -> Result<String> {
+    let (from, to) = period.range(&date)?;
+    Ok(reports::markdown_report(
+        period,
+        &from,
+        &to,
+        &store.between(&from, &to)?,
+        &tasks.list()?,
+    ))
+}
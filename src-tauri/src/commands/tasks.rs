@@ -0,0 +1,53 @@
+use tauri::State;
+
+use crate::error::Result;
+use crate::tasks::{Task, TaskDraft, TaskStore};
+
+#[tauri::command]
+pub async fn list_tasks(store: State<'_, TaskStore>) -> Result<Vec<Task>> {
+    store.list()
+}
+
+#[tauri::command]
+pub async fn tasks_due_on(store: State<'_, TaskStore>, date: String) -> Result<Vec<Task>> {
+    store.due_on(&date)
+}
+
+#[tauri::command]
+pub async fn tasks_with_tag(store: State<'_, TaskStore>, tag: String) -> Result<Vec<Task>> {
+    store.with_tag(&tag)
+}
+
+#[tauri::command]
+pub async fn create_task(store: State<'_, TaskStore>, draft: TaskDraft) -> Result<Task> {
+    store.create(draft)
+}
+
+#[tauri::command]
+pub async fn update_task(
+    store: State<'_, TaskStore>,
+    id: String,
+    base_revision: u64,
+    draft: TaskDraft,
+) -> Result<Task> {
+    store.update(&id, base_revision, draft)
+}
+
+#[tauri::command]
+pub async fn delete_task(store: State<'_, TaskStore>, id: String) -> Result<Task> {
+    store.delete(&id)
+}
+
+#[tauri::command]
+pub async fn toggle_task(store: State<'_, TaskStore>, id: String) -> Result<Task> {
+    store.toggle(&id)
+}
+
+#[tauri::command]
+pub async fn revert_task(
+    store: State<'_, TaskStore>,
+    id: String,
+    update_id: String,
+) -> Result<Task> {
+    store.revert(&id, &update_id)
+}
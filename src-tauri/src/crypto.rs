@@ -0,0 +1,514 @@
+//! Optional encryption at rest. With a passphrase set, every data file, history
+//! segment and backup is sealed with XChaCha20-Poly1305 under a key derived from the
+//! passphrase with Argon2id. Only `encryption.json` (salt, KDF parameters and a check
+//! value) stays readable. Files are sealed and opened in `persist`, so the stores
+//! above never see ciphertext.
+
+use std::fs::{self, File};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{RwLock, RwLockReadGuard};
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+
+use crate::backups::BACKUPS_DIR;
+use crate::error::{Error, Result};
+use crate::history::HISTORY_DIR;
+use crate::persist::{self, DATA_FILES};
+use crate::tasks::TASKS_FILE;
+
+pub const KEY_FILE: &str = "encryption.json";
+
+/// Prefix of every sealed file; plain JSON and gzip never start with it.
+const MAGIC: &[u8] = b"SHKENC1\n";
+const NONCE_LEN: usize = 24;
+const SALT_LEN: usize = 16;
+/// Sealed into the key file so a wrong passphrase is caught before any data is read.
+const CHECK: &[u8] = b"shakshuka";
+/// Argon2id cost: 19 MiB, 2 passes, 1 lane (the OWASP baseline).
+const KDF_MEMORY_KIB: u32 = 19 * 1024;
+const KDF_ITERATIONS: u32 = 2;
+const MIN_PASSPHRASE_LEN: usize = 8;
+/// Re-sealed copies written next to each file before a passphrase change commits.
+const REKEY_EXT: &str = ".rekey";
+
+/// The key in use, once unlocked.
+static CIPHER: RwLock<Option<Cipher>> = RwLock::new(None);
+/// Shared by every read and write of a data file, exclusive while re-keying, so no
+/// file is sealed or opened with a key that is being replaced.
+static REKEY: RwLock<()> = RwLock::new(());
+
+#[derive(Clone)]
+struct Cipher(XChaCha20Poly1305);
+
+impl Cipher {
+    fn derive(passphrase: &str, key_file: &KeyFile) -> Result<Self> {
+        let params = Params::new(
+            key_file.memory_kib,
+            key_file.iterations,
+            key_file.parallelism,
+            Some(32),
+        )
+        .map_err(|e| Error::Invalid(format!("bad key derivation parameters: {e}")))?;
+        let mut key = [0u8; 32];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), &from_hex(&key_file.salt)?, &mut key)
+            .map_err(|e| Error::Invalid(format!("could not derive the key: {e}")))?;
+        Ok(Self(XChaCha20Poly1305::new(&key.into())))
+    }
+
+    /// `MAGIC || nonce || ciphertext+tag`, with a fresh random nonce.
+    fn seal(&self, plain: &[u8]) -> Result<Vec<u8>> {
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        let sealed = self
+            .0
+            .encrypt(XNonce::from_slice(&nonce), plain)
+            .map_err(|_| Error::Invalid("encryption failed".into()))?;
+        Ok([MAGIC, &nonce, &sealed].concat())
+    }
+
+    fn open(&self, bytes: &[u8]) -> Result<Vec<u8>> {
+        let Some(body) = bytes.strip_prefix(MAGIC) else {
+            return Err(Error::Corrupt("not an encrypted file".into()));
+        };
+        if body.len() < NONCE_LEN {
+            return Err(Error::Corrupt("encrypted file is truncated".into()));
+        }
+        let (nonce, sealed) = body.split_at(NONCE_LEN);
+        self.0
+            .decrypt(XNonce::from_slice(nonce), sealed)
+            .map_err(|_| Error::Corrupt("encrypted file failed authentication".into()))
+    }
+}
+
+/// `encryption.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KeyFile {
+    version: u32,
+    /// Hex.
+    salt: String,
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+    /// [`CHECK`] sealed with the key, hex.
+    check: String,
+}
+
+impl KeyFile {
+    /// A fresh salt for `passphrase`, and the key it yields.
+    fn generate(passphrase: &str, memory_kib: u32, iterations: u32) -> Result<(Self, Cipher)> {
+        if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+            return Err(Error::Invalid(format!(
+                "Passphrase must be at least {MIN_PASSPHRASE_LEN} characters"
+            )));
+        }
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let mut key_file = Self {
+            version: 1,
+            salt: to_hex(&salt),
+            memory_kib,
+            iterations,
+            parallelism: 1,
+            check: String::new(),
+        };
+        let cipher = Cipher::derive(passphrase, &key_file)?;
+        key_file.check = to_hex(&cipher.seal(CHECK)?);
+        Ok((key_file, cipher))
+    }
+
+    fn read(dir: &Path) -> Result<Option<Self>> {
+        match fs::read(dir.join(KEY_FILE)) {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// The key for `passphrase`, if it is the right one.
+    fn unlock(&self, passphrase: &str) -> Result<Cipher> {
+        let cipher = Cipher::derive(passphrase, self)?;
+        match cipher.open(&from_hex(&self.check)?) {
+            Ok(check) if check == CHECK => Ok(cipher),
+            _ => Err(Error::BadPassphrase),
+        }
+    }
+}
+
+/// Taken by `persist` around every read and write of a data file.
+pub fn guard() -> RwLockReadGuard<'static, ()> {
+    REKEY.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// The key in use, if a passphrase has been entered.
+fn cipher() -> Option<Cipher> {
+    CIPHER.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+pub fn is_sealed(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+/// `bytes` sealed with the active key. Unchanged when encryption is off, or when they
+/// are already sealed (a `.bak` generation being put back, say).
+pub fn seal(bytes: &[u8]) -> Result<Vec<u8>> {
+    match cipher() {
+        Some(cipher) if !is_sealed(bytes) => cipher.seal(bytes),
+        _ => Ok(bytes.to_vec()),
+    }
+}
+
+/// The plaintext of a file read from disk. Plain files pass through, so a directory
+/// that was never encrypted, or a file dropped in by hand, still reads.
+pub fn open(bytes: Vec<u8>) -> Result<Vec<u8>> {
+    if !is_sealed(&bytes) {
+        return Ok(bytes);
+    }
+    cipher().ok_or(Error::Locked)?.open(&bytes)
+}
+
+/// True when sealed files can be written now; `Segments` uses this to decide between
+/// appending a line and rewriting the segment.
+pub fn is_active() -> bool {
+    cipher().is_some()
+}
+
+/// Whether `dir` has a passphrase, and whether it has been entered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptionStatus {
+    pub enabled: bool,
+    pub locked: bool,
+}
+
+/// The passphrase side of encryption: unlocking at startup and changing it later.
+pub struct Vault {
+    dir: PathBuf,
+}
+
+impl Vault {
+    /// Finishes or rolls back a passphrase change that was interrupted while
+    /// encryption was being turned off. (Other cases are settled by [`Vault::unlock`].)
+    pub fn open(dir: PathBuf) -> Result<Self> {
+        if KeyFile::read(&dir)?.is_none() {
+            let _exclusive = REKEY.write().unwrap_or_else(|e| e.into_inner());
+            finish_rekey(&dir, None)?;
+        }
+        Ok(Self { dir })
+    }
+
+    pub fn status(&self) -> Result<EncryptionStatus> {
+        let enabled = KeyFile::read(&self.dir)?.is_some();
+        Ok(EncryptionStatus {
+            enabled,
+            locked: enabled && !is_active(),
+        })
+    }
+
+    /// Fails with [`Error::BadPassphrase`] unless `passphrase` is the one in use.
+    pub fn check(&self, passphrase: &str) -> Result<()> {
+        let key_file = KeyFile::read(&self.dir)?
+            .ok_or_else(|| Error::Invalid("encryption is not enabled".into()))?;
+        key_file.unlock(passphrase).map(|_| ())
+    }
+
+    /// Checks `passphrase` and makes the key available to every read and write.
+    pub fn unlock(&self, passphrase: &str) -> Result<()> {
+        let key_file = KeyFile::read(&self.dir)?
+            .ok_or_else(|| Error::Invalid("encryption is not enabled".into()))?;
+        let cipher = key_file.unlock(passphrase)?;
+        let _exclusive = REKEY.write().unwrap_or_else(|e| e.into_inner());
+        finish_rekey(&self.dir, Some(&cipher))?;
+        *CIPHER.write().unwrap_or_else(|e| e.into_inner()) = Some(cipher);
+        Ok(())
+    }
+
+    /// Forgets the key again, so a data directory that failed to load after
+    /// [`Vault::unlock`] is locked and the passphrase can be entered once more.
+    pub fn lock(&self) {
+        let _exclusive = REKEY.write().unwrap_or_else(|e| e.into_inner());
+        *CIPHER.write().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    /// Re-encrypts everything under `new`. `current` must be the passphrase in use,
+    /// if any. `None` for `new` turns encryption off; `None` for `current` turns it on.
+    pub fn change_passphrase(&self, current: Option<&str>, new: Option<&str>) -> Result<()> {
+        let _exclusive = REKEY.write().unwrap_or_else(|e| e.into_inner());
+        let old = match (KeyFile::read(&self.dir)?, current) {
+            (Some(key_file), Some(passphrase)) => Some(key_file.unlock(passphrase)?),
+            (Some(_), None) => return Err(Error::BadPassphrase),
+            (None, _) if new.is_none() => {
+                return Err(Error::Invalid("encryption is not enabled".into()))
+            }
+            (None, _) => None,
+        };
+        let new = new
+            .map(|p| KeyFile::generate(p, KDF_MEMORY_KIB, KDF_ITERATIONS))
+            .transpose()?;
+        rekey(&self.dir, old.as_ref(), new.as_ref())?;
+        *CIPHER.write().unwrap_or_else(|e| e.into_inner()) = new.map(|(_, cipher)| cipher);
+        Ok(())
+    }
+}
+
+/// An export sealed on its own: the salt and KDF parameters travel with it, so it
+/// opens wherever the passphrase is known, not only in the directory it came from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SealedExport {
+    /// Always [`SEALED_EXPORT_FORMAT`].
+    format: String,
+    key: KeyFile,
+    /// The sealed bytes, hex.
+    data: String,
+}
+
+pub const SEALED_EXPORT_FORMAT: &str = "shakshuka-encrypted";
+
+impl SealedExport {
+    pub fn seal(passphrase: &str, plain: &[u8]) -> Result<Self> {
+        let (key, cipher) = KeyFile::generate(passphrase, KDF_MEMORY_KIB, KDF_ITERATIONS)?;
+        Ok(Self {
+            format: SEALED_EXPORT_FORMAT.into(),
+            key,
+            data: to_hex(&cipher.seal(plain)?),
+        })
+    }
+
+    pub fn open(&self, passphrase: &str) -> Result<Vec<u8>> {
+        self.key.unlock(passphrase)?.open(&from_hex(&self.data)?)
+    }
+}
+
+/// Re-seals every file under `new` in three steps: write `<file>.rekey` copies,
+/// commit by replacing (or removing) the key file, then move the copies into place.
+/// A crash before the commit leaves copies the old key cannot read, which
+/// [`finish_rekey`] deletes; after it, copies it moves into place.
+fn rekey(dir: &Path, old: Option<&Cipher>, new: Option<&(KeyFile, Cipher)>) -> Result<()> {
+    let result = data_files(dir).iter().try_for_each(|path| {
+        let bytes = fs::read(path)?;
+        let plain = if is_sealed(&bytes) {
+            match old.ok_or(Error::Locked)?.open(&bytes) {
+                Ok(plain) => plain,
+                // Damaged already; left for the loader to quarantine.
+                Err(Error::Corrupt(_)) => return Ok(()),
+                Err(e) => return Err(e),
+            }
+        } else {
+            bytes
+        };
+        let out = match new {
+            Some((_, cipher)) => cipher.seal(&plain)?,
+            None => plain,
+        };
+        write_synced(&with_suffix(path, REKEY_EXT), &out)
+    });
+    if let Err(e) = result {
+        finish_rekey(dir, old)?;
+        return Err(e);
+    }
+
+    let key_path = dir.join(KEY_FILE);
+    match new {
+        Some((key_file, _)) => {
+            let tmp = with_suffix(&key_path, ".tmp");
+            write_synced(&tmp, &serde_json::to_vec_pretty(key_file)?)?;
+            fs::rename(&tmp, &key_path)?;
+        }
+        None => fs::remove_file(&key_path)?,
+    }
+    finish_rekey(dir, new.map(|(_, cipher)| cipher))
+}
+
+/// Settles `.rekey` copies left by [`rekey`]: a copy readable under the key now in
+/// force (`None`: plain) replaces its file; any other copy is stale and deleted.
+fn finish_rekey(dir: &Path, cipher: Option<&Cipher>) -> Result<()> {
+    for path in data_dirs(dir).iter().flat_map(|d| files_in(d)) {
+        let Some(target) = path
+            .to_str()
+            .and_then(|p| p.strip_suffix(REKEY_EXT))
+            .map(PathBuf::from)
+        else {
+            continue;
+        };
+        let bytes = fs::read(&path)?;
+        let current = match cipher {
+            Some(cipher) => is_sealed(&bytes) && cipher.open(&bytes).is_ok(),
+            None => !is_sealed(&bytes),
+        };
+        if current {
+            persist::record_own_write(&target, &bytes);
+            fs::rename(&path, &target)?;
+        } else {
+            fs::remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
+
+/// The data directory and the subdirectories the app keeps files in.
+fn data_dirs(dir: &Path) -> [PathBuf; 4] {
+    [
+        dir.to_path_buf(),
+        dir.join(HISTORY_DIR),
+        dir.join(BACKUPS_DIR),
+        dir.join("corrupt"),
+    ]
+}
+
+/// Every file the app wrote that holds data. The directory may be shared with the
+/// webview's own storage, so only files the app owns are touched.
+fn data_files(dir: &Path) -> Vec<PathBuf> {
+    let [top, history, backups, corrupt] = data_dirs(dir);
+    let mut files = Vec::new();
+    for name in std::iter::once(TASKS_FILE).chain(DATA_FILES.iter().copied()) {
+        for path in [top.join(name), persist::backup_path(&top.join(name))] {
+            if path.is_file() {
+                files.push(path);
+            }
+        }
+    }
+    let named = |path: &PathBuf, ends: &[&str]| {
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+        ends.iter().any(|end| name.ends_with(end))
+    };
+    files.extend(
+        files_in(&history)
+            .into_iter()
+            .filter(|p| named(p, &[".jsonl", ".jsonl.bak"])),
+    );
+    files.extend(
+        files_in(&backups)
+            .into_iter()
+            .filter(|p| named(p, &[".json.gz"])),
+    );
+    files.extend(
+        files_in(&corrupt)
+            .into_iter()
+            .filter(|p| named(p, &[".json"])),
+    );
+    files
+}
+
+fn files_in(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.is_file())
+        .collect()
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+fn write_synced(path: &Path, bytes: &[u8]) -> Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn from_hex(s: &str) -> Result<Vec<u8>> {
+    let bad = || Error::Invalid(format!("{KEY_FILE} is damaged"));
+    if !s.len().is_multiple_of(2) {
+        return Err(bad());
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2).ok_or_else(bad)?, 16).map_err(|_| bad()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Cheap parameters; the real ones take a noticeable moment in debug builds.
+    fn key(passphrase: &str) -> (KeyFile, Cipher) {
+        KeyFile::generate(passphrase, 64, 1).unwrap()
+    }
+
+    #[test]
+    fn sealed_bytes_open_only_with_the_right_key() {
+        let (key_file, cipher) = key("correct horse");
+        let sealed = cipher.seal(b"[1,2,3]").unwrap();
+        assert!(is_sealed(&sealed));
+        assert_eq!(cipher.open(&sealed).unwrap(), b"[1,2,3]");
+
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(matches!(cipher.open(&tampered), Err(Error::Corrupt(_))));
+
+        assert!(key_file.unlock("correct horse").is_ok());
+        assert!(matches!(
+            key_file.unlock("wrong horse"),
+            Err(Error::BadPassphrase)
+        ));
+        assert!(KeyFile::generate("short", 64, 1).is_err());
+    }
+
+    #[test]
+    fn rekey_seals_owned_files_and_leaves_others() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = |name: &str| dir.path().join(name);
+        fs::write(path(TASKS_FILE), "[]").unwrap();
+        fs::create_dir(path(HISTORY_DIR)).unwrap();
+        fs::write(path(HISTORY_DIR).join("task-updates-2025-03.jsonl"), "{}\n").unwrap();
+        fs::write(path("webview.db"), "not ours").unwrap();
+
+        let first = key("first passphrase");
+        rekey(dir.path(), None, Some(&first)).unwrap();
+        let sealed = fs::read(path(TASKS_FILE)).unwrap();
+        assert_eq!(first.1.open(&sealed).unwrap(), b"[]");
+        assert_eq!(fs::read(path("webview.db")).unwrap(), b"not ours");
+        assert!(KeyFile::read(dir.path()).unwrap().is_some());
+
+        let second = key("second passphrase");
+        rekey(dir.path(), Some(&first.1), Some(&second)).unwrap();
+        let segment = fs::read(path(HISTORY_DIR).join("task-updates-2025-03.jsonl")).unwrap();
+        assert_eq!(second.1.open(&segment).unwrap(), b"{}\n");
+
+        rekey(dir.path(), Some(&second.1), None).unwrap();
+        assert_eq!(fs::read(path(TASKS_FILE)).unwrap(), b"[]");
+        assert!(KeyFile::read(dir.path()).unwrap().is_none());
+    }
+
+    #[test]
+    fn interrupted_rekey_rolls_back_before_the_commit() {
+        let dir = tempfile::tempdir().unwrap();
+        let tasks = dir.path().join(TASKS_FILE);
+        let old = key("old passphrase");
+        fs::write(&tasks, old.1.seal(b"[]").unwrap()).unwrap();
+
+        // Copies were written under the new key, but the key file never changed.
+        let new = key("new passphrase");
+        let copy = with_suffix(&tasks, REKEY_EXT);
+        fs::write(&copy, new.1.seal(b"[]").unwrap()).unwrap();
+        finish_rekey(dir.path(), Some(&old.1)).unwrap();
+        assert!(!copy.exists());
+        assert!(old.1.open(&fs::read(&tasks).unwrap()).is_ok());
+
+        // Same again, but the commit happened: the copy wins.
+        fs::write(&copy, new.1.seal(b"[1]").unwrap()).unwrap();
+        finish_rekey(dir.path(), Some(&new.1)).unwrap();
+        assert_eq!(new.1.open(&fs::read(&tasks).unwrap()).unwrap(), b"[1]");
+    }
+}
@@ -0,0 +1,198 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::persist::{self, DATA_FILES};
+use crate::tasks::TASKS_FILE;
+
+/// The profile whose data lives in the base directory itself, where it always has.
+pub const DEFAULT_PROFILE: &str = "default";
+/// Every other profile is a subdirectory of this one.
+pub const PROFILES_DIR: &str = "profiles";
+/// The profile used when `--profile` is not given: the last one switched to. Kept
+/// in plain text, since it is read before any profile's passphrase is entered.
+pub const PROFILE_FILE: &str = "profiles.json";
+const MAX_PROFILE_NAME: usize = 32;
+
+/// The one directory every data file lives in, resolved once in `setup()`: the
+/// active profile's directory.
+pub struct DataDir(pub PathBuf);
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ProfileFile {
+    active: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileList {
+    pub active: String,
+    /// Sorted, with the default profile first.
+    pub names: Vec<String>,
+}
+
+/// The profiles under one base directory, and which of them this run uses.
+pub struct Profiles {
+    base: PathBuf,
+    active: String,
+}
+
+impl Profiles {
+    /// `--profile` if given, otherwise the last profile switched to, otherwise the
+    /// default one.
+    pub fn resolve(base: PathBuf, flag: Option<&str>) -> Result<Self> {
+        let active = match flag {
+            Some(name) => check_name(name)?.to_string(),
+            None => Self::saved(&base)?,
+        };
+        Ok(Self { base, active })
+    }
+
+    fn saved(base: &Path) -> Result<String> {
+        // A damaged file, or a name edited into something unusable, falls back to the
+        // default profile rather than blocking startup.
+        let file: ProfileFile = match fs::read(base.join(PROFILE_FILE)) {
+            Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_default(),
+            Err(e) if e.kind() == ErrorKind::NotFound => ProfileFile::default(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(file
+            .active
+            .filter(|name| check_name(name).is_ok())
+            .unwrap_or_else(|| DEFAULT_PROFILE.into()))
+    }
+
+    pub fn active(&self) -> &str {
+        &self.active
+    }
+
+    /// The active profile's data directory.
+    pub fn dir(&self) -> PathBuf {
+        self.dir_of(&self.active)
+    }
+
+    fn dir_of(&self, name: &str) -> PathBuf {
+        match name {
+            DEFAULT_PROFILE => self.base.clone(),
+            name => self.base.join(PROFILES_DIR).join(name),
+        }
+    }
+
+    pub fn list(&self) -> Result<ProfileList> {
+        let mut names = Vec::new();
+        match fs::read_dir(self.base.join(PROFILES_DIR)) {
+            Ok(entries) => {
+                for entry in entries {
+                    let entry = entry?;
+                    let name = entry.file_name().to_string_lossy().into_owned();
+                    if entry.file_type()?.is_dir() && check_name(&name).is_ok() {
+                        names.push(name);
+                    }
+                }
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        names.retain(|n| n != DEFAULT_PROFILE);
+        names.sort();
+        names.insert(0, DEFAULT_PROFILE.into());
+        Ok(ProfileList {
+            active: self.active.clone(),
+            names,
+        })
+    }
+
+    /// Makes `name` (created if new) the profile used from the next start.
+    pub fn select(&self, name: &str) -> Result<()> {
+        let name = check_name(name)?;
+        fs::create_dir_all(self.dir_of(name))?;
+        let file = ProfileFile {
+            active: Some(name.into()),
+        };
+        // Read at startup, before any profile's passphrase, so never sealed.
+        persist::atomic_write_plain(
+            &self.base.join(PROFILE_FILE),
+            &serde_json::to_vec_pretty(&file)?,
+        )
+    }
+}
+
+/// Profile names become directory names: letters, digits, `-` and `_` only.
+pub fn check_name(name: &str) -> Result<&str> {
+    let ok = !name.is_empty()
+        && name.len() <= MAX_PROFILE_NAME
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    match ok {
+        true => Ok(name),
+        false => Err(Error::Invalid(format!(
+            "profile name {name:?} must be 1-{MAX_PROFILE_NAME} letters, digits, - or _"
+        ))),
+    }
+}
+
+/// Moves data files from directories older builds wrote to (tasks in AppData,
+/// everything else in AppConfig) into `target`. A file already present in `target`
+/// wins and the legacy copy is left alone. Returns the files that were moved.
+pub fn migrate_legacy(target: &Path, legacy_dirs: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut moved = Vec::new();
+    for dir in legacy_dirs.iter().filter(|d| d.as_path() != target) {
+        for name in std::iter::once(TASKS_FILE).chain(DATA_FILES.iter().copied()) {
+            let from = dir.join(name);
+            let to = target.join(name);
+            if !from.is_file() || to.exists() {
+                continue;
+            }
+            persist::atomic_write(&to, &fs::read(&from)?)?;
+            fs::remove_file(&from)?;
+            moved.push(to);
+        }
+    }
+    Ok(moved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profiles_live_in_subdirectories_of_the_base() {
+        let base = tempfile::tempdir().unwrap();
+        let profiles = Profiles::resolve(base.path().to_path_buf(), None).unwrap();
+        assert_eq!(profiles.active(), DEFAULT_PROFILE);
+        assert_eq!(profiles.dir(), base.path());
+
+        profiles.select("work").unwrap();
+        assert!(profiles.select("../work").is_err());
+        assert_eq!(
+            profiles.list().unwrap(),
+            ProfileList {
+                active: DEFAULT_PROFILE.into(),
+                names: vec![DEFAULT_PROFILE.into(), "work".into()],
+            }
+        );
+
+        let work = Profiles::resolve(base.path().to_path_buf(), None).unwrap();
+        assert_eq!(work.dir(), base.path().join(PROFILES_DIR).join("work"));
+        let flagged = Profiles::resolve(base.path().to_path_buf(), Some("personal")).unwrap();
+        assert_eq!(flagged.active(), "personal");
+        assert!(Profiles::resolve(base.path().to_path_buf(), Some("a/b")).is_err());
+    }
+
+    #[test]
+    fn corrupt_profile_file_falls_back_to_the_default() {
+        let base = tempfile::tempdir().unwrap();
+        fs::write(base.path().join(PROFILE_FILE), br#"{"active": "wo"#).unwrap();
+        let profiles = Profiles::resolve(base.path().to_path_buf(), None).unwrap();
+        assert_eq!(profiles.active(), DEFAULT_PROFILE);
+
+        // Switching profile writes a good file over it.
+        profiles.select("work").unwrap();
+        let work = Profiles::resolve(base.path().to_path_buf(), None).unwrap();
+        assert_eq!(work.active(), "work");
+    }
+}
@@ -0,0 +1,677 @@
+//! `doctor`: finds what hand edits and old bugs leave behind in the data (strikes
+//! and planner blocks of deleted tasks, duplicate task ids, `revision`s that are not
+//! numbers, impossible dates, `dueHour`s outside 0-23) and optionally repairs it,
+//! after a backup.
+//!
+//! With the JSON backend the files are read as they are on disk, without the typed
+//! loading that would quarantine a file over one bad field. A repair starts with a
+//! safety backup, which does load them typed: a file too broken for that is moved to
+//! `corrupt/` as anywhere else in the app, and the backup holds its last good copy.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::backups::{BackupKind, Backups};
+use crate::bundle::check_settings;
+use crate::error::Result;
+use crate::persist::{self, DataFiles, PLANNER_FILE, SETTINGS_FILE, STRIKES_FILE};
+use crate::planner::{PlannerStore, ScheduledTask};
+use crate::schema;
+use crate::settings::AppSettings;
+use crate::storage::{Backend, Storage};
+use crate::strikes::{StrikeAction, StrikeEntry};
+use crate::tasks::{is_valid_date, Task, TaskStore, TASKS_FILE};
+
+/// One problem found, and what a repair does about it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Issue {
+    pub file: String,
+    /// Position in the file's array; `None` for the file as a whole.
+    pub index: Option<usize>,
+    pub id: Option<String>,
+    pub problem: String,
+    /// What a repair does; `None` if it cannot repair this.
+    pub fix: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DoctorReport {
+    pub issues: Vec<Issue>,
+    pub repaired: bool,
+    /// The safety backup taken before the repair.
+    pub backup: Option<String>,
+}
+
+/// The checked records, untyped so that anything can be looked at.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Data {
+    pub tasks: Vec<Value>,
+    pub strikes: Vec<Value>,
+    pub settings: Option<Value>,
+    pub planner: Vec<Value>,
+    /// Files that could not be read at all; they are reported and never written.
+    pub unreadable: Vec<String>,
+}
+
+/// Finds the problems in `data` and fixes them in place. `now` stands in for missing
+/// timestamps.
+pub fn examine(data: &mut Data, now: i64) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let mut found =
+        |file: &str, index: Option<usize>, id: Option<&str>, problem: String, fix: &str| {
+            issues.push(Issue {
+                file: file.into(),
+                index,
+                id: id.map(str::to_string),
+                problem,
+                fix: Some(fix.into()),
+            })
+        };
+
+    // Settings first: strike dates are repaired in the user's timezone.
+    let mut settings = AppSettings::default();
+    if let Some(value) = &mut data.settings {
+        match serde_json::from_value::<AppSettings>(value.clone()) {
+            Ok(parsed) => {
+                settings = parsed;
+                let defaults = AppSettings::default();
+                while let Err(problem) = check_settings(settings.clone()) {
+                    if settings.reset_hour > 23 {
+                        settings.reset_hour = defaults.reset_hour;
+                    } else {
+                        settings.timezone = defaults.timezone.clone();
+                    }
+                    found(SETTINGS_FILE, None, None, problem, "reset to the default");
+                }
+            }
+            Err(e) => {
+                let problem = e.to_string();
+                found(
+                    SETTINGS_FILE,
+                    None,
+                    None,
+                    problem,
+                    "replaced with the defaults",
+                );
+            }
+        }
+        // Rewritten only when something was wrong, so defaults are not filled in
+        // for fields the file leaves out.
+        if serde_json::from_value::<AppSettings>(value.clone())
+            .ok()
+            .as_ref()
+            != Some(&settings)
+        {
+            *value = serde_json::to_value(&settings).unwrap_or_default();
+        }
+    }
+    let tz = settings.tz();
+
+    // Tasks: fix what can be fixed field by field, drop what cannot, keep the most
+    // recently updated copy of a duplicated id.
+    let mut kept: Vec<Value> = Vec::new();
+    let mut by_id: HashMap<String, usize> = HashMap::new();
+    for (i, mut task) in std::mem::take(&mut data.tasks).into_iter().enumerate() {
+        let at = Some(i);
+        let Some(obj) = task.as_object_mut() else {
+            found(TASKS_FILE, at, None, "not an object".into(), "removed");
+            continue;
+        };
+        let Some(id) = text(obj, "id") else {
+            found(TASKS_FILE, at, None, "no id".into(), "removed");
+            continue;
+        };
+        let id = id.as_str();
+        if text(obj, "title").is_none() {
+            found(TASKS_FILE, at, Some(id), "no title".into(), "removed");
+            continue;
+        }
+        if obj.get("revision").and_then(Value::as_u64).is_none() {
+            let problem = format!("revision is {}", shown(obj.get("revision")));
+            found(TASKS_FILE, at, Some(id), problem, "set to 0");
+            obj.insert("revision".into(), 0.into());
+        }
+        if obj.get("createdAt").and_then(Value::as_i64).is_none() {
+            let problem = format!("createdAt is {}", shown(obj.get("createdAt")));
+            found(TASKS_FILE, at, Some(id), problem, "set to now");
+            obj.insert("createdAt".into(), now.into());
+        }
+        if obj.get("updatedAt").and_then(Value::as_i64).is_none() {
+            let problem = format!("updatedAt is {}", shown(obj.get("updatedAt")));
+            found(TASKS_FILE, at, Some(id), problem, "set to createdAt");
+            obj.insert("updatedAt".into(), obj["createdAt"].clone());
+        }
+        if !obj.get("completed").is_some_and(Value::is_boolean) {
+            let problem = format!("completed is {}", shown(obj.get("completed")));
+            found(TASKS_FILE, at, Some(id), problem, "set to false");
+            obj.insert("completed".into(), false.into());
+        }
+        if let Some(date) = obj.get("dueDate").filter(|d| !d.is_null()) {
+            if !date.as_str().is_some_and(is_valid_date) {
+                let problem = format!("dueDate {date} is not a date");
+                found(TASKS_FILE, at, Some(id), problem, "removed the due date");
+                obj.remove("dueDate");
+            }
+        }
+        if let Some(hour) = obj.get("dueHour").filter(|h| !h.is_null()) {
+            if hour.as_u64().is_none_or(|h| h > 23) {
+                let problem = format!("dueHour {hour} is not in 0-23");
+                found(TASKS_FILE, at, Some(id), problem, "removed the due hour");
+                obj.remove("dueHour");
+            }
+        }
+        if let Some(tags) = obj.get("tags").filter(|t| !t.is_null()) {
+            let texts: Vec<Value> = tags
+                .as_array()
+                .map(|t| t.iter().filter(|t| t.is_string()).cloned().collect())
+                .unwrap_or_default();
+            if tags.as_array().is_none_or(|t| t.len() != texts.len()) {
+                let problem = format!("tags {tags} are not all text");
+                found(TASKS_FILE, at, Some(id), problem, "kept the text tags");
+                obj.insert("tags".into(), texts.into());
+            }
+        }
+        if let Err(e) = serde_json::from_value::<Task>(task.clone()) {
+            found(TASKS_FILE, at, Some(id), e.to_string(), "removed");
+            continue;
+        }
+        match by_id.get(id) {
+            Some(&k) => {
+                let problem = "duplicate id".to_string();
+                found(
+                    TASKS_FILE,
+                    at,
+                    Some(id),
+                    problem,
+                    "kept the most recently updated copy",
+                );
+                if updated_at(&task) > updated_at(&kept[k]) {
+                    kept[k] = task;
+                }
+            }
+            None => {
+                by_id.insert(id.to_string(), kept.len());
+                kept.push(task);
+            }
+        }
+    }
+    data.tasks = kept;
+    // With tasks.json unreadable every strike would look orphaned.
+    let tasks_known = !data.unreadable.iter().any(|f| f == TASKS_FILE);
+    let exists = |id: &str| !tasks_known || by_id.contains_key(id);
+
+    let mut kept = Vec::new();
+    let mut seen = HashSet::new();
+    for (i, mut strike) in std::mem::take(&mut data.strikes).into_iter().enumerate() {
+        let at = Some(i);
+        let Some(obj) = strike.as_object_mut() else {
+            found(STRIKES_FILE, at, None, "not an object".into(), "removed");
+            continue;
+        };
+        let Some(task_id) = text(obj, "taskId") else {
+            found(STRIKES_FILE, at, None, "no taskId".into(), "removed");
+            continue;
+        };
+        let id = Some(task_id.as_str());
+        if !exists(&task_id) {
+            let problem = format!("task {task_id} does not exist");
+            found(STRIKES_FILE, at, id, problem, "removed");
+            continue;
+        }
+        let Some(ts) = obj.get("ts").and_then(Value::as_i64) else {
+            let problem = format!("ts is {}", shown(obj.get("ts")));
+            found(STRIKES_FILE, at, id, problem, "removed");
+            continue;
+        };
+        if !obj
+            .get("date")
+            .and_then(Value::as_str)
+            .is_some_and(is_valid_date)
+        {
+            let problem = format!("date {} is not a date", shown(obj.get("date")));
+            let date = DateTime::from_timestamp_millis(ts).map(|t| t.with_timezone(&tz));
+            let Some(date) = date else {
+                found(STRIKES_FILE, at, id, problem, "removed");
+                continue;
+            };
+            found(STRIKES_FILE, at, id, problem, "set from ts");
+            obj.insert("date".into(), date.format("%Y-%m-%d").to_string().into());
+        }
+        let action = obj.get("action").filter(|a| !a.is_null());
+        if let Some(action) = action.filter(|a| StrikeAction::deserialize(*a).is_err()) {
+            let problem = format!("action {action} is not strike, completed or expired");
+            found(STRIKES_FILE, at, id, problem, "counted as a strike");
+            obj.remove("action");
+        }
+        if let Err(e) = serde_json::from_value::<StrikeEntry>(strike.clone()) {
+            found(STRIKES_FILE, at, id, e.to_string(), "removed");
+            continue;
+        }
+        if !seen.insert((task_id.clone(), ts)) {
+            found(STRIKES_FILE, at, id, "duplicate entry".into(), "removed");
+            continue;
+        }
+        kept.push(strike);
+    }
+    data.strikes = kept;
+
+    let mut kept = Vec::new();
+    for (i, block) in std::mem::take(&mut data.planner).into_iter().enumerate() {
+        let at = Some(i);
+        let parsed = serde_json::from_value::<ScheduledTask>(block.clone())
+            .map_err(|e| e.to_string())
+            .and_then(|b| b.validate().map(|_| b).map_err(|e| e.to_string()));
+        match parsed {
+            Err(problem) => found(PLANNER_FILE, at, None, problem, "removed"),
+            Ok(b) if !exists(&b.task_id) => {
+                let problem = format!("task {} does not exist", b.task_id);
+                found(PLANNER_FILE, at, Some(&b.task_id), problem, "removed");
+            }
+            Ok(_) => kept.push(block),
+        }
+    }
+    data.planner = kept;
+    issues
+}
+
+/// A non-empty string field.
+fn text(obj: &Map<String, Value>, key: &str) -> Option<String> {
+    obj.get(key)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+fn shown(value: Option<&Value>) -> String {
+    value.map_or_else(|| "missing".into(), Value::to_string)
+}
+
+fn updated_at(task: &Value) -> i64 {
+    task.get("updatedAt")
+        .and_then(Value::as_i64)
+        .unwrap_or_default()
+}
+
+/// `NaN` and `Infinity` written as `null`. JavaScript's `JSON.stringify` writes NaN
+/// as `null` itself, but hand edits and other tools do not.
+fn nan_to_null(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let (mut in_string, mut escaped) = (false, false);
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if let Some(word) = ["-Infinity", "Infinity", "NaN"]
+            .into_iter()
+            .find(|w| rest.starts_with(w))
+        {
+            out.push_str("null");
+            rest = &rest[word.len()..];
+            continue;
+        }
+        out.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+/// The items of a file that should hold an array.
+fn array(name: &str, doc: Option<Value>, data: &mut Data, issues: &mut Vec<Issue>) -> Vec<Value> {
+    match doc {
+        None => Vec::new(),
+        Some(Value::Array(items)) => items,
+        Some(_) => {
+            issues.push(Issue {
+                file: name.into(),
+                index: None,
+                id: None,
+                problem: "not an array".into(),
+                fix: None,
+            });
+            data.unreadable.push(name.into());
+            Vec::new()
+        }
+    }
+}
+
+fn values<T: Serialize>(items: &[T]) -> Result<Vec<Value>> {
+    items
+        .iter()
+        .map(|i| serde_json::to_value(i).map_err(Into::into))
+        .collect()
+}
+
+/// Reads and repairs one data directory.
+pub struct Doctor {
+    dir: PathBuf,
+    storage: Arc<dyn Storage>,
+    /// Read the backend's files as they are on disk (JSON backend).
+    raw: bool,
+}
+
+impl Doctor {
+    pub fn new(dir: PathBuf, storage: Arc<dyn Storage>, backend: Backend) -> Self {
+        Self {
+            dir,
+            storage,
+            raw: backend == Backend::Json,
+        }
+    }
+
+    /// Checks everything and, with `repair`, writes the fixed data back after a backup.
+    /// The writes go through the stores, like a restore's, so they take the same locks
+    /// as the app's edits, bump task revisions and are recorded in the task history.
+    pub fn run(
+        &self,
+        repair: bool,
+        now: i64,
+        backups: &Backups,
+        tasks: &TaskStore,
+        files: &DataFiles,
+        planner: &PlannerStore,
+    ) -> Result<DoctorReport> {
+        let (before, mut issues) = self.read()?;
+        let mut after = before.clone();
+        issues.extend(examine(&mut after, now));
+        let mut report = DoctorReport {
+            repaired: false,
+            backup: None,
+            issues,
+        };
+        if repair && after != before {
+            report.backup = Some(backups.create(BackupKind::Safety, now)?.id);
+            write(&before, &after, tasks, files, planner)?;
+            report.repaired = true;
+        }
+        Ok(report)
+    }
+
+    fn read(&self) -> Result<(Data, Vec<Issue>)> {
+        let mut data = Data::default();
+        let mut issues = Vec::new();
+        let planner = self.read_raw(PLANNER_FILE, &mut data, &mut issues)?;
+        data.planner = array(PLANNER_FILE, planner, &mut data, &mut issues);
+        if self.raw {
+            let tasks = self.read_raw(TASKS_FILE, &mut data, &mut issues)?;
+            data.tasks = array(TASKS_FILE, tasks, &mut data, &mut issues);
+            let strikes = self.read_raw(STRIKES_FILE, &mut data, &mut issues)?;
+            data.strikes = array(STRIKES_FILE, strikes, &mut data, &mut issues);
+            data.settings = self.read_raw(SETTINGS_FILE, &mut data, &mut issues)?;
+        } else {
+            data.tasks = values(&self.storage.load_tasks()?)?;
+            data.strikes = values(&self.storage.load_strikes()?)?;
+            data.settings = self
+                .storage
+                .load_settings()?
+                .map(serde_json::to_value)
+                .transpose()?;
+        }
+        Ok((data, issues))
+    }
+
+    /// One file's document, reading `NaN` and `Infinity` as `null` if it has to.
+    /// `None` if the file is missing or not JSON at all.
+    fn read_raw(
+        &self,
+        name: &str,
+        data: &mut Data,
+        issues: &mut Vec<Issue>,
+    ) -> Result<Option<Value>> {
+        let Some(bytes) = persist::read_file(&self.dir.join(name))? else {
+            return Ok(None);
+        };
+        let doc = match serde_json::from_slice::<Value>(&bytes) {
+            Ok(doc) => doc,
+            Err(e) => {
+                let lenient = std::str::from_utf8(&bytes)
+                    .ok()
+                    .and_then(|t| serde_json::from_str(&nan_to_null(t)).ok());
+                let (problem, fix) = match &lenient {
+                    Some(_) => (
+                        "NaN or Infinity where a number belongs".to_string(),
+                        Some("read as null".to_string()),
+                    ),
+                    None => (format!("not valid JSON: {e}"), None),
+                };
+                issues.push(Issue {
+                    file: name.into(),
+                    index: None,
+                    id: None,
+                    problem,
+                    fix,
+                });
+                let Some(doc) = lenient else {
+                    data.unreadable.push(name.into());
+                    return Ok(None);
+                };
+                doc
+            }
+        };
+        Ok(Some(schema::upgrade(name, doc)?.1))
+    }
+}
+
+/// The files `after` changed, written back through the stores. Unreadable files are
+/// left alone.
+fn write(
+    before: &Data,
+    after: &Data,
+    tasks: &TaskStore,
+    files: &DataFiles,
+    planner: &PlannerStore,
+) -> Result<()> {
+    let skip = |name: &str| after.unreadable.iter().any(|f| f == name);
+    if after.tasks != before.tasks && !skip(TASKS_FILE) {
+        tasks.replace_all(serde_json::from_value(Value::Array(after.tasks.clone()))?)?;
+    }
+    if after.strikes != before.strikes && !skip(STRIKES_FILE) {
+        files.write(STRIKES_FILE, &Value::Array(after.strikes.clone()))?;
+    }
+    if after.settings != before.settings && !skip(SETTINGS_FILE) {
+        if let Some(settings) = &after.settings {
+            files.write(SETTINGS_FILE, settings)?;
+        }
+    }
+    if after.planner != before.planner && !skip(PLANNER_FILE) {
+        planner.replace(serde_json::from_value(Value::Array(after.planner.clone()))?)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use std::fs;
+    use std::path::Path;
+
+    use super::*;
+    use crate::events::{self, Events, Recorded};
+    use crate::storage::{JsonStorage, MemoryStorage};
+    use crate::tasks::TaskDraft;
+
+    fn stores(
+        dir: &Path,
+        storage: Arc<dyn Storage>,
+        events: Arc<dyn Events>,
+    ) -> (Backups, TaskStore, DataFiles, PlannerStore) {
+        (
+            Backups::new(dir, storage.clone()),
+            TaskStore::new(storage.clone(), events.clone()),
+            DataFiles::new(dir.to_path_buf(), storage, events.clone()),
+            PlannerStore::new(dir.to_path_buf(), events),
+        )
+    }
+
+    #[test]
+    fn finds_and_fixes_field_problems() {
+        let mut data = Data {
+            tasks: vec![
+                json!({ "id": "a", "revision": null, "title": "A", "completed": false,
+                        "createdAt": 1, "updatedAt": 5, "dueDate": "2025-02-30", "dueHour": 24 }),
+                json!({ "id": "a", "revision": 3, "title": "A, newer", "completed": false,
+                        "createdAt": 1, "updatedAt": 9 }),
+                json!({ "id": "", "title": "No id" }),
+                json!({ "id": "b", "revision": 1, "title": "B", "completed": true,
+                        "createdAt": 1, "updatedAt": 1, "tags": ["x", 3] }),
+            ],
+            strikes: vec![
+                json!({ "taskId": "b", "date": "2025-03-01", "ts": 10 }),
+                json!({ "taskId": "b", "date": "2025-03-01", "ts": 10 }),
+                json!({ "taskId": "gone", "date": "2025-03-01", "ts": 11 }),
+                json!({ "taskId": "a", "date": "someday", "ts": 1_740_787_200_000i64, "action": "skipped" }),
+            ],
+            settings: Some(json!({ "resetHour": 30, "timezone": "Asia/Tokyo", "theme": "dark" })),
+            planner: vec![
+                json!({ "taskId": "gone", "task": {}, "startHour": 9, "startMinute": 0,
+                        "durationMinutes": 30, "date": "2025-03-01" }),
+            ],
+            unreadable: Vec::new(),
+        };
+        let issues = examine(&mut data, 100);
+        let problems: Vec<_> = issues
+            .iter()
+            .map(|i| (i.file.as_str(), i.index, i.problem.as_str()))
+            .collect();
+        assert_eq!(
+            problems,
+            [
+                ("settings.json", None, "resetHour 30 is not in 0-23"),
+                ("tasks.json", Some(0), "revision is null"),
+                (
+                    "tasks.json",
+                    Some(0),
+                    "dueDate \"2025-02-30\" is not a date"
+                ),
+                ("tasks.json", Some(0), "dueHour 24 is not in 0-23"),
+                ("tasks.json", Some(1), "duplicate id"),
+                ("tasks.json", Some(2), "no id"),
+                ("tasks.json", Some(3), "tags [\"x\",3] are not all text"),
+                ("strikes.json", Some(1), "duplicate entry"),
+                ("strikes.json", Some(2), "task gone does not exist"),
+                ("strikes.json", Some(3), "date \"someday\" is not a date"),
+                (
+                    "strikes.json",
+                    Some(3),
+                    "action \"skipped\" is not strike, completed or expired"
+                ),
+                ("planner-schedule.json", Some(0), "task gone does not exist"),
+            ]
+        );
+
+        let titles: Vec<_> = data
+            .tasks
+            .iter()
+            .map(|t| t["title"].as_str().unwrap())
+            .collect();
+        assert_eq!(titles, ["A, newer", "B"]);
+        assert_eq!(data.tasks[1]["tags"], json!(["x"]));
+        assert_eq!(data.strikes.len(), 2);
+        // Midnight UTC is 09:00 in Tokyo, the same day.
+        assert_eq!(data.strikes[1]["date"], "2025-03-01");
+        assert!(data.strikes[1].get("action").is_none());
+        assert_eq!(data.settings.as_ref().unwrap()["resetHour"], 9);
+        assert_eq!(data.settings.as_ref().unwrap()["theme"], "dark");
+        assert!(data.planner.is_empty());
+
+        // A second pass finds nothing.
+        assert!(examine(&mut data.clone(), 100).is_empty());
+    }
+
+    #[test]
+    fn repairs_nan_on_disk_after_a_safety_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(TASKS_FILE);
+        fs::write(
+            &path,
+            r#"{"version":1,"data":[{"id":"a","revision":NaN,"title":"A","completed":false,"createdAt":1,"updatedAt":1}]}"#,
+        )
+        .unwrap();
+        let storage = Arc::new(JsonStorage::new(dir.path().to_path_buf(), Arc::new(())));
+        let doctor = Doctor::new(dir.path().to_path_buf(), storage.clone(), Backend::Json);
+        let (backups, tasks, files, planner) = stores(dir.path(), storage.clone(), Arc::new(()));
+        let run = |repair| {
+            doctor
+                .run(repair, 5, &backups, &tasks, &files, &planner)
+                .unwrap()
+        };
+
+        let report = run(false);
+        let problems: Vec<_> = report.issues.iter().map(|i| i.problem.as_str()).collect();
+        assert_eq!(
+            problems,
+            ["NaN or Infinity where a number belongs", "revision is null"]
+        );
+        assert!(!report.repaired);
+        assert!(fs::read_to_string(&path).unwrap().contains("NaN"));
+
+        let report = run(true);
+        assert!(report.repaired);
+        let backup = backups.list().unwrap().remove(0);
+        assert_eq!(report.backup, Some(backup.id));
+        assert_eq!(backup.kind, BackupKind::Safety);
+        let corrupt = fs::read_dir(dir.path().join("corrupt")).unwrap();
+        let kept = corrupt.map(|f| fs::read_to_string(f.unwrap().path()).unwrap());
+        assert!(kept.collect::<Vec<_>>().concat().contains("NaN"));
+        assert_eq!(storage.load_tasks().unwrap()[0].title, "A");
+        assert!(run(false).issues.is_empty());
+    }
+
+    #[test]
+    fn repairs_go_through_the_stores() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Arc::new(MemoryStorage::default());
+        let events = Arc::new(Recorded::default());
+        let doctor = Doctor::new(dir.path().to_path_buf(), storage.clone(), Backend::Memory);
+        let (backups, tasks, files, planner) = stores(dir.path(), storage.clone(), events.clone());
+        let task = tasks
+            .create(TaskDraft {
+                title: "A".into(),
+                ..Default::default()
+            })
+            .unwrap();
+        storage
+            .save_tasks(&[Task {
+                due_hour: Some(24),
+                ..task.clone()
+            }])
+            .unwrap();
+        let orphan = json!([{ "taskId": "gone", "date": "2025-03-01", "ts": 1 }]);
+        files.write(STRIKES_FILE, &orphan).unwrap();
+        events.0.lock().unwrap().clear();
+
+        let report = doctor
+            .run(true, 5, &backups, &tasks, &files, &planner)
+            .unwrap();
+        assert_eq!(report.issues.len(), 2);
+        let repaired = &tasks.list().unwrap()[0];
+        assert_eq!((repaired.due_hour, repaired.revision), (None, 1));
+        let history = tasks.history().query(&Default::default()).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].diff["dueHour"]["old"], 24);
+        assert!(storage.load_strikes().unwrap().is_empty());
+        let names: Vec<_> = events
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(n, _)| n.clone())
+            .collect();
+        assert_eq!(names, [events::TASKS_CHANGED, events::STRIKES_CHANGED]);
+    }
+}
@@ -0,0 +1,71 @@
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+use crate::tasks::Task;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[cfg(feature = "sqlite")]
+    #[error(transparent)]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("task {0} not found")]
+    TaskNotFound(String),
+    #[error("{0}")]
+    Invalid(String),
+    #[error("{file} was written by a newer version of the app (schema v{version})")]
+    UnsupportedVersion { file: String, version: u64 },
+    /// The data directory is encrypted and no passphrase has been entered yet.
+    #[error("the data is encrypted; unlock it first")]
+    Locked,
+    #[error("wrong passphrase")]
+    BadPassphrase,
+    /// An encrypted file that does not decrypt: damaged, or tampered with.
+    #[error("{0}")]
+    Corrupt(String),
+    /// The edit was based on an older revision than the stored one.
+    #[error("task {} was changed elsewhere (now at revision {})", current.id, current.revision)]
+    Conflict { current: Box<Task> },
+}
+
+impl Error {
+    /// Stable identifier the frontend can branch on.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::Io(_) => "io",
+            Error::Json(_) => "json",
+            #[cfg(feature = "sqlite")]
+            Error::Sqlite(_) => "sqlite",
+            Error::TaskNotFound(_) => "notFound",
+            Error::Invalid(_) => "invalid",
+            Error::UnsupportedVersion { .. } => "unsupportedVersion",
+            Error::Locked => "locked",
+            Error::BadPassphrase => "badPassphrase",
+            Error::Corrupt(_) => "corrupt",
+            Error::Conflict { .. } => "conflict",
+        }
+    }
+}
+
+// Commands reject with `{ kind, message }` so the webview gets something it can read.
+// A conflict also carries `current`, the stored task the caller should rebase onto.
+impl Serialize for Error {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let current = match self {
+            Error::Conflict { current } => Some(current),
+            _ => None,
+        };
+        let mut s = serializer.serialize_struct("Error", 2 + current.is_some() as usize)?;
+        s.serialize_field("kind", self.kind())?;
+        s.serialize_field("message", &self.to_string())?;
+        if let Some(current) = current {
+            s.serialize_field("current", current)?;
+        }
+        s.end()
+    }
+}
@@ -0,0 +1,36 @@
+use serde_json::Value;
+
+/// Emitted when a data file could not be parsed and was quarantined.
+pub const DATA_RECOVERED: &str = "data-recovered";
+/// Emitted after every task write, with a `TasksChanged` payload.
+pub const TASKS_CHANGED: &str = "tasks-changed";
+/// Emitted after strikes are appended or rewritten, with a `StrikesChanged` payload.
+pub const STRIKES_CHANGED: &str = "strikes-changed";
+/// Emitted after settings are saved, with the new settings as payload.
+pub const SETTINGS_CHANGED: &str = "settings-changed";
+/// Emitted after the planner schedule is saved, with the new schedule as payload.
+pub const PLANNER_CHANGED: &str = "planner-changed";
+/// Emitted after a calendar is imported or cleared, with a `BusyImport` payload.
+pub const BUSY_CHANGED: &str = "busy-changed";
+
+/// Where stores report things the webview should hear about. The Tauri `AppHandle`
+/// implementation lives in `commands`; `()` drops everything, for tests and CLI modes.
+pub trait Events: Send + Sync {
+    fn emit(&self, event: &str, payload: Value);
+}
+
+impl Events for () {
+    fn emit(&self, _event: &str, _payload: Value) {}
+}
+
+/// Keeps every event, for assertions.
+#[cfg(test)]
+#[derive(Default)]
+pub struct Recorded(pub std::sync::Mutex<Vec<(String, Value)>>);
+
+#[cfg(test)]
+impl Events for Recorded {
+    fn emit(&self, event: &str, payload: Value) {
+        self.0.lock().unwrap().push((event.to_string(), payload));
+    }
+}
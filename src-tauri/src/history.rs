@@ -0,0 +1,678 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::crypto;
+use crate::error::{Error, Result};
+use crate::events::Events;
+use crate::persist::{self, UPDATES_FILE};
+use crate::storage::Storage;
+use crate::tasks::Task;
+use crate::time::utc_month;
+
+/// Directory (inside the data directory) holding the JSONL history segments.
+pub const HISTORY_DIR: &str = "history";
+
+const SEGMENT_PREFIX: &str = "task-updates-";
+const SEGMENT_EXT: &str = "jsonl";
+
+/// After compaction every this-many-th record of a task keeps its full snapshot, as
+/// does the oldest one retained.
+const CHECKPOINT_INTERVAL: usize = 20;
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// One edit of one task; field names match the frontend `TaskUpdate` type.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskUpdate {
+    pub update_id: String,
+    pub task_id: String,
+    /// Epoch milliseconds.
+    pub timestamp: i64,
+    /// Field name -> `{ "old": .., "new": .. }`; absent fields are `null`.
+    pub diff: Map<String, Value>,
+    /// The task as it was after this edit. Compaction keeps it only at checkpoints;
+    /// other states are rebuilt by replaying diffs from the last one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub full_snapshot: Option<Value>,
+}
+
+impl TaskUpdate {
+    /// The record of `before` becoming `after`, or `None` if nothing changed.
+    pub fn between(before: &Task, after: &Task) -> Result<Option<Self>> {
+        let update = Self::change(Some(before), Some(after), after.updated_at)?;
+        Ok((!update.diff.is_empty()).then_some(update))
+    }
+
+    /// The record of `task` being created: every field goes from `null` to its value.
+    pub fn created(task: &Task) -> Result<Self> {
+        Self::change(None, Some(task), task.created_at)
+    }
+
+    /// The record of `task` being deleted at `timestamp`: every field goes to `null`,
+    /// so the old side still holds all of it once earlier records are compacted away.
+    pub fn deleted(task: &Task, timestamp: i64) -> Result<Self> {
+        Self::change(Some(task), None, timestamp)
+    }
+
+    /// A creation record; only these take the `id` from nothing.
+    pub fn is_creation(&self) -> bool {
+        self.diff.get("id").is_some_and(|id| id["old"].is_null())
+    }
+
+    /// A deletion record; only these take the `id` away.
+    pub fn is_deletion(&self) -> bool {
+        self.diff.get("id").is_some_and(|id| id["new"].is_null())
+    }
+
+    fn change(before: Option<&Task>, after: Option<&Task>, timestamp: i64) -> Result<Self> {
+        let old = as_object(before)?;
+        let new = as_object(after)?;
+        let mut diff = Map::new();
+        for key in old.keys().chain(new.keys()) {
+            let (o, n) = (old.get(key), new.get(key));
+            if o != n && !diff.contains_key(key) {
+                diff.insert(key.clone(), json!({ "old": o, "new": n }));
+            }
+        }
+        Ok(Self {
+            update_id: uuid::Uuid::new_v4().to_string(),
+            task_id: after.or(before).map(|t| t.id.clone()).unwrap_or_default(),
+            timestamp,
+            diff,
+            full_snapshot: after.map(|_| Value::Object(new)),
+        })
+    }
+}
+
+fn as_object(task: Option<&Task>) -> Result<Map<String, Value>> {
+    match task.map(serde_json::to_value).transpose()? {
+        Some(Value::Object(map)) => Ok(map),
+        _ => Ok(Map::new()),
+    }
+}
+
+/// Which updates to return; every bound is optional and `from`/`to` are inclusive.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryQuery {
+    #[serde(default)]
+    pub task_id: Option<String>,
+    #[serde(default)]
+    pub from: Option<i64>,
+    #[serde(default)]
+    pub to: Option<i64>,
+}
+
+impl HistoryQuery {
+    pub fn matches(&self, update: &TaskUpdate) -> bool {
+        self.task_id.as_ref().is_none_or(|id| *id == update.task_id)
+            && self.from.is_none_or(|from| update.timestamp >= from)
+            && self.to.is_none_or(|to| update.timestamp <= to)
+    }
+}
+
+/// Append-only JSONL log, one segment per UTC month (`task-updates-2025-03.jsonl`).
+/// Appending writes one line; a new month starts a new segment, and range queries
+/// only open the segments they overlap. Callers serialise access.
+pub struct Segments {
+    dir: PathBuf,
+}
+
+impl Segments {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn segment(&self, month: &str) -> PathBuf {
+        self.dir
+            .join(format!("{SEGMENT_PREFIX}{month}.{SEGMENT_EXT}"))
+    }
+
+    /// Existing segments by month, oldest first.
+    fn list(&self) -> Result<BTreeMap<String, PathBuf>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(BTreeMap::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut segments = BTreeMap::new();
+        for entry in entries {
+            let path = entry?.path();
+            let month = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| n.strip_prefix(SEGMENT_PREFIX))
+                .and_then(|n| n.strip_suffix(&format!(".{SEGMENT_EXT}")))
+                .map(str::to_string);
+            if let Some(month) = month {
+                segments.insert(month, path);
+            }
+        }
+        Ok(segments)
+    }
+
+    /// Adds `update` to its month's segment. Sealed segments cannot be appended to,
+    /// so with encryption on the segment is rewritten instead.
+    pub fn append(&self, update: &TaskUpdate) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.segment(&utc_month(update.timestamp));
+        let guard = crypto::guard();
+        if crypto::is_active() {
+            drop(guard);
+            let mut bytes = persist::read_file(&path)?.unwrap_or_default();
+            if bytes.last().is_some_and(|b| *b != b'\n') {
+                bytes.push(b'\n');
+            }
+            serde_json::to_writer(&mut bytes, update)?;
+            bytes.push(b'\n');
+            return persist::atomic_write(&path, &bytes);
+        }
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(&path)?;
+        let mut line = serde_json::to_vec(update)?;
+        line.push(b'\n');
+        // A crash mid-append leaves a torn last line; start on a fresh one so only
+        // that record is lost.
+        if ends_mid_line(&mut file)? {
+            line.insert(0, b'\n');
+        }
+        file.write_all(&line)?;
+        file.sync_data()?;
+        Ok(())
+    }
+
+    /// Matching updates, oldest first. Lines that do not parse are skipped.
+    pub fn read(&self, query: &HistoryQuery) -> Result<Vec<TaskUpdate>> {
+        let first = query.from.map(utc_month);
+        let last = query.to.map(utc_month);
+        let mut updates = Vec::new();
+        for (month, path) in self.list()? {
+            if first.as_ref().is_some_and(|f| month < *f)
+                || last.as_ref().is_some_and(|l| month > *l)
+            {
+                continue;
+            }
+            let bytes = persist::read_file(&path)?.unwrap_or_default();
+            for line in bytes.split(|b| *b == b'\n') {
+                if let Ok(update) = serde_json::from_slice::<TaskUpdate>(line) {
+                    if query.matches(&update) {
+                        updates.push(update);
+                    }
+                }
+            }
+        }
+        updates.sort_by_key(|u| u.timestamp);
+        Ok(updates)
+    }
+
+    /// Rewrites the log to hold exactly `updates` (imports, compaction). Each
+    /// segment is replaced atomically.
+    pub fn replace(&self, updates: &[TaskUpdate]) -> Result<()> {
+        let mut months: BTreeMap<String, Vec<u8>> = BTreeMap::new();
+        for update in updates {
+            let bytes = months.entry(utc_month(update.timestamp)).or_default();
+            serde_json::to_writer(&mut *bytes, update)?;
+            bytes.push(b'\n');
+        }
+        for (month, path) in self.list()? {
+            if !months.contains_key(&month) {
+                fs::remove_file(path)?;
+            }
+        }
+        for (month, bytes) in months {
+            persist::atomic_write(&self.segment(&month), &bytes)?;
+        }
+        Ok(())
+    }
+}
+
+fn ends_mid_line(file: &mut File) -> Result<bool> {
+    if file.metadata()?.len() == 0 {
+        return Ok(false);
+    }
+    file.seek(SeekFrom::End(-1))?;
+    let mut last = [0u8];
+    file.read_exact(&mut last)?;
+    Ok(last[0] != b'\n')
+}
+
+/// Moves the old `task-updates.json` array into `storage`'s log and keeps the file as
+/// `task-updates.json.bak`. Entries that do not parse as updates are dropped.
+pub fn import_legacy(dir: &Path, storage: &dyn Storage, events: &dyn Events) -> Result<usize> {
+    let path = dir.join(UPDATES_FILE);
+    let Some(entries) = persist::load_json::<Vec<Value>>(&path, events)? else {
+        return Ok(0);
+    };
+    let mut updates: Vec<TaskUpdate> = entries
+        .into_iter()
+        .filter_map(|e| serde_json::from_value(e).ok())
+        .collect();
+    updates.sort_by_key(|u| u.timestamp);
+    for update in &updates {
+        storage.append_update(update)?;
+    }
+    fs::rename(&path, persist::backup_path(&path))?;
+    Ok(updates.len())
+}
+
+/// A task as a JSON object, or `None` while no snapshot has been seen to start from.
+type State = Option<Map<String, Value>>;
+
+/// Moves `state` past `update`: to its snapshot if it has one, to nothing if it is a
+/// deletion, otherwise by its diff.
+fn replay(state: &mut State, update: &TaskUpdate) {
+    if update.is_deletion() {
+        *state = None;
+        return;
+    }
+    match &update.full_snapshot {
+        Some(Value::Object(snapshot)) => *state = Some(snapshot.clone()),
+        _ => {
+            if let Some(state) = state {
+                apply(state, &update.diff, "new");
+            }
+        }
+    }
+}
+
+/// Replays `updates` (one task's, oldest first) from nothing.
+fn state_after(updates: &[TaskUpdate]) -> State {
+    let mut state = None;
+    for update in updates {
+        replay(&mut state, update);
+    }
+    state
+}
+
+/// Sets each field named in `diff` to its `side` ("old" or "new") value; `null`
+/// removes it.
+fn apply(state: &mut Map<String, Value>, diff: &Map<String, Value>, side: &str) {
+    for (key, change) in diff {
+        match change.get(side) {
+            None | Some(Value::Null) => state.remove(key),
+            Some(new) => state.insert(key.clone(), new.clone()),
+        };
+    }
+}
+
+/// What a compaction removed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompactionReport {
+    /// Records older than the retention window.
+    pub updates_removed: usize,
+    pub snapshots_dropped: usize,
+    /// Serialized size before minus after.
+    pub bytes_reclaimed: u64,
+}
+
+/// `updates` (oldest first) with everything before `cutoff` dropped and full
+/// snapshots kept only at checkpoints. Snapshots that are kept, including the new
+/// first one of a task whose older records were dropped, are rebuilt by replay so
+/// they hold even if the original record had none.
+pub fn compact(updates: &[TaskUpdate], cutoff: Option<i64>) -> Vec<TaskUpdate> {
+    // Per task: the state after the last record seen, and how many have been kept.
+    let mut tasks: HashMap<&str, (State, usize)> = HashMap::new();
+    let mut kept = Vec::new();
+    for update in updates {
+        let (state, count) = tasks.entry(&update.task_id).or_default();
+        replay(state, update);
+        if cutoff.is_some_and(|cutoff| update.timestamp < cutoff) {
+            continue;
+        }
+        let full_snapshot = if *count % CHECKPOINT_INTERVAL == 0 {
+            state.clone().map(Value::Object)
+        } else {
+            None
+        };
+        *count += 1;
+        kept.push(TaskUpdate {
+            full_snapshot,
+            ..update.clone()
+        });
+    }
+    kept
+}
+
+/// The last state of every task in `updates` (all tasks', oldest first), in one pass.
+/// A deleted task keeps what it held when it was deleted; tasks whose remaining
+/// history is not enough to rebuild them are left out.
+pub fn last_states(updates: &[TaskUpdate]) -> BTreeMap<String, Task> {
+    let mut states: BTreeMap<&str, State> = BTreeMap::new();
+    for update in updates {
+        let state = states.entry(&update.task_id).or_default();
+        if update.is_deletion() {
+            let mut old = Map::new();
+            apply(&mut old, &update.diff, "old");
+            *state = Some(old);
+        } else {
+            replay(state, update);
+        }
+    }
+    states
+        .into_iter()
+        .filter_map(|(id, state)| Some((id.to_string(), rebuild(id, state).ok()?)))
+        .collect()
+}
+
+fn rebuild(id: &str, state: State) -> Result<Task> {
+    let state = state.ok_or_else(|| {
+        Error::Invalid(format!(
+            "the history of task {id} has no snapshot to rebuild it from"
+        ))
+    })?;
+    Ok(serde_json::from_value(Value::Object(state))?)
+}
+
+fn serialized_len(updates: &[TaskUpdate]) -> Result<u64> {
+    updates.iter().try_fold(0, |total, update| {
+        Ok(total + serde_json::to_vec(update)?.len() as u64 + 1)
+    })
+}
+
+/// The task history. The task store records edits through it and compaction runs
+/// through it, so the two never interleave.
+#[derive(Clone)]
+pub struct HistoryStore {
+    storage: Arc<dyn Storage>,
+    lock: Arc<Mutex<()>>,
+}
+
+impl HistoryStore {
+    pub fn new(storage: Arc<dyn Storage>) -> Self {
+        Self {
+            storage,
+            lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    pub fn query(&self, query: &HistoryQuery) -> Result<Vec<TaskUpdate>> {
+        self.storage.load_updates(query)
+    }
+
+    /// The task as it was at `timestamp`. Before its oldest retained record, that
+    /// record's diff is undone instead of replayed; a task with no history at all is
+    /// taken from the task list. Works for deleted tasks as long as history remains.
+    pub fn task_at(&self, id: &str, timestamp: i64) -> Result<Task> {
+        let updates = self.for_task(id)?;
+        let seen = updates.partition_point(|u| u.timestamp <= timestamp);
+        let not_yet = || Error::Invalid(format!("task {id} did not exist yet at {timestamp}"));
+        let task = if seen > 0 {
+            if updates[seen - 1].is_deletion() {
+                return Err(Error::Invalid(format!(
+                    "task {id} had been deleted by {timestamp}"
+                )));
+            }
+            rebuild(id, state_after(&updates[..seen]))?
+        } else if let Some(first) = updates.first() {
+            if first.is_creation() {
+                return Err(not_yet());
+            }
+            let mut state = state_after(std::slice::from_ref(first));
+            if let Some(state) = &mut state {
+                apply(state, &first.diff, "old");
+            }
+            rebuild(id, state)?
+        } else {
+            self.storage
+                .load_tasks()?
+                .into_iter()
+                .find(|t| t.id == id)
+                .ok_or_else(|| Error::TaskNotFound(id.to_string()))?
+        };
+        if task.created_at > timestamp {
+            return Err(not_yet());
+        }
+        Ok(task)
+    }
+
+    /// The task as history record `update_id` left it.
+    pub fn task_after(&self, id: &str, update_id: &str) -> Result<Task> {
+        let updates = self.for_task(id)?;
+        let idx = updates
+            .iter()
+            .position(|u| u.update_id == update_id)
+            .ok_or_else(|| {
+                Error::Invalid(format!("task {id} has no history record {update_id}"))
+            })?;
+        rebuild(id, state_after(&updates[..=idx]))
+    }
+
+    fn for_task(&self, id: &str) -> Result<Vec<TaskUpdate>> {
+        self.storage.load_updates(&HistoryQuery {
+            task_id: Some(id.to_string()),
+            ..Default::default()
+        })
+    }
+
+    /// Logs `before` becoming `after`, unless nothing changed.
+    pub fn record(&self, before: &Task, after: &Task) -> Result<()> {
+        match TaskUpdate::between(before, after)? {
+            Some(update) => self.append(&update),
+            None => Ok(()),
+        }
+    }
+
+    /// Logs a record made elsewhere, such as a creation or deletion.
+    pub fn append(&self, update: &TaskUpdate) -> Result<()> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        self.storage.append_update(update)
+    }
+
+    /// Applies the retention window from the settings as of `now` and thins out
+    /// snapshots. The history is only rewritten if that changes anything.
+    pub fn compact(&self, now: i64) -> Result<CompactionReport> {
+        let days = self
+            .storage
+            .load_settings()?
+            .unwrap_or_default()
+            .history_retention_days;
+        let cutoff = (days > 0).then(|| now - i64::from(days) * DAY_MS);
+
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let updates = self.storage.load_updates(&HistoryQuery::default())?;
+        let compacted = compact(&updates, cutoff);
+        let count_snapshots =
+            |updates: &[TaskUpdate]| updates.iter().filter(|u| u.full_snapshot.is_some()).count();
+        let removed = updates.len() - compacted.len();
+        let report = CompactionReport {
+            updates_removed: removed,
+            snapshots_dropped: count_snapshots(&updates)
+                .saturating_sub(count_snapshots(&compacted)),
+            bytes_reclaimed: serialized_len(&updates)?.saturating_sub(serialized_len(&compacted)?),
+        };
+        if compacted != updates {
+            self.storage.save_updates(&compacted)?;
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+
+    fn update(id: &str, task_id: &str, timestamp: i64) -> TaskUpdate {
+        TaskUpdate {
+            update_id: id.into(),
+            task_id: task_id.into(),
+            timestamp,
+            diff: Map::new(),
+            full_snapshot: None,
+        }
+    }
+
+    // 2025-03-31T23:00Z and 2025-04-01T01:00Z
+    const MARCH: i64 = 1_743_462_000_000;
+    const APRIL: i64 = 1_743_469_200_000;
+
+    #[test]
+    fn appends_rotate_by_month_and_queries_filter() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = Segments::new(dir.path().to_path_buf());
+        log.append(&update("1", "a", MARCH)).unwrap();
+        log.append(&update("2", "b", MARCH + 1)).unwrap();
+        log.append(&update("3", "a", APRIL)).unwrap();
+
+        let segments: Vec<_> = log.list().unwrap().into_keys().collect();
+        assert_eq!(segments, ["2025-03", "2025-04"]);
+
+        let by_task = HistoryQuery {
+            task_id: Some("a".into()),
+            ..Default::default()
+        };
+        let ids = |q| -> Vec<String> {
+            log.read(q)
+                .unwrap()
+                .into_iter()
+                .map(|u| u.update_id)
+                .collect()
+        };
+        assert_eq!(ids(&by_task), ["1", "3"]);
+        let april = HistoryQuery {
+            from: Some(APRIL),
+            ..Default::default()
+        };
+        assert_eq!(ids(&april), ["3"]);
+    }
+
+    #[test]
+    fn torn_last_line_only_loses_that_record() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = Segments::new(dir.path().to_path_buf());
+        log.append(&update("1", "a", MARCH)).unwrap();
+        let path = log.segment("2025-03");
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(br#"{"updateId":"2","ta"#).unwrap();
+
+        log.append(&update("3", "a", MARCH + 2)).unwrap();
+        let ids: Vec<_> = log
+            .read(&HistoryQuery::default())
+            .unwrap()
+            .into_iter()
+            .map(|u| u.update_id)
+            .collect();
+        assert_eq!(ids, ["1", "3"]);
+    }
+
+    #[test]
+    fn diff_lists_changed_fields_only() {
+        let before: Task = serde_json::from_value(json!({
+            "id": "a", "revision": 0, "title": "t", "completed": false,
+            "createdAt": 1, "updatedAt": 1, "notes": "n"
+        }))
+        .unwrap();
+        assert!(TaskUpdate::between(&before, &before).unwrap().is_none());
+
+        let after = Task {
+            notes: None,
+            revision: 1,
+            updated_at: 2,
+            ..before.clone()
+        };
+        let update = TaskUpdate::between(&before, &after).unwrap().unwrap();
+        let mut keys: Vec<_> = update.diff.keys().cloned().collect();
+        keys.sort();
+        assert_eq!(keys, ["notes", "revision", "updatedAt"]);
+        assert_eq!(update.diff["notes"], json!({ "old": "n", "new": null }));
+        assert_eq!(update.full_snapshot.unwrap()["revision"], 1);
+    }
+
+    #[test]
+    fn compaction_keeps_checkpoints_and_replays_the_rest() {
+        let edit = |n: i64| TaskUpdate {
+            update_id: n.to_string(),
+            task_id: "a".into(),
+            timestamp: n * DAY_MS,
+            diff: Map::from_iter([("title".to_string(), json!({ "old": n - 1, "new": n }))]),
+            full_snapshot: Some(json!({ "id": "a", "title": n })),
+        };
+        let updates: Vec<_> = (1..=50).map(edit).collect();
+
+        let kept = compact(&updates, Some(10 * DAY_MS));
+        assert_eq!(kept.len(), 41);
+        assert_eq!(kept[0].update_id, "10");
+        let checkpoints: Vec<_> = kept
+            .iter()
+            .filter_map(|u| u.full_snapshot.as_ref())
+            .map(|s| s["title"].clone())
+            .collect();
+        assert_eq!(checkpoints, [json!(10), json!(30), json!(50)]);
+
+        // A second pass rebuilds the new first snapshot from the diffs alone.
+        let again = compact(&kept, Some(15 * DAY_MS));
+        assert_eq!(again[0].update_id, "15");
+        assert_eq!(
+            again[0].full_snapshot,
+            Some(json!({ "id": "a", "title": 15 }))
+        );
+        assert_eq!(compact(&again, Some(15 * DAY_MS)), again);
+    }
+
+    #[test]
+    fn task_at_replays_diffs_and_undoes_the_first() {
+        let storage = Arc::new(MemoryStorage::default());
+        let history = HistoryStore::new(storage.clone());
+        let task = |notes: &str, at: i64| -> Task {
+            serde_json::from_value(json!({
+                "id": "a", "revision": at, "title": "t", "completed": false,
+                "createdAt": 0, "updatedAt": at, "notes": notes
+            }))
+            .unwrap()
+        };
+        let mut updates: Vec<_> = [("v0", "v1", 10), ("v1", "v2", 20), ("v2", "v3", 30)]
+            .into_iter()
+            .map(|(old, new, at)| {
+                TaskUpdate::between(&task(old, at - 10), &task(new, at))
+                    .unwrap()
+                    .unwrap()
+            })
+            .collect();
+        // As compaction leaves it: only the first record has a snapshot.
+        updates[1].full_snapshot = None;
+        updates[2].full_snapshot = None;
+        storage.save_updates(&updates).unwrap();
+
+        let notes_at = |ts| history.task_at("a", ts).unwrap().notes.unwrap();
+        assert_eq!(notes_at(5), "v0");
+        assert_eq!(notes_at(10), "v1");
+        assert_eq!(notes_at(29), "v2");
+        assert_eq!(notes_at(100), "v3");
+        assert!(history.task_at("a", -1).is_err());
+        let v2 = history.task_after("a", &updates[1].update_id).unwrap();
+        assert_eq!(v2, task("v2", 20));
+    }
+
+    #[test]
+    fn creation_and_deletion_bound_a_task_life() {
+        let storage = Arc::new(MemoryStorage::default());
+        let history = HistoryStore::new(storage.clone());
+        let task: Task = serde_json::from_value(json!({
+            "id": "a", "revision": 0, "title": "t", "completed": false,
+            "createdAt": 10, "updatedAt": 10, "notes": "n"
+        }))
+        .unwrap();
+        let created = TaskUpdate::created(&task).unwrap();
+        let deleted = TaskUpdate::deleted(&task, 30).unwrap();
+        assert!(created.is_creation() && !created.is_deletion());
+        assert!(deleted.is_deletion() && deleted.full_snapshot.is_none());
+        storage.save_updates(&[created, deleted.clone()]).unwrap();
+
+        assert!(history.task_at("a", 5).is_err());
+        assert_eq!(history.task_at("a", 20).unwrap(), task);
+        assert!(history.task_at("a", 30).is_err());
+        let kept = compact(&[deleted], None);
+        assert_eq!(last_states(&kept)["a"], task);
+    }
+}
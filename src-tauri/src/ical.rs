@@ -0,0 +1,382 @@
+//! iCalendar (RFC 5545) output, and enough of a reader to take it back apart:
+//! line folding, TEXT escaping and content-line parsing.
+
+use chrono::{DateTime, NaiveDate, NaiveTime};
+use chrono_tz::Tz;
+
+use crate::tasks::Task;
+use crate::time::local_to_utc;
+
+const PRODID: &str = "-//Shakshuka//Shakshuka Tasks//EN";
+
+/// Longest content line, in octets, before it is folded (§3.1). Excludes the CRLF.
+const MAX_LINE_OCTETS: usize = 75;
+
+/// `value` as a TEXT value (§3.3.11): backslash, semicolon and comma escaped, line
+/// breaks written as `\n`.
+pub fn escape_text(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.replace("\r\n", "\n").chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ';' => out.push_str("\\;"),
+            ',' => out.push_str("\\,"),
+            '\n' | '\r' => out.push_str("\\n"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Undoes [`escape_text`]. `\N` is accepted for a line break too; an unknown escape
+/// keeps the escaped character.
+pub fn unescape_text(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => out.push('\n'),
+            Some(c) => out.push(c),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// `line` folded into CRLF-terminated lines of at most 75 octets, continuation
+/// lines starting with a space (§3.1). Multi-byte characters are never split.
+pub fn fold(line: &str) -> String {
+    let mut out = String::with_capacity(line.len() + line.len() / MAX_LINE_OCTETS * 3 + 2);
+    let mut used = 0;
+    for c in line.chars() {
+        if used + c.len_utf8() > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            used = 1;
+        }
+        out.push(c);
+        used += c.len_utf8();
+    }
+    out.push_str("\r\n");
+    out
+}
+
+/// Joins folded lines back together. Bare LF line ends are accepted as well as CRLF.
+pub fn unfold(text: &str) -> String {
+    text.replace("\r\n", "\n")
+        .replace("\n ", "")
+        .replace("\n\t", "")
+}
+
+/// One unfolded content line: `NAME;PARAM=value:VALUE`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentLine {
+    /// Upper-cased.
+    pub name: String,
+    /// Parameter names upper-cased; quotes removed from values.
+    pub params: Vec<(String, String)>,
+    /// Still escaped; see [`unescape_text`].
+    pub value: String,
+}
+
+impl ContentLine {
+    pub fn parse(line: &str) -> Option<Self> {
+        // The value starts at the first colon outside a quoted parameter value.
+        let mut quoted = false;
+        let colon = line.char_indices().find_map(|(i, c)| match c {
+            '"' => {
+                quoted = !quoted;
+                None
+            }
+            ':' if !quoted => Some(i),
+            _ => None,
+        })?;
+        let (head, value) = (&line[..colon], &line[colon + 1..]);
+        let mut parts = head.split(';');
+        let name = parts.next()?.trim().to_ascii_uppercase();
+        if name.is_empty() {
+            return None;
+        }
+        let params = parts
+            .filter_map(|p| p.split_once('='))
+            .map(|(k, v)| {
+                (
+                    k.trim().to_ascii_uppercase(),
+                    v.trim_matches('"').to_string(),
+                )
+            })
+            .collect();
+        Some(Self {
+            name,
+            params,
+            value: value.to_string(),
+        })
+    }
+
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// The content lines of an iCalendar document, unfolded. Blank and malformed lines
+/// are skipped.
+pub fn content_lines(text: &str) -> Vec<ContentLine> {
+    unfold(text)
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .filter_map(ContentLine::parse)
+        .collect()
+}
+
+/// Builds a `VCALENDAR`, folding every line as it is added.
+pub struct Calendar {
+    out: String,
+}
+
+impl Default for Calendar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Calendar {
+    pub fn new() -> Self {
+        let mut calendar = Self { out: String::new() };
+        calendar.line("BEGIN", "VCALENDAR");
+        calendar.line("VERSION", "2.0");
+        calendar.line("PRODID", PRODID);
+        calendar.line("CALSCALE", "GREGORIAN");
+        calendar
+    }
+
+    /// Adds `name:value`, where `value` is already in its iCalendar form. `name` may
+    /// carry parameters (`DUE;VALUE=DATE`).
+    pub fn line(&mut self, name: &str, value: &str) {
+        self.out.push_str(&fold(&format!("{name}:{value}")));
+    }
+
+    /// Adds a TEXT property, escaped.
+    pub fn text(&mut self, name: &str, value: &str) {
+        self.line(name, &escape_text(value));
+    }
+
+    pub fn finish(mut self) -> String {
+        self.line("END", "VCALENDAR");
+        self.out
+    }
+}
+
+/// `20250301T090000Z`
+pub fn utc_stamp(ms: i64) -> String {
+    DateTime::from_timestamp_millis(ms)
+        .unwrap_or_default()
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+/// A VTODO for every task with a deadline. Tags become CATEGORIES and notes
+/// DESCRIPTION.
+///
+/// - `dueDate` and `dueHour`: due at that hour in `tz`, written in UTC.
+/// - `dueDate` alone: due that day (`DUE;VALUE=DATE`).
+/// - `dueHour` alone, the daily deadline: recurs daily from the day the task was
+///   created, in floating time so it stays at that hour wherever the calendar is.
+pub fn tasks_to_ics(tasks: &[Task], tz: Tz, now: i64) -> String {
+    let mut cal = Calendar::new();
+    for task in tasks {
+        let date = task
+            .due_date
+            .as_deref()
+            .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+        let hour = task
+            .due_hour
+            .and_then(|h| NaiveTime::from_hms_opt(h.into(), 0, 0));
+        let due = match (date, hour) {
+            (Some(date), Some(hour)) => {
+                let due = local_to_utc(tz, date.and_time(hour));
+                vec![("DUE", utc_stamp(due.timestamp_millis()))]
+            }
+            (Some(date), None) => vec![("DUE;VALUE=DATE", date.format("%Y%m%d").to_string())],
+            (None, Some(hour)) => {
+                let created = DateTime::from_timestamp_millis(task.created_at)
+                    .unwrap_or_default()
+                    .with_timezone(&tz)
+                    .date_naive();
+                vec![
+                    ("DTSTART", created.format("%Y%m%dT000000").to_string()),
+                    (
+                        "DUE",
+                        created.and_time(hour).format("%Y%m%dT%H%M%S").to_string(),
+                    ),
+                    ("RRULE", "FREQ=DAILY".to_string()),
+                ]
+            }
+            (None, None) => continue,
+        };
+        cal.line("BEGIN", "VTODO");
+        cal.text("UID", &format!("{}@shakshuka", task.id));
+        cal.line("DTSTAMP", &utc_stamp(now));
+        cal.line("CREATED", &utc_stamp(task.created_at));
+        cal.line("LAST-MODIFIED", &utc_stamp(task.updated_at));
+        cal.line("SEQUENCE", &task.revision.to_string());
+        cal.text("SUMMARY", &task.title);
+        if let Some(notes) = &task.notes {
+            cal.text("DESCRIPTION", notes);
+        }
+        if let Some(tags) = task.tags.as_ref().filter(|t| !t.is_empty()) {
+            let tags: Vec<_> = tags.iter().map(|t| escape_text(t)).collect();
+            cal.line("CATEGORIES", &tags.join(","));
+        }
+        for (name, value) in &due {
+            cal.line(name, value);
+        }
+        if task.completed {
+            cal.line("STATUS", "COMPLETED");
+            cal.line("PERCENT-COMPLETE", "100");
+        } else {
+            cal.line("STATUS", "NEEDS-ACTION");
+        }
+        cal.line("END", "VTODO");
+    }
+    cal.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Splits a list value (CATEGORIES) on its unescaped commas and unescapes each item.
+    fn split_list(value: &str) -> Vec<String> {
+        let mut items = Vec::new();
+        let mut start = 0;
+        let mut escaped = false;
+        for (i, c) in value.char_indices() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                ',' => {
+                    items.push(unescape_text(&value[start..i]));
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+        items.push(unescape_text(&value[start..]));
+        items
+    }
+
+    fn task(id: &str) -> Task {
+        Task {
+            id: id.into(),
+            revision: 3,
+            title: "Pay rent".into(),
+            notes: None,
+            completed: false,
+            created_at: 1_740_787_200_000, // 2025-03-01T00:00Z
+            updated_at: 1_740_787_200_000,
+            due_hour: None,
+            due_date: None,
+            tags: None,
+        }
+    }
+
+    #[test]
+    fn folding_and_escaping_round_trip() {
+        let samples = [
+            "plain".to_string(),
+            "semi;colon, comma\\backslash".to_string(),
+            "two\r\nlines and a trailing break\n".to_string(),
+            "ünïcödé and emoji 🍳 ".repeat(12),
+            "x".repeat(200),
+        ];
+        for sample in &samples {
+            let folded = fold(&format!("DESCRIPTION:{}", escape_text(sample)));
+            for line in folded.split_terminator("\r\n") {
+                assert!(line.len() <= MAX_LINE_OCTETS, "{line:?} is too long");
+            }
+            assert!(folded.ends_with("\r\n"));
+            let lines = content_lines(&folded);
+            assert_eq!(lines.len(), 1);
+            assert_eq!(lines[0].name, "DESCRIPTION");
+            assert_eq!(unescape_text(&lines[0].value), sample.replace("\r\n", "\n"));
+        }
+
+        // Two-octet characters: 75 does not divide evenly, so lines come up one short.
+        let folded = fold(&format!("SUMMARY:{}", "é".repeat(80)));
+        let first = folded.split("\r\n").next().unwrap();
+        assert_eq!(first.len(), 74);
+        assert_eq!(unfold(&folded), format!("SUMMARY:{}\n", "é".repeat(80)));
+    }
+
+    #[test]
+    fn content_lines_keep_params_and_quoted_colons() {
+        let line = ContentLine::parse(
+            r#"DTSTART;TZID="America/New_York:odd";VALUE=DATE-TIME:20250301T090000"#,
+        )
+        .unwrap();
+        assert_eq!(line.name, "DTSTART");
+        assert_eq!(line.param("tzid"), Some("America/New_York:odd"));
+        assert_eq!(line.value, "20250301T090000");
+        assert_eq!(split_list(r"work,a\,b,home"), ["work", "a,b", "home"]);
+    }
+
+    #[test]
+    fn tasks_become_vtodos_with_their_deadlines() {
+        let tz: Tz = "Europe/Paris".parse().unwrap();
+        let dated = Task {
+            due_date: Some("2025-03-10".into()),
+            due_hour: Some(17),
+            notes: Some("Bank, then; landlord\nsecond line".into()),
+            tags: Some(vec!["home".into(), "money,bills".into()]),
+            ..task("a")
+        };
+        let day = Task {
+            due_date: Some("2025-03-11".into()),
+            completed: true,
+            ..task("b")
+        };
+        let daily = Task {
+            due_hour: Some(9),
+            ..task("c")
+        };
+        let undated = task("d");
+        let ics = tasks_to_ics(&[dated, day, daily, undated], tz, 1_740_787_200_000);
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        let lines = content_lines(&ics);
+        let values = |name: &str| -> Vec<String> {
+            lines
+                .iter()
+                .filter(|l| l.name == name)
+                .map(|l| l.value.clone())
+                .collect()
+        };
+        assert_eq!(values("BEGIN").len(), 4);
+        assert_eq!(values("UID"), ["a@shakshuka", "b@shakshuka", "c@shakshuka"]);
+        // 17:00 in Paris (UTC+1 in March before DST)
+        assert_eq!(
+            values("DUE"),
+            ["20250310T160000Z", "20250311", "20250301T090000"]
+        );
+        assert_eq!(values("RRULE"), ["FREQ=DAILY"]);
+        assert_eq!(
+            values("STATUS"),
+            ["NEEDS-ACTION", "COMPLETED", "NEEDS-ACTION"]
+        );
+        assert_eq!(
+            unescape_text(&values("DESCRIPTION")[0]),
+            "Bank, then; landlord\nsecond line"
+        );
+        assert_eq!(
+            split_list(&values("CATEGORIES")[0]),
+            ["home", "money,bills"]
+        );
+    }
+}
@@ -0,0 +1,532 @@
+//! Tasks from other tools: Todoist CSV exports, Taskwarrior `task export` JSON and
+//! todo.txt. Converted tasks go through [`check_task`], the checks `create_task`
+//! applies, and are only written by [`TaskImport::apply`], which merges them like a
+//! bundle import.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::backups::{Backups, Stores};
+use crate::bundle::{check_task, Checked, ImportMode, ImportReport, Rejected};
+use crate::error::{Error, Result};
+use crate::tasks::Task;
+use crate::time::{local_to_utc, timezone};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    /// A project's CSV export; the file name is the project.
+    Todoist,
+    /// `task export`: a JSON array, or one object per line from older versions.
+    Taskwarrior,
+    TodoTxt,
+}
+
+/// What an import would write. `index` in `rejected` and `warnings` is the row of a
+/// CSV (after the header), the line of todo.txt or the position in Taskwarrior's
+/// array, from 0.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskImport {
+    pub source: Source,
+    pub tasks: Vec<Task>,
+    pub rejected: Vec<Rejected>,
+    /// Imported, but with something dropped (a due date that could not be read).
+    pub warnings: Vec<Rejected>,
+}
+
+impl TaskImport {
+    /// Converts `text`. `name` is the file name, the project of a Todoist export;
+    /// dates and times are moved into `tz`. Only a file that is not in the `source`
+    /// format at all is an error.
+    pub fn convert(
+        source: Source,
+        text: &str,
+        name: Option<&str>,
+        tz: Tz,
+        now: i64,
+    ) -> Result<Self> {
+        let mut import = TaskImport {
+            source,
+            tasks: Vec::new(),
+            rejected: Vec::new(),
+            warnings: Vec::new(),
+        };
+        match source {
+            Source::Todoist => import.todoist(text, name, tz, now)?,
+            Source::Taskwarrior => import.taskwarrior(text, tz, now)?,
+            Source::TodoTxt => import.todo_txt(text, tz, now),
+        }
+        Ok(import)
+    }
+
+    /// Merges the tasks into the current ones after a safety backup.
+    pub fn apply(self, now: i64, backups: &Backups, stores: &Stores) -> Result<ImportReport> {
+        let checked = Checked {
+            tasks: self.tasks,
+            rejected: self.rejected,
+            ..Checked::default()
+        };
+        checked.apply(ImportMode::Merge, now, backups, stores)
+    }
+
+    fn push(&mut self, index: usize, task: Task) {
+        let id = task.id.clone();
+        match check_task(task) {
+            Ok(task) => self.tasks.push(task),
+            Err(reason) => self.rejected.push(note(index, Some(id), reason)),
+        }
+    }
+
+    fn todoist(&mut self, text: &str, name: Option<&str>, tz: Tz, now: i64) -> Result<()> {
+        let mut rows = csv_rows(text).into_iter();
+        let header: Vec<String> = rows
+            .next()
+            .unwrap_or_default()
+            .iter()
+            .map(|h| h.trim().to_ascii_uppercase())
+            .collect();
+        let col = |name: &str| header.iter().position(|h| h == name);
+        let content = col("CONTENT")
+            .ok_or_else(|| Error::Invalid("not a Todoist CSV export: no CONTENT column".into()))?;
+        let (kind, description) = (col("TYPE"), col("DESCRIPTION"));
+        let (date, deadline, zone) = (col("DATE"), col("DEADLINE"), col("TIMEZONE"));
+        let project = name
+            .map(|n| n.rsplit(['/', '\\']).next().unwrap_or(n))
+            .map(|n| n.strip_suffix(".csv").unwrap_or(n).trim().to_string())
+            .filter(|n| !n.is_empty());
+
+        let mut section = None;
+        // A task is held back until its comment rows ("note") have been read.
+        let mut pending: Option<(usize, Task)> = None;
+        for (index, row) in rows.enumerate() {
+            let field = |c: Option<usize>| c.and_then(|c| row.get(c)).map_or("", |f| f.trim());
+            match field(kind).to_ascii_lowercase().as_str() {
+                "section" => section = Some(field(Some(content)).to_string()),
+                "note" => {
+                    if let Some((_, task)) = &mut pending {
+                        let comment = field(Some(content));
+                        task.notes = Some(match task.notes.take() {
+                            Some(notes) => format!("{notes}\n\n{comment}"),
+                            None => comment.to_string(),
+                        });
+                    }
+                }
+                "task" | "" => {
+                    if let Some((index, task)) = pending.take() {
+                        self.push(index, task);
+                    }
+                    let (title, labels) = split_words(field(Some(content)), &['@']);
+                    let mut task = new_task(None, title, now);
+                    task.notes = Some(field(description).to_string()).filter(|n| !n.is_empty());
+                    let tags = project.iter().chain(section.iter()).chain(labels.iter());
+                    for tag in tags {
+                        add_tag(&mut task, tag);
+                    }
+                    let due = Some(field(deadline))
+                        .filter(|d| !d.is_empty())
+                        .unwrap_or(field(date));
+                    if !due.is_empty() {
+                        match loose_date(due) {
+                            Some((day, time)) => {
+                                let from = timezone(field(zone)).unwrap_or(tz);
+                                set_due(&mut task, from, day, time, tz);
+                            }
+                            None => self.warnings.push(note(
+                                index,
+                                None,
+                                format!("due date {due:?} is not a date; imported without one"),
+                            )),
+                        }
+                    }
+                    pending = Some((index, task));
+                }
+                _ => {}
+            }
+        }
+        if let Some((index, task)) = pending {
+            self.push(index, task);
+        }
+        Ok(())
+    }
+
+    fn taskwarrior(&mut self, text: &str, tz: Tz, now: i64) -> Result<()> {
+        let bad = |e: serde_json::Error| Error::Invalid(format!("not a Taskwarrior export: {e}"));
+        let records: Vec<Value> = if text.trim_start().starts_with('[') {
+            serde_json::from_str(text).map_err(bad)?
+        } else {
+            text.lines()
+                .filter(|l| !l.trim().is_empty())
+                .map(|l| serde_json::from_str(l.trim().trim_end_matches(',')))
+                .collect::<std::result::Result<_, _>>()
+                .map_err(bad)?
+        };
+        for (index, record) in records.into_iter().enumerate() {
+            let record: Warrior = match serde_json::from_value(record) {
+                Ok(record) => record,
+                Err(e) => {
+                    self.rejected.push(note(index, None, e.to_string()));
+                    continue;
+                }
+            };
+            let id = record.uuid.filter(|u| !u.trim().is_empty());
+            match record.status.as_deref() {
+                Some("deleted") => {
+                    self.rejected
+                        .push(note(index, id, "deleted in Taskwarrior".into()));
+                    continue;
+                }
+                Some("recurring") => {
+                    self.rejected.push(note(
+                        index,
+                        id,
+                        "a recurrence template; its instances are imported".into(),
+                    ));
+                    continue;
+                }
+                _ => {}
+            }
+            let mut task = new_task(id, record.description.unwrap_or_default(), now);
+            task.completed = record.status.as_deref() == Some("completed");
+            let time = |t: &Option<String>| t.as_deref().and_then(warrior_time);
+            if let Some(entry) = time(&record.entry) {
+                task.created_at = entry.timestamp_millis();
+            }
+            task.updated_at = time(&record.modified)
+                .or_else(|| time(&record.end))
+                .map_or(task.created_at, |t| t.timestamp_millis());
+            let notes: Vec<_> = record
+                .annotations
+                .into_iter()
+                .map(|a| a.description)
+                .collect();
+            task.notes = Some(notes.join("\n")).filter(|n| !n.is_empty());
+            for tag in record.project.iter().chain(record.tags.iter()) {
+                add_tag(&mut task, tag);
+            }
+            if let Some(due) = &record.due {
+                match warrior_time(due) {
+                    Some(due) => {
+                        let utc = timezone("UTC").unwrap_or(tz);
+                        set_due(&mut task, utc, due.date_naive(), Some(due.time()), tz);
+                    }
+                    None => self.warnings.push(note(
+                        index,
+                        Some(task.id.clone()),
+                        format!("due {due:?} is not a date; imported without one"),
+                    )),
+                }
+            }
+            self.push(index, task);
+        }
+        Ok(())
+    }
+
+    fn todo_txt(&mut self, text: &str, tz: Tz, now: i64) {
+        let day_ms = |d: NaiveDate| local_to_utc(tz, d.and_time(NaiveTime::MIN)).timestamp_millis();
+        for (index, line) in text.lines().enumerate() {
+            let mut words = line.split_whitespace().peekable();
+            if words.peek().is_none() {
+                continue;
+            }
+            let completed = words.next_if_eq(&"x").is_some();
+            if !completed {
+                // Priority, `(A)`; there is nothing to map it to.
+                words.next_if(|w| w.len() == 3 && w.starts_with('(') && w.ends_with(')'));
+            }
+            let mut dates = Vec::new();
+            while dates.len() < 1 + usize::from(completed) {
+                match words
+                    .peek()
+                    .and_then(|w| NaiveDate::parse_from_str(w, "%Y-%m-%d").ok())
+                {
+                    Some(date) => {
+                        dates.push(date);
+                        words.next();
+                    }
+                    None => break,
+                }
+            }
+            let (title, tags) = split_words(&words.collect::<Vec<_>>().join(" "), &['+', '@']);
+            let mut rest = Vec::new();
+            let mut due = None;
+            for word in title.split(' ') {
+                match word.strip_prefix("due:") {
+                    Some(date) => due = Some(date.to_string()),
+                    None => rest.push(word),
+                }
+            }
+            let mut task = new_task(None, rest.join(" "), now);
+            task.completed = completed;
+            // `x done created`, or just `created` on an open task.
+            let (done, created) = match (completed, dates.as_slice()) {
+                (true, [done, created]) => (Some(*done), Some(*created)),
+                (true, [done]) => (Some(*done), None),
+                (false, [created]) => (None, Some(*created)),
+                _ => (None, None),
+            };
+            if let Some(created) = created {
+                task.created_at = day_ms(created);
+            }
+            task.updated_at = done.map_or(task.created_at, day_ms);
+            for tag in &tags {
+                add_tag(&mut task, tag);
+            }
+            if let Some(due) = due {
+                match NaiveDate::parse_from_str(&due, "%Y-%m-%d") {
+                    Ok(date) => task.due_date = Some(date.format("%Y-%m-%d").to_string()),
+                    Err(_) => self.warnings.push(note(
+                        index,
+                        None,
+                        format!("due:{due} is not a date; imported without one"),
+                    )),
+                }
+            }
+            self.push(index, task);
+        }
+    }
+}
+
+/// The fields of a Taskwarrior task that map onto ours.
+#[derive(Deserialize)]
+struct Warrior {
+    uuid: Option<String>,
+    description: Option<String>,
+    status: Option<String>,
+    entry: Option<String>,
+    modified: Option<String>,
+    end: Option<String>,
+    due: Option<String>,
+    project: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    annotations: Vec<Annotation>,
+}
+
+#[derive(Deserialize)]
+struct Annotation {
+    description: String,
+}
+
+/// Taskwarrior writes `20250301T120000Z`; ISO 8601 is accepted too.
+fn warrior_time(value: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(value.trim_end_matches('Z'), "%Y%m%dT%H%M%S")
+        .map(|t| t.and_utc())
+        .ok()
+        .or_else(|| DateTime::parse_from_rfc3339(value).ok().map(|t| t.to_utc()))
+}
+
+fn note(index: usize, id: Option<String>, reason: String) -> Rejected {
+    Rejected {
+        section: "tasks".into(),
+        index: Some(index),
+        id,
+        reason,
+    }
+}
+
+fn new_task(id: Option<String>, title: String, now: i64) -> Task {
+    Task {
+        id: id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+        revision: 0,
+        title,
+        notes: None,
+        completed: false,
+        created_at: now,
+        updated_at: now,
+        due_hour: None,
+        due_date: None,
+        tags: None,
+    }
+}
+
+fn add_tag(task: &mut Task, tag: &str) {
+    let tag = tag.trim();
+    let tags = task.tags.get_or_insert_with(Vec::new);
+    if !tag.is_empty() && !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+        tags.push(tag.to_string());
+    }
+}
+
+/// Due date and deadline hour in `tz` of `date` (at `time`, if given) in `from`.
+/// A deadline of midnight is taken as a date without an hour.
+fn set_due(task: &mut Task, from: Tz, date: NaiveDate, time: Option<NaiveTime>, tz: Tz) {
+    let local = match time {
+        Some(time) => local_to_utc(from, date.and_time(time))
+            .with_timezone(&tz)
+            .naive_local(),
+        None => date.and_time(NaiveTime::MIN),
+    };
+    task.due_date = Some(local.date().format("%Y-%m-%d").to_string());
+    task.due_hour = (local.time() != NaiveTime::MIN).then_some(local.hour() as u8);
+}
+
+/// Splits words starting with one of `marks` (`@label`, `+project`) off `text`.
+fn split_words(text: &str, marks: &[char]) -> (String, Vec<String>) {
+    let (tagged, words): (Vec<&str>, Vec<&str>) = text
+        .split_whitespace()
+        .partition(|w| w.len() > 1 && w.starts_with(marks));
+    let tags = tagged.iter().map(|w| w[1..].to_string()).collect();
+    (words.join(" "), tags)
+}
+
+/// The date forms Todoist writes for fixed dates. Recurring ("every monday") and
+/// relative ("tomorrow") dates are not read.
+fn loose_date(value: &str) -> Option<(NaiveDate, Option<NaiveTime>)> {
+    let value = value.trim().trim_end_matches('Z');
+    for format in ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M"] {
+        if let Ok(t) = NaiveDateTime::parse_from_str(value, format) {
+            return Some((t.date(), Some(t.time())));
+        }
+    }
+    for format in ["%Y-%m-%d", "%d %b %Y", "%b %d %Y", "%d %B %Y", "%B %d %Y"] {
+        if let Ok(d) = NaiveDate::parse_from_str(value, format) {
+            return Some((d, None));
+        }
+    }
+    None
+}
+
+/// The rows of RFC 4180 CSV; quoted fields may hold commas, `""` and line breaks.
+/// Blank rows are dropped.
+fn csv_rows(text: &str) -> Vec<Vec<String>> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let (mut rows, mut row, mut field) = (Vec::new(), Vec::new(), String::new());
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted => {
+                if chars.next_if_eq(&'"').is_some() {
+                    field.push('"');
+                } else {
+                    quoted = false;
+                }
+            }
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => row.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows.retain(|r: &Vec<String>| r.iter().any(|f| !f.trim().is_empty()));
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(source: Source, text: &str, name: Option<&str>) -> TaskImport {
+        let tz: Tz = "Europe/Berlin".parse().unwrap();
+        TaskImport::convert(source, text, name, tz, 1_000).unwrap()
+    }
+
+    #[test]
+    fn todoist_rows_labels_sections_and_comments() {
+        let csv = "\u{feff}TYPE,CONTENT,DESCRIPTION,PRIORITY,INDENT,AUTHOR,RESPONSIBLE,DATE,DATE_LANG,TIMEZONE\r\n\
+            section,Errands,,,,,,,,\r\n\
+            task,Buy milk @shop,\"2 litres, \"\"oat\"\"\",4,1,me,,2025-03-01 18:00,en,America/New_York\r\n\
+            note,\"ask for\nthe receipt\",,,,,,,,\r\n\
+            task,Water plants,,1,1,me,,every day,en,\r\n\
+            task,,,1,1,me,,,,\r\n";
+        let import = convert(Source::Todoist, csv, Some("C:\\exports\\Home.csv"));
+        assert_eq!(import.tasks.len(), 2);
+        let milk = &import.tasks[0];
+        assert_eq!(milk.title, "Buy milk");
+        assert_eq!(
+            milk.notes.as_deref(),
+            Some("2 litres, \"oat\"\n\nask for\nthe receipt")
+        );
+        assert_eq!(
+            milk.tags.as_deref(),
+            Some(&["Home".into(), "Errands".into(), "shop".into()][..])
+        );
+        // 18:00 in New York is midnight in Berlin: a due date without an hour.
+        assert_eq!(
+            (milk.due_date.as_deref(), milk.due_hour),
+            (Some("2025-03-02"), None)
+        );
+
+        assert_eq!(import.tasks[1].due_date, None);
+        assert_eq!(import.warnings.len(), 1);
+        assert_eq!(import.warnings[0].index, Some(3));
+        assert_eq!(import.rejected.len(), 1);
+        assert_eq!(import.rejected[0].index, Some(4));
+
+        let tz: Tz = "UTC".parse().unwrap();
+        assert!(TaskImport::convert(Source::Todoist, "a,b\n1,2\n", None, tz, 0).is_err());
+    }
+
+    #[test]
+    fn taskwarrior_export() {
+        let json = r#"[
+            {"uuid":"5f1c","description":"File taxes","status":"pending","entry":"20250301T120000Z",
+             "modified":"20250302T080000Z","due":"20250315T150000Z","project":"Home.Admin","tags":["money"],
+             "annotations":[{"entry":"20250301T120500Z","description":"forms in drawer"}]},
+            {"uuid":"77aa","description":"Old","status":"deleted","entry":"20250301T120000Z"},
+            {"uuid":"88bb","description":"Gym","status":"completed","entry":"20250301T120000Z","end":"20250303T190000Z"},
+            {"uuid":"99cc","description":"","status":"pending"}
+        ]"#;
+        let import = convert(Source::Taskwarrior, json, None);
+        let ids: Vec<_> = import.tasks.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, ["5f1c", "88bb"]);
+        let taxes = &import.tasks[0];
+        assert_eq!(taxes.created_at, 1_740_830_400_000);
+        assert_eq!(taxes.notes.as_deref(), Some("forms in drawer"));
+        assert_eq!(
+            taxes.tags.as_deref(),
+            Some(&["Home.Admin".into(), "money".into()][..])
+        );
+        assert_eq!(
+            (taxes.due_date.as_deref(), taxes.due_hour),
+            (Some("2025-03-15"), Some(16))
+        );
+        assert!(import.tasks[1].completed);
+        let rejected: Vec<_> = import.rejected.iter().map(|r| r.id.as_deref()).collect();
+        assert_eq!(rejected, [Some("77aa"), Some("99cc")]);
+
+        // Older versions: one object per line.
+        let lines = "{\"uuid\":\"1\",\"description\":\"A\",\"status\":\"pending\"},\n{\"uuid\":\"2\",\"description\":\"B\",\"status\":\"pending\"}\n";
+        assert_eq!(convert(Source::Taskwarrior, lines, None).tasks.len(), 2);
+        let tz: Tz = "UTC".parse().unwrap();
+        assert!(TaskImport::convert(Source::Taskwarrior, "not json", None, tz, 0).is_err());
+    }
+
+    #[test]
+    fn todo_txt_lines() {
+        let text = "(A) 2025-03-01 Call mom +Family @phone due:2025-03-05\n\
+            \n\
+            x 2025-03-04 2025-03-02 Pay rent +Home\n\
+            Fix bike due:soon\n\
+            +Tagged @only\n";
+        let import = convert(Source::TodoTxt, text, None);
+        assert_eq!(import.tasks.len(), 3);
+        let call = &import.tasks[0];
+        assert_eq!(call.title, "Call mom");
+        assert_eq!(
+            call.tags.as_deref(),
+            Some(&["Family".into(), "phone".into()][..])
+        );
+        assert_eq!(call.due_date.as_deref(), Some("2025-03-05"));
+        // Midnight in Berlin.
+        assert_eq!(call.created_at, 1_740_783_600_000);
+        let rent = &import.tasks[1];
+        assert!(rent.completed);
+        assert!(rent.updated_at > rent.created_at);
+        assert_eq!(import.tasks[2].title, "Fix bike");
+        assert_eq!(import.warnings[0].index, Some(3));
+        assert_eq!(import.rejected[0].index, Some(4));
+    }
+}
@@ -1,20 +1,303 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use tauri::{AppHandle, Manager};
+mod backups;
+mod bundle;
+mod busy;
+mod cli;
+mod commands;
+mod crypto;
+mod data_dir;
+mod doctor;
+mod error;
+mod events;
+mod history;
+mod ical;
+mod importers;
+mod persist;
+mod planner;
+mod reports;
+mod schema;
+mod search;
+mod settings;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod storage;
+mod strikes;
+mod tasks;
+mod time;
+mod watcher;
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use tauri::{App, AppHandle, Manager};
+
+use crate::backups::Backups;
+use crate::busy::BusyStore;
+use crate::cli::{Cli, Command};
+use crate::crypto::Vault;
+use crate::data_dir::{DataDir, Profiles};
+use crate::doctor::Doctor;
+use crate::error::Error;
+use crate::events::Events;
+use crate::persist::DataFiles;
+use crate::planner::PlannerStore;
+use crate::search::{SearchIndex, Stale};
+use crate::storage::Backend;
+use crate::strikes::StrikeStore;
+use crate::tasks::TaskStore;
+use crate::watcher::DataWatcher;
+
+/// The active profile under `--data-dir` if given, otherwise under the platform AppData
+/// directory with any files left in the old AppConfig/AppLocalData locations moved
+/// into it (except on a dry run).
+fn resolve_profiles(app: &App, cli: &Cli) -> Result<Profiles, Box<dyn std::error::Error>> {
+    if let Some(dir) = &cli.data_dir {
+        return Ok(Profiles::resolve(
+            std::path::absolute(dir)?,
+            cli.profile.as_deref(),
+        )?);
+    }
+    let dir = app.path().app_data_dir()?;
+    if !matches!(
+        cli.command,
+        Some(Command::Migrate { check: true } | Command::Doctor { repair: false })
+    ) {
+        let legacy = [
+            app.path().app_config_dir()?,
+            app.path().app_local_data_dir()?,
+        ];
+        data_dir::migrate_legacy(&dir, &legacy)?;
+    }
+    Ok(Profiles::resolve(dir, cli.profile.as_deref())?)
+}
+
+/// `shakshuka migrate [--check]`: prints what was (or would be) upgraded and exits.
+/// An encrypted directory is upgraded by the app itself once it is unlocked.
+fn run_migrate(dir: &Path, vault: &Vault, check: bool) -> ! {
+    if vault.status().is_ok_and(|s| s.enabled) {
+        eprintln!("{} is encrypted; open the app to migrate it", dir.display());
+        std::process::exit(1)
+    }
+    match schema::migrate_dir(dir, check) {
+        Ok(migrated) => {
+            let verb = if check { "would migrate" } else { "migrated" };
+            for m in &migrated {
+                println!("{verb} {m}");
+            }
+            if migrated.is_empty() {
+                println!(
+                    "{}: all files at v{}",
+                    dir.display(),
+                    schema::CURRENT_VERSION
+                );
+            }
+            std::process::exit(0)
+        }
+        Err(e) => {
+            eprintln!("migration failed: {e}");
+            std::process::exit(1)
+        }
+    }
+}
+
+/// `shakshuka doctor [--repair]`: prints each problem and what was (or would be) done
+/// about it, and exits non-zero if any is left unrepaired.
+fn run_doctor(dir: &Path, vault: &Vault, storage: Option<&str>, repair: bool) -> ! {
+    if vault.status().is_ok_and(|s| s.enabled) {
+        eprintln!("{} is encrypted; open the app to check it", dir.display());
+        std::process::exit(1)
+    }
+    let report = Backend::select(storage, dir).and_then(|backend| {
+        let events: Arc<dyn Events> = Arc::new(());
+        let storage = backend.open(dir, events.clone())?;
+        let tasks = TaskStore::new(storage.clone(), events.clone());
+        let files = DataFiles::new(dir.to_path_buf(), storage.clone(), events.clone());
+        let planner = PlannerStore::new(dir.to_path_buf(), events);
+        let backups = Backups::new(dir, storage.clone());
+        Doctor::new(dir.to_path_buf(), storage, backend).run(
+            repair,
+            time::now_ms(),
+            &backups,
+            &tasks,
+            &files,
+            &planner,
+        )
+    });
+    match report {
+        Ok(report) => {
+            for issue in &report.issues {
+                let place = match (issue.index, &issue.id) {
+                    (Some(i), Some(id)) => format!("{}[{i}] ({id})", issue.file),
+                    (Some(i), None) => format!("{}[{i}]", issue.file),
+                    (None, _) => issue.file.clone(),
+                };
+                let fix = match (&issue.fix, report.repaired) {
+                    (Some(fix), true) => format!(": {fix}"),
+                    (Some(fix), false) => format!(" (--repair: {fix})"),
+                    (None, _) => " (cannot be repaired)".into(),
+                };
+                println!("{place}: {}{fix}", issue.problem);
+            }
+            if let Some(backup) = &report.backup {
+                println!("backed up first as {backup}");
+            }
+            if report.issues.is_empty() {
+                println!("{}: no problems found", dir.display());
+            }
+            let left = report
+                .issues
+                .iter()
+                .any(|i| !report.repaired || i.fix.is_none());
+            std::process::exit(i32::from(left))
+        }
+        Err(e) => {
+            eprintln!("check failed: {e}");
+            std::process::exit(1)
+        }
+    }
+}
+
+/// What `open_data` needs, kept from startup in case it has to wait for a passphrase.
+pub struct Startup {
+    dir: PathBuf,
+    storage: Option<String>,
+}
+
+/// Loads the data directory and puts the stores in managed state. Runs from `setup()`,
+/// or from the `unlock` command when the directory is encrypted.
+pub fn open_data(app: &AppHandle) -> error::Result<()> {
+    let startup = app.state::<Startup>();
+    let dir = startup.dir.clone();
+    schema::migrate_dir(&dir, false)?;
+    let backend = Backend::select(startup.storage.as_deref(), &dir)?;
+    if backend == Backend::Sqlite && crypto::is_active() {
+        return Err(Error::Invalid(
+            "encryption is not supported with the SQLite backend".into(),
+        ));
+    }
+    let stale = Stale::default();
+    let events = stale.watch(Arc::new(app.clone()));
+    let storage = backend.open(&dir, events.clone())?;
+    history::import_legacy(&dir, storage.as_ref(), events.as_ref())?;
+    let tasks = TaskStore::new(storage.clone(), events.clone());
+    let history = tasks.history();
+    // Not fatal: the log is left as it was and compacted on a later run.
+    if let Err(e) = history.compact(time::now_ms()) {
+        eprintln!("could not compact the task history: {e}");
+    }
+    app.manage(backend);
+    app.manage(tasks);
+    app.manage(SearchIndex::new(storage.clone(), history.clone(), stale));
+    app.manage(history);
+    app.manage(StrikeStore::new(storage.clone(), events.clone()));
+    let backups = Backups::new(&dir, storage.clone());
+    backups.schedule();
+    app.manage(backups);
+    match DataWatcher::start(dir.clone(), storage.clone(), events.clone()) {
+        Ok(Some(watcher)) => {
+            app.manage(watcher);
+        }
+        Ok(None) => {}
+        // Not fatal: the app works, it just won't notice external edits.
+        Err(e) => eprintln!("{e}"),
+    }
+    app.manage(PlannerStore::new(dir.clone(), events.clone()));
+    app.manage(BusyStore::new(dir.clone(), events.clone()));
+    app.manage(Doctor::new(dir.clone(), storage.clone(), backend));
+    app.manage(DataFiles::new(dir.clone(), storage, events));
+    app.manage(DataDir(dir));
+    Ok(())
+}
+
+fn autostart_args(cli: &Cli) -> Vec<String> {
+    match &cli.profile {
+        Some(profile) => vec!["--profile".into(), profile.clone()],
+        None => Vec::new(),
+    }
+}
 
 fn main() {
+    let cli = Cli::parse();
+
     tauri::Builder::default()
-        // Autostart plugin: enabled by default on install
-        .plugin(tauri_plugin_autostart::init(
-            tauri_plugin_autostart::MacosLauncher::LaunchAgent,
-            Some(true), // enable at install/update by default
-        ))
+        // Autostart plugin (enabled by the frontend on first run; a LaunchAgent on
+        // macOS). Started with `--profile`, autostart opens that profile; otherwise
+        // the last one used.
+        .plugin(
+            tauri_plugin_autostart::Builder::new()
+                .args(autostart_args(&cli))
+                .build(),
+        )
         // Filesystem plugin for desktop persistence
         .plugin(tauri_plugin_fs::init())
-        .setup(|_app| {
-            // Place any setup you need here. For now, nothing.
+        .setup(move |app| {
+            let profiles = resolve_profiles(app, &cli)?;
+            let dir = profiles.dir();
+            std::fs::create_dir_all(&dir)?;
+            app.manage(profiles);
+            let vault = Vault::open(dir.clone())?;
+            if let Some(Command::Migrate { check }) = cli.command {
+                run_migrate(&dir, &vault, check);
+            }
+            if let Some(Command::Doctor { repair }) = cli.command {
+                run_doctor(&dir, &vault, cli.storage.as_deref(), repair);
+            }
+            let locked = vault.status()?.locked;
+            app.manage(vault);
+            app.manage(Startup {
+                dir,
+                storage: cli.storage.clone(),
+            });
+            // Otherwise the `unlock` command opens it once the passphrase is entered.
+            if !locked {
+                open_data(app.handle())?;
+            }
             Ok(())
         })
+        .invoke_handler(tauri::generate_handler![
+            commands::tasks::list_tasks,
+            commands::tasks::tasks_due_on,
+            commands::tasks::tasks_with_tag,
+            commands::tasks::create_task,
+            commands::tasks::update_task,
+            commands::tasks::delete_task,
+            commands::tasks::toggle_task,
+            commands::tasks::revert_task,
+            commands::history::query_history,
+            commands::history::task_at,
+            commands::history::compact_history,
+            commands::search::search,
+            commands::strikes::append_strikes,
+            commands::strikes::list_strikes,
+            commands::strikes::monthly_stats,
+            commands::strikes::export_strikes_csv,
+            commands::strikes::strike_report,
+            commands::backups::list_backups,
+            commands::backups::create_backup,
+            commands::backups::preview_restore,
+            commands::backups::restore_backup,
+            commands::bundle::export_all,
+            commands::bundle::import_all,
+            commands::importers::preview_task_import,
+            commands::importers::import_tasks,
+            commands::calendar::export_tasks_ics,
+            commands::planner::list_schedule,
+            commands::planner::save_schedule,
+            commands::planner::export_planner_ics,
+            commands::planner::import_busy_ics,
+            commands::planner::busy_blocks,
+            commands::planner::clear_busy,
+            commands::data::read_data_file,
+            commands::data::write_data_file,
+            commands::data::data_dir,
+            commands::profiles::list_profiles,
+            commands::profiles::switch_profile,
+            commands::doctor::doctor,
+            commands::crypto::encryption_status,
+            commands::crypto::unlock,
+            commands::crypto::change_passphrase,
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
-}
\ No newline at end of file
+}
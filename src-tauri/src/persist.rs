@@ -0,0 +1,463 @@
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::crypto;
+use crate::error::{Error, Result};
+use crate::events::{self, Events};
+use crate::history::{HistoryQuery, TaskUpdate};
+use crate::schema;
+use crate::settings::AppSettings;
+use crate::storage::Storage;
+use crate::strikes::{StrikeEntry, StrikesChanged};
+use crate::time::now_ms;
+
+pub const STRIKES_FILE: &str = "strikes.json";
+pub const SETTINGS_FILE: &str = "settings.json";
+pub const UPDATES_FILE: &str = "task-updates.json";
+pub const USED_MESSAGES_FILE: &str = "used-messages.json";
+pub const WIDGETS_FILE: &str = "dashboard-widgets.json";
+pub const PLANNER_FILE: &str = "planner-schedule.json";
+pub const BUSY_FILE: &str = "busy-calendar.json";
+
+/// JSON files the webview may read through `read_data_file`, and write through
+/// `write_data_file` unless they are in `STORE_FILES`. `tasks.json` is deliberately
+/// absent: it is owned by the task store.
+pub const DATA_FILES: &[&str] = &[
+    STRIKES_FILE,
+    SETTINGS_FILE,
+    UPDATES_FILE,
+    USED_MESSAGES_FILE,
+    WIDGETS_FILE,
+    PLANNER_FILE,
+    BUSY_FILE,
+];
+
+/// Data files that stay readable but are only written through their own stores,
+/// which validate them.
+pub const STORE_FILES: &[&str] = &[PLANNER_FILE, BUSY_FILE];
+
+/// `tasks.json` -> `tasks.json.bak`
+pub fn backup_path(path: &Path) -> PathBuf {
+    sibling(path, "bak")
+}
+
+fn sibling(path: &Path, ext: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(ext);
+    path.with_file_name(name)
+}
+
+/// Replaces `path` with `bytes` so that a crash leaves either the old or the new
+/// contents on disk, never a torn file. The previous generation is kept as `.bak`.
+/// With encryption on, what lands on disk is `bytes` sealed.
+pub fn atomic_write(path: &Path, bytes: &[u8]) -> Result<()> {
+    let _guard = crypto::guard();
+    atomic_write_plain(path, &crypto::seal(bytes)?)
+}
+
+/// [`atomic_write`] without sealing, for files outside any data directory that are
+/// read before a passphrase can be entered.
+pub fn atomic_write_plain(path: &Path, bytes: &[u8]) -> Result<()> {
+    let dir = path
+        .parent()
+        .ok_or_else(|| Error::Invalid(format!("{} has no parent directory", path.display())))?;
+    fs::create_dir_all(dir)?;
+
+    let tmp = sibling(path, "tmp");
+    {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&tmp)?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+    }
+
+    // Keep the current file as the backup generation. A hard link is atomic and cheap;
+    // fall back to a copy on filesystems that do not support links.
+    if path.exists() {
+        let bak = backup_path(path);
+        match fs::remove_file(&bak) {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        if fs::hard_link(path, &bak).is_err() {
+            fs::copy(path, &bak)?;
+        }
+    }
+
+    // Recorded before the rename so a watcher woken by it already knows the bytes.
+    record_own_write(path, bytes);
+    fs::rename(&tmp, path)?;
+    sync_dir(dir);
+    Ok(())
+}
+
+/// Hash of the last contents this process wrote to each path, so the file watcher
+/// can tell the app's own writes from external edits.
+static OWN_WRITES: LazyLock<Mutex<HashMap<PathBuf, u64>>> = LazyLock::new(Default::default);
+
+fn content_hash(bytes: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    hasher.finish()
+}
+
+pub fn record_own_write(path: &Path, bytes: &[u8]) {
+    let mut writes = OWN_WRITES.lock().unwrap_or_else(|e| e.into_inner());
+    writes.insert(path.to_path_buf(), content_hash(bytes));
+}
+
+/// True if `bytes` is exactly what the app last wrote to `path`.
+pub fn is_own_write(path: &Path, bytes: &[u8]) -> bool {
+    let writes = OWN_WRITES.lock().unwrap_or_else(|e| e.into_inner());
+    writes.get(path) == Some(&content_hash(bytes))
+}
+
+/// Persists the rename itself. Directories cannot be opened for syncing on Windows,
+/// where `MoveFileEx` is already durable, so failures here are ignored.
+fn sync_dir(dir: &Path) {
+    if let Ok(d) = File::open(dir) {
+        let _ = d.sync_all();
+    }
+}
+
+/// Reads `path`, decrypting it if it was written encrypted. Returns `None` if the
+/// file does not exist.
+pub fn read_file(path: &Path) -> Result<Option<Vec<u8>>> {
+    let _guard = crypto::guard();
+    match fs::read(path) {
+        Ok(bytes) => crypto::open(bytes).map(Some),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Reads `path`, unwraps its version envelope and migrates it to the current schema.
+/// Returns `None` if the file does not exist.
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> Result<Option<T>> {
+    let Some(bytes) = read_file(path)? else {
+        return Ok(None);
+    };
+    let (_, data) = schema::upgrade(&file_name(path), serde_json::from_slice(&bytes)?)?;
+    Ok(Some(serde_json::from_value(data)?))
+}
+
+/// Writes `value` inside the current version envelope.
+pub fn write_json<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<()> {
+    atomic_write(
+        path,
+        serde_json::to_string_pretty(&schema::wrap(value)?)?.as_bytes(),
+    )
+}
+
+/// Payload of the `data-recovered` event.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Recovery {
+    /// File name, e.g. `tasks.json`.
+    pub file: String,
+    /// Why the file was rejected.
+    pub error: String,
+    pub quarantined_to: PathBuf,
+    /// True when the `.bak` generation was valid and has been put back in place.
+    pub restored_from_backup: bool,
+}
+
+/// Like [`read_json`], but a file that is not valid JSON of type `T` (or does not
+/// decrypt) is moved to `corrupt/<name>-<timestamp>.json` instead of being silently
+/// replaced, and the `.bak` generation is tried in its place. Returns `None` if neither is usable.
+pub fn load_json<T: DeserializeOwned>(path: &Path, events: &dyn Events) -> Result<Option<T>> {
+    Ok(load_checked(path, events, |_: &T| Ok(()))?.0)
+}
+
+/// Like [`load_json`], but a file (or `.bak`) that decodes and then fails `check` is
+/// treated as corrupt too. The flag is true when `path` was quarantined.
+pub fn load_checked<T: DeserializeOwned>(
+    path: &Path,
+    events: &dyn Events,
+    check: impl Fn(&T) -> Result<()>,
+) -> Result<(Option<T>, bool)> {
+    let error = match read_json::<T>(path) {
+        Ok(Some(value)) => match check(&value) {
+            Ok(()) => return Ok((Some(value), false)),
+            Err(e) => e,
+        },
+        Err(e @ (Error::Json(_) | Error::Corrupt(_))) => e,
+        other => return Ok((other?, false)),
+    };
+    let quarantined_to = quarantine(path, "")?;
+
+    let bak = backup_path(path);
+    let restored = match read_json::<T>(&bak) {
+        Ok(Some(value)) if check(&value).is_ok() => {
+            if let Some(bytes) = read_file(&bak)? {
+                atomic_write(path, &bytes)?;
+            }
+            Some(value)
+        }
+        Ok(None) => None,
+        Ok(Some(_)) | Err(Error::Json(_) | Error::Corrupt(_)) => {
+            quarantine(&bak, ".bak")?;
+            None
+        }
+        Err(e) => return Err(e),
+    };
+
+    let recovery = Recovery {
+        file: file_name(path),
+        error: error.to_string(),
+        quarantined_to,
+        restored_from_backup: restored.is_some(),
+    };
+    events.emit(events::DATA_RECOVERED, serde_json::to_value(&recovery)?);
+    Ok((restored, true))
+}
+
+/// Moves `path` into the `corrupt/` directory beside it.
+fn quarantine(path: &Path, suffix: &str) -> Result<PathBuf> {
+    let name = file_name(path);
+    let stem = name.split('.').next().unwrap_or(&name);
+    let dir = path.with_file_name("corrupt");
+    fs::create_dir_all(&dir)?;
+    let to = dir.join(format!("{stem}-{}{suffix}.json", now_ms()));
+    fs::rename(path, &to)?;
+    Ok(to)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// The data files the webview reads and writes by name. Strikes, settings and task
+/// history are handed to the storage backend; the rest are loose JSON files.
+pub struct DataFiles {
+    dir: PathBuf,
+    storage: Arc<dyn Storage>,
+    events: Arc<dyn Events>,
+    // Writers share one `.tmp` name per file, and recovery renames files, so access is serialised.
+    lock: Mutex<()>,
+}
+
+impl DataFiles {
+    pub fn new(dir: PathBuf, storage: Arc<dyn Storage>, events: Arc<dyn Events>) -> Self {
+        Self {
+            dir,
+            storage,
+            events,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Every loose data file is an array; anything else is quarantined.
+    pub fn read(&self, name: &str) -> Result<Option<Value>> {
+        let path = self.path(name)?;
+        match name {
+            STRIKES_FILE => Ok(Some(serde_json::to_value(self.storage.load_strikes()?)?)),
+            UPDATES_FILE => {
+                let updates = self.storage.load_updates(&HistoryQuery::default())?;
+                Ok(Some(serde_json::to_value(updates)?))
+            }
+            SETTINGS_FILE => Ok(self
+                .storage
+                .load_settings()?
+                .map(serde_json::to_value)
+                .transpose()?),
+            _ => {
+                let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+                Ok(load_json::<Vec<Value>>(&path, self.events.as_ref())?.map(Value::Array))
+            }
+        }
+    }
+
+    /// The saved settings, or the defaults if there are none yet.
+    pub fn settings(&self) -> Result<AppSettings> {
+        Ok(self.storage.load_settings()?.unwrap_or_default())
+    }
+
+    pub fn write(&self, name: &str, value: &Value) -> Result<()> {
+        let path = self.path(name)?;
+        if STORE_FILES.contains(&name) {
+            return Err(Error::Invalid(format!(
+                "{name} is written by its own store"
+            )));
+        }
+        match name {
+            STRIKES_FILE => {
+                let strikes = Vec::<StrikeEntry>::deserialize(value)?;
+                let before = self.storage.load_strikes()?;
+                self.storage.save_strikes(&strikes)?;
+                let changed = StrikesChanged::between(&before, &strikes);
+                self.events
+                    .emit(events::STRIKES_CHANGED, serde_json::to_value(changed)?);
+                Ok(())
+            }
+            UPDATES_FILE => self
+                .storage
+                .save_updates(&Vec::<TaskUpdate>::deserialize(value)?),
+            SETTINGS_FILE => {
+                let settings = AppSettings::deserialize(value)?;
+                self.storage.save_settings(&settings)?;
+                self.events
+                    .emit(events::SETTINGS_CHANGED, serde_json::to_value(settings)?);
+                Ok(())
+            }
+            _ => {
+                let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+                write_json(&path, value)
+            }
+        }
+    }
+
+    fn path(&self, name: &str) -> Result<PathBuf> {
+        if !DATA_FILES.contains(&name) {
+            return Err(Error::Invalid(format!("{name} is not a data file")));
+        }
+        Ok(self.dir.join(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::Recorded;
+    use crate::storage::JsonStorage;
+    use crate::tasks::Task;
+
+    const TASKS: &str = r#"[{"id":"a","revision":1,"title":"Write report","completed":false,"createdAt":1,"updatedAt":2}]"#;
+
+    fn corrupt_entries(dir: &Path) -> Vec<PathBuf> {
+        fs::read_dir(dir.join("corrupt"))
+            .map(|rd| rd.map(|e| e.unwrap().path()).collect())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn atomic_write_keeps_previous_generation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tasks.json");
+        atomic_write(&path, b"[1]").unwrap();
+        atomic_write(&path, b"[2]").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"[2]");
+        assert_eq!(fs::read(backup_path(&path)).unwrap(), b"[1]");
+        assert!(!dir.path().join("tasks.json.tmp").exists());
+    }
+
+    #[test]
+    fn truncated_file_is_quarantined_and_backup_restored() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tasks.json");
+        fs::write(backup_path(&path), TASKS).unwrap();
+        fs::write(&path, &TASKS[..TASKS.len() / 2]).unwrap();
+        let events = Recorded::default();
+
+        let tasks: Vec<Task> = load_json(&path, &events).unwrap().unwrap();
+
+        assert_eq!(tasks[0].title, "Write report");
+        assert_eq!(fs::read_to_string(&path).unwrap(), TASKS);
+        let quarantined = corrupt_entries(dir.path());
+        assert_eq!(quarantined.len(), 1);
+        assert_eq!(
+            fs::read_to_string(&quarantined[0]).unwrap(),
+            &TASKS[..TASKS.len() / 2]
+        );
+        let events = events.0.lock().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].0, events::DATA_RECOVERED);
+        assert_eq!(events[0].1["file"], "tasks.json");
+        assert_eq!(events[0].1["restoredFromBackup"], true);
+    }
+
+    #[test]
+    fn garbage_without_backup_yields_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tasks.json");
+        fs::write(&path, b"\x00\xffnot json at all").unwrap();
+        let events = Recorded::default();
+
+        let tasks: Option<Vec<Task>> = load_json(&path, &events).unwrap();
+
+        assert!(tasks.is_none());
+        assert!(!path.exists());
+        assert_eq!(corrupt_entries(dir.path()).len(), 1);
+        assert_eq!(events.0.lock().unwrap()[0].1["restoredFromBackup"], false);
+    }
+
+    #[test]
+    fn garbage_backup_is_quarantined_too() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tasks.json");
+        fs::write(&path, b"{").unwrap();
+        fs::write(backup_path(&path), b"[{\"id\":").unwrap();
+
+        let tasks: Option<Vec<Task>> = load_json(&path, &()).unwrap();
+
+        assert!(tasks.is_none());
+        assert!(!backup_path(&path).exists());
+        assert_eq!(corrupt_entries(dir.path()).len(), 2);
+    }
+
+    #[test]
+    fn schema_violation_is_treated_as_corrupt() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Arc::new(JsonStorage::new(dir.path().to_path_buf(), Arc::new(())));
+        let files = DataFiles::new(dir.path().to_path_buf(), storage, Arc::new(()));
+        fs::write(dir.path().join(STRIKES_FILE), br#"{"not":"an array"}"#).unwrap();
+        fs::write(dir.path().join(SETTINGS_FILE), b"[]").unwrap();
+        fs::write(dir.path().join(WIDGETS_FILE), b"{}").unwrap();
+
+        // Strikes fall back to an empty history; the broken file is still quarantined.
+        assert_eq!(
+            files.read(STRIKES_FILE).unwrap(),
+            Some(Value::Array(vec![]))
+        );
+        assert!(files.read(SETTINGS_FILE).unwrap().is_none());
+        assert!(files.read(WIDGETS_FILE).unwrap().is_none());
+        assert_eq!(corrupt_entries(dir.path()).len(), 3);
+    }
+
+    #[test]
+    fn store_files_are_read_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Arc::new(JsonStorage::new(dir.path().to_path_buf(), Arc::new(())));
+        let files = DataFiles::new(dir.path().to_path_buf(), storage, Arc::new(()));
+        fs::write(dir.path().join(PLANNER_FILE), b"[]").unwrap();
+
+        assert!(matches!(
+            files.write(PLANNER_FILE, &Value::Array(vec![])),
+            Err(Error::Invalid(_))
+        ));
+        assert!(matches!(
+            files.write(BUSY_FILE, &Value::Array(vec![])),
+            Err(Error::Invalid(_))
+        ));
+        assert!(!dir.path().join(BUSY_FILE).exists());
+        assert_eq!(
+            files.read(PLANNER_FILE).unwrap(),
+            Some(Value::Array(vec![]))
+        );
+    }
+
+    #[test]
+    fn missing_file_is_not_a_recovery() {
+        let dir = tempfile::tempdir().unwrap();
+        let events = Recorded::default();
+        let tasks: Option<Vec<Task>> = load_json(&dir.path().join("tasks.json"), &events).unwrap();
+        assert!(tasks.is_none());
+        assert!(events.0.lock().unwrap().is_empty());
+    }
+}
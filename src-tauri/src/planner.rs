@@ -0,0 +1,204 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use chrono::{Duration, NaiveDate, NaiveTime};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::{Error, Result};
+use crate::events::{self, Events};
+use crate::ical::{self, Calendar};
+use crate::persist::{self, PLANNER_FILE};
+use crate::tasks::{is_valid_date, Task};
+use crate::time::local_to_utc;
+
+/// Longest block the planner offers is three hours; anything up to a day is accepted.
+const MAX_DURATION_MINUTES: u32 = 24 * 60;
+
+/// One block of `planner-schedule.json`; field names match the frontend
+/// `ScheduledTask` type.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledTask {
+    pub task_id: String,
+    /// The task as it was when scheduled, for display. Exports use the live task
+    /// when it still exists.
+    pub task: Value,
+    /// 0-23, in the user's timezone.
+    pub start_hour: u8,
+    /// 0-59; the planner uses 0 and 30.
+    pub start_minute: u8,
+    pub duration_minutes: u32,
+    /// YYYY-MM-DD in the user's timezone.
+    pub date: String,
+}
+
+impl ScheduledTask {
+    pub fn validate(&self) -> Result<()> {
+        if self.task_id.trim().is_empty() {
+            return Err(Error::Invalid("taskId is empty".into()));
+        }
+        if !is_valid_date(&self.date) {
+            return Err(Error::Invalid(format!(
+                "date {:?} is not YYYY-MM-DD",
+                self.date
+            )));
+        }
+        if self.start_hour > 23 || self.start_minute > 59 {
+            return Err(Error::Invalid(format!(
+                "{}:{:02} is not a time of day",
+                self.start_hour, self.start_minute
+            )));
+        }
+        if self.duration_minutes == 0 || self.duration_minutes > MAX_DURATION_MINUTES {
+            return Err(Error::Invalid(format!(
+                "durationMinutes {} is not in 1-{MAX_DURATION_MINUTES}",
+                self.duration_minutes
+            )));
+        }
+        Ok(())
+    }
+
+    /// Start and end in UTC epoch milliseconds. The end is the start plus the
+    /// duration in elapsed time, so a block the planner let run past midnight (see
+    /// `checkExtendsPastMidnight`), or across a DST change, ends on the right day at
+    /// the right hour.
+    pub fn span(&self, tz: Tz) -> Option<(i64, i64)> {
+        let date = NaiveDate::parse_from_str(&self.date, "%Y-%m-%d").ok()?;
+        let time = NaiveTime::from_hms_opt(self.start_hour.into(), self.start_minute.into(), 0)?;
+        let start = local_to_utc(tz, date.and_time(time));
+        let end = start + Duration::minutes(self.duration_minutes.into());
+        Some((start.timestamp_millis(), end.timestamp_millis()))
+    }
+}
+
+/// The planner's schedule, kept in `planner-schedule.json` whatever the storage
+/// backend. It used to live in the webview's localStorage.
+#[derive(Clone)]
+pub struct PlannerStore {
+    path: PathBuf,
+    events: Arc<dyn Events>,
+    lock: Arc<Mutex<()>>,
+}
+
+impl PlannerStore {
+    pub fn new(dir: PathBuf, events: Arc<dyn Events>) -> Self {
+        Self {
+            path: dir.join(PLANNER_FILE),
+            events,
+            lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    pub fn list(&self) -> Result<Vec<ScheduledTask>> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(persist::load_json(&self.path, self.events.as_ref())?.unwrap_or_default())
+    }
+
+    /// Replaces the whole schedule; nothing is written if any block is invalid.
+    pub fn replace(&self, blocks: Vec<ScheduledTask>) -> Result<()> {
+        for block in &blocks {
+            block.validate()?;
+        }
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        persist::write_json(&self.path, &blocks)?;
+        self.events
+            .emit(events::PLANNER_CHANGED, serde_json::to_value(&blocks)?);
+        Ok(())
+    }
+}
+
+/// A VEVENT for every block. Times are written in UTC, converted from `tz`.
+pub fn to_ics(blocks: &[ScheduledTask], tasks: &[Task], tz: Tz, now: i64) -> String {
+    let mut cal = Calendar::new();
+    for block in blocks {
+        let Some((start, end)) = block.span(tz) else {
+            continue;
+        };
+        let live = tasks.iter().find(|t| t.id == block.task_id);
+        let title = live
+            .map(|t| t.title.clone())
+            .or_else(|| block.task.get("title")?.as_str().map(str::to_string))
+            .unwrap_or_else(|| "Scheduled task".into());
+        cal.line("BEGIN", "VEVENT");
+        cal.text(
+            "UID",
+            &format!(
+                "{}-{}-{:02}{:02}@shakshuka",
+                block.task_id,
+                block.date.replace('-', ""),
+                block.start_hour,
+                block.start_minute
+            ),
+        );
+        cal.line("DTSTAMP", &ical::utc_stamp(now));
+        cal.line("DTSTART", &ical::utc_stamp(start));
+        cal.line("DTEND", &ical::utc_stamp(end));
+        cal.text("SUMMARY", &title);
+        if let Some(notes) = live.and_then(|t| t.notes.as_ref()) {
+            cal.text("DESCRIPTION", notes);
+        }
+        if let Some(tags) = live.and_then(|t| t.tags.as_ref()).filter(|t| !t.is_empty()) {
+            let tags: Vec<_> = tags.iter().map(|t| ical::escape_text(t)).collect();
+            cal.line("CATEGORIES", &tags.join(","));
+        }
+        cal.line("TRANSP", "OPAQUE");
+        cal.line("END", "VEVENT");
+    }
+    cal.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::ical::content_lines;
+
+    fn block(date: &str, hour: u8, minute: u8, duration: u32) -> ScheduledTask {
+        ScheduledTask {
+            task_id: "a".into(),
+            task: json!({ "id": "a", "title": "Deep work" }),
+            start_hour: hour,
+            start_minute: minute,
+            duration_minutes: duration,
+            date: date.into(),
+        }
+    }
+
+    #[test]
+    fn blocks_crossing_midnight_end_the_next_day() {
+        let tz: Tz = "America/New_York".parse().unwrap();
+        let late = block("2025-03-01", 23, 30, 90);
+        let ics = to_ics(&[late], &[], tz, 0);
+        let lines = content_lines(&ics);
+        let value = |name: &str| lines.iter().find(|l| l.name == name).unwrap().value.clone();
+        // 23:30 EST is 04:30Z the next day; 90 minutes later is 06:00Z.
+        assert_eq!(value("DTSTART"), "20250302T043000Z");
+        assert_eq!(value("DTEND"), "20250302T060000Z");
+        assert_eq!(value("SUMMARY"), "Deep work");
+
+        // Across the spring-forward night: 01:00 EST plus two hours is 04:00 EDT.
+        let (start, end) = block("2025-03-09", 1, 0, 120).span(tz).unwrap();
+        assert_eq!(ical::utc_stamp(start), "20250309T060000Z");
+        assert_eq!(ical::utc_stamp(end), "20250309T080000Z");
+    }
+
+    #[test]
+    fn the_store_rejects_invalid_blocks() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = PlannerStore::new(dir.path().to_path_buf(), Arc::new(()));
+        assert!(store.list().unwrap().is_empty());
+
+        store.replace(vec![block("2025-03-01", 9, 30, 60)]).unwrap();
+        for bad in [
+            block("03/01/2025", 9, 0, 30),
+            block("2025-03-01", 24, 0, 30),
+            block("2025-03-01", 9, 0, 0),
+        ] {
+            assert!(store.replace(vec![bad]).is_err());
+        }
+        assert_eq!(store.list().unwrap(), [block("2025-03-01", 9, 30, 60)]);
+    }
+}
@@ -0,0 +1,312 @@
+//! Strike history for use outside the app: CSV of the raw entries, and a Markdown
+//! status report of a day or week, grouped by tag.
+
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::strikes::{StrikeAction, StrikeEntry};
+use crate::tasks::Task;
+
+const UNTAGGED: &str = "Untagged";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Period {
+    Day,
+    /// Monday to Sunday.
+    Week,
+}
+
+impl Period {
+    /// The first and last day of the period holding `date` (YYYY-MM-DD).
+    pub fn range(self, date: &str) -> Result<(String, String)> {
+        let day = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| Error::Invalid(format!("{date:?} is not a YYYY-MM-DD date")))?;
+        let (from, to) = match self {
+            Period::Day => (day, day),
+            Period::Week => {
+                let monday = day - Days::new(day.weekday().num_days_from_monday().into());
+                (monday, monday + Days::new(6))
+            }
+        };
+        let format = |d: NaiveDate| d.format("%Y-%m-%d").to_string();
+        Ok((format(from), format(to)))
+    }
+}
+
+fn action(entry: &StrikeEntry) -> StrikeAction {
+    entry.action.unwrap_or(StrikeAction::Strike)
+}
+
+fn title<'a>(tasks: &'a [Task], id: &str) -> Option<&'a str> {
+    tasks.iter().find(|t| t.id == id).map(|t| t.title.as_str())
+}
+
+/// A CSV field, quoted when it holds a separator, quote or line break (RFC 4180).
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// `date,taskId,title,action,note,ts`, one row per entry in time order. `title` is
+/// empty for deleted tasks; `ts` is epoch milliseconds.
+pub fn strikes_csv(entries: &[StrikeEntry], tasks: &[Task]) -> String {
+    let mut entries: Vec<_> = entries.iter().collect();
+    entries.sort_by_key(|e| e.ts);
+    let mut out = String::from("date,taskId,title,action,note,ts\r\n");
+    for entry in entries {
+        let action = match action(entry) {
+            StrikeAction::Strike => "strike",
+            StrikeAction::Completed => "completed",
+            StrikeAction::Expired => "expired",
+        };
+        let row = [
+            csv_field(&entry.date),
+            csv_field(&entry.task_id),
+            csv_field(title(tasks, &entry.task_id).unwrap_or_default()),
+            action.to_string(),
+            csv_field(entry.note.as_deref().unwrap_or_default()),
+            entry.ts.to_string(),
+        ];
+        out.push_str(&row.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// Text safe to put in a Markdown list item: one line, no emphasis or links.
+fn markdown_text(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' | '*' | '_' | '`' | '[' | ']' | '<' | '>' | '#' | '|' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\r' | '\n' => out.push(' '),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// One task's entries of one kind in the period.
+#[derive(Default)]
+struct Line {
+    task_id: String,
+    title: String,
+    count: usize,
+    notes: Vec<String>,
+}
+
+/// A Markdown report of the entries dated `from..=to`: totals, then completed,
+/// struck and expired tasks under each of their tags. A task with several tags is
+/// listed under each.
+pub fn markdown_report(
+    period: Period,
+    from: &str,
+    to: &str,
+    entries: &[StrikeEntry],
+    tasks: &[Task],
+) -> String {
+    let mut entries: Vec<_> = entries
+        .iter()
+        .filter(|e| (from..=to).contains(&e.date.as_str()))
+        .collect();
+    entries.sort_by_key(|e| e.ts);
+
+    // (untagged, tag) -> completed/struck/expired -> a line per task, in order of
+    // first appearance.
+    let sections = [
+        (StrikeAction::Completed, "Completed"),
+        (StrikeAction::Strike, "Struck"),
+        (StrikeAction::Expired, "Expired"),
+    ];
+    let mut totals = [0usize; 3];
+    let mut groups: BTreeMap<(bool, String), [Vec<Line>; 3]> = BTreeMap::new();
+    for entry in entries {
+        let section = sections
+            .iter()
+            .position(|(a, _)| *a == action(entry))
+            .unwrap_or(1);
+        totals[section] += 1;
+        let task = tasks.iter().find(|t| t.id == entry.task_id);
+        let tags = task
+            .and_then(|t| t.tags.clone())
+            .filter(|t| !t.is_empty())
+            .unwrap_or_default();
+        let keys: Vec<_> = match tags.is_empty() {
+            // Untagged sorts after every tag.
+            true => vec![(true, UNTAGGED.to_string())],
+            false => tags.into_iter().map(|t| (false, t)).collect(),
+        };
+        for key in keys {
+            let lines = &mut groups.entry(key).or_default()[section];
+            let index = match lines.iter().position(|l| l.task_id == entry.task_id) {
+                Some(index) => index,
+                None => {
+                    let title = task.map_or_else(
+                        || format!("(deleted task {})", entry.task_id),
+                        |t| t.title.clone(),
+                    );
+                    lines.push(Line {
+                        task_id: entry.task_id.clone(),
+                        title,
+                        ..Line::default()
+                    });
+                    lines.len() - 1
+                }
+            };
+            let line = &mut lines[index];
+            line.count += 1;
+            if let Some(note) = entry
+                .note
+                .as_deref()
+                .map(str::trim)
+                .filter(|n| !n.is_empty())
+            {
+                line.notes.push(note.to_string());
+            }
+        }
+    }
+
+    let mut out = match period {
+        Period::Day => format!("# Daily report: {from}\n\n"),
+        Period::Week => format!("# Weekly report: {from} to {to}\n\n"),
+    };
+    out.push_str(&format!(
+        "**{} completed, {} struck, {} expired**\n",
+        totals[0], totals[1], totals[2]
+    ));
+    if groups.is_empty() {
+        out.push_str("\nNothing recorded.\n");
+    }
+    for ((_, tag), lines) in &groups {
+        out.push_str(&format!("\n## {}\n", markdown_text(tag)));
+        for ((_, heading), lines) in sections.iter().zip(lines) {
+            if lines.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("\n### {heading}\n\n"));
+            for line in lines {
+                out.push_str(&format!("- {}", markdown_text(&line.title)));
+                if line.count > 1 {
+                    out.push_str(&format!(" (×{})", line.count));
+                }
+                if !line.notes.is_empty() {
+                    let notes: Vec<_> = line.notes.iter().map(|n| markdown_text(n)).collect();
+                    out.push_str(&format!(": {}", notes.join("; ")));
+                }
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: &str, title: &str, tags: &[&str]) -> Task {
+        Task {
+            id: id.into(),
+            revision: 0,
+            title: title.into(),
+            notes: None,
+            completed: false,
+            created_at: 0,
+            updated_at: 0,
+            due_hour: None,
+            due_date: None,
+            tags: (!tags.is_empty()).then(|| tags.iter().map(|t| t.to_string()).collect()),
+        }
+    }
+
+    fn entry(
+        id: &str,
+        date: &str,
+        ts: i64,
+        action: Option<StrikeAction>,
+        note: Option<&str>,
+    ) -> StrikeEntry {
+        StrikeEntry {
+            task_id: id.into(),
+            date: date.into(),
+            note: note.map(str::to_string),
+            ts,
+            action,
+        }
+    }
+
+    #[test]
+    fn csv_quotes_fields_and_defaults_the_action() {
+        let tasks = [task("a", "Write \"the\" report, v2", &[])];
+        let entries = [
+            entry("gone", "2025-03-04", 2, Some(StrikeAction::Expired), None),
+            entry("a", "2025-03-03", 1, None, Some("line one\nline two")),
+        ];
+        assert_eq!(
+            strikes_csv(&entries, &tasks),
+            "date,taskId,title,action,note,ts\r\n\
+             2025-03-03,a,\"Write \"\"the\"\" report, v2\",strike,\"line one\nline two\",1\r\n\
+             2025-03-04,gone,,expired,,2\r\n"
+        );
+    }
+
+    #[test]
+    fn weekly_report_groups_by_tag() {
+        assert_eq!(
+            Period::Week.range("2025-03-05").unwrap(),
+            ("2025-03-03".into(), "2025-03-09".into())
+        );
+        assert!(Period::Day.range("03/05/2025").is_err());
+
+        let tasks = [
+            task("gym", "Gym", &["health"]),
+            task("doc", "Design *doc*", &["work", "health"]),
+            task("misc", "Tidy desk", &[]),
+        ];
+        let entries = [
+            entry(
+                "gym",
+                "2025-03-03",
+                1,
+                Some(StrikeAction::Strike),
+                Some("sick"),
+            ),
+            entry(
+                "gym",
+                "2025-03-05",
+                2,
+                Some(StrikeAction::Strike),
+                Some("late"),
+            ),
+            entry("doc", "2025-03-04", 3, Some(StrikeAction::Completed), None),
+            entry("misc", "2025-03-09", 4, Some(StrikeAction::Expired), None),
+            entry("gym", "2025-03-10", 5, Some(StrikeAction::Completed), None),
+        ];
+        let report = markdown_report(Period::Week, "2025-03-03", "2025-03-09", &entries, &tasks);
+        assert_eq!(
+            report,
+            "# Weekly report: 2025-03-03 to 2025-03-09\n\n\
+             **1 completed, 2 struck, 1 expired**\n\
+             \n## health\n\
+             \n### Completed\n\n- Design \\*doc\\*\n\
+             \n### Struck\n\n- Gym (×2): sick; late\n\
+             \n## work\n\
+             \n### Completed\n\n- Design \\*doc\\*\n\
+             \n## Untagged\n\
+             \n### Expired\n\n- Tidy desk\n"
+        );
+
+        let empty = markdown_report(Period::Day, "2025-03-11", "2025-03-11", &entries, &tasks);
+        assert!(empty.ends_with("**0 completed, 0 struck, 0 expired**\n\nNothing recorded.\n"));
+    }
+}
@@ -0,0 +1,172 @@
+use std::fmt;
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::error::{Error, Result};
+use crate::persist::{self, DATA_FILES};
+use crate::tasks::TASKS_FILE;
+
+/// Version written into every data file's `{ "version": n, "data": ... }` envelope.
+/// Files from before the envelope existed are bare documents and count as version 0.
+pub const CURRENT_VERSION: u64 = 1;
+
+/// Upgrades the `data` of one file from version `n` to `n + 1`, where `n` is the
+/// index into [`MIGRATIONS`]. Migrations must accept any JSON: shape problems are
+/// left for deserialisation to reject, so the file is quarantined rather than rewritten.
+type Migration = fn(file: &str, data: Value) -> Value;
+
+const MIGRATIONS: &[Migration] = &[v0_to_v1];
+
+/// v0 -> v1: introduce the envelope. Old frontends computed `revision + 1` on tasks
+/// without one, which serialised as `null`; reset those to 0.
+fn v0_to_v1(file: &str, mut data: Value) -> Value {
+    if file == TASKS_FILE {
+        for task in data.as_array_mut().into_iter().flatten() {
+            if let Some(obj) = task.as_object_mut() {
+                if !obj.get("revision").is_some_and(Value::is_u64) {
+                    obj.insert("revision".into(), json!(0));
+                }
+            }
+        }
+    }
+    data
+}
+
+#[derive(Serialize)]
+struct Envelope<'a, T: ?Sized> {
+    version: u64,
+    data: &'a T,
+}
+
+/// Wraps `data` in the current envelope, ready to be written.
+pub fn wrap<T: Serialize + ?Sized>(data: &T) -> Result<Value> {
+    Ok(serde_json::to_value(Envelope {
+        version: CURRENT_VERSION,
+        data,
+    })?)
+}
+
+/// Splits a parsed file into its version and payload.
+fn split(doc: Value) -> (u64, Value) {
+    match doc {
+        Value::Object(mut obj)
+            if obj.len() == 2 && obj.contains_key("data") && obj.contains_key("version") =>
+        {
+            match obj.get("version").and_then(Value::as_u64) {
+                Some(version) => (version, obj.remove("data").unwrap_or_default()),
+                None => (0, Value::Object(obj)),
+            }
+        }
+        other => (0, other),
+    }
+}
+
+/// Unwraps a parsed file and runs whatever migrations it is missing, returning its
+/// original version and the up-to-date payload.
+pub fn upgrade(file: &str, doc: Value) -> Result<(u64, Value)> {
+    let (version, mut data) = split(doc);
+    if version > CURRENT_VERSION {
+        return Err(Error::UnsupportedVersion {
+            file: file.to_string(),
+            version,
+        });
+    }
+    for migrate in &MIGRATIONS[version as usize..] {
+        data = migrate(file, data);
+    }
+    Ok((version, data))
+}
+
+/// One file that is (or would be) rewritten by [`migrate_dir`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Migrated {
+    pub file: String,
+    pub from: u64,
+    pub to: u64,
+}
+
+impl fmt::Display for Migrated {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: v{} -> v{}", self.file, self.from, self.to)
+    }
+}
+
+/// Brings every data file in `dir` up to [`CURRENT_VERSION`]. With `check` set nothing
+/// is written and the result only lists what would change. Files that are not valid
+/// JSON are skipped; the loader quarantines them on first read.
+pub fn migrate_dir(dir: &Path, check: bool) -> Result<Vec<Migrated>> {
+    let mut migrated = Vec::new();
+    for name in std::iter::once(TASKS_FILE).chain(DATA_FILES.iter().copied()) {
+        let path = dir.join(name);
+        let Ok(Some(bytes)) = persist::read_file(&path) else {
+            continue;
+        };
+        let Ok(doc) = serde_json::from_slice::<Value>(&bytes) else {
+            continue;
+        };
+        let (from, data) = upgrade(name, doc)?;
+        if from == CURRENT_VERSION {
+            continue;
+        }
+        if !check {
+            persist::write_json(&path, &data)?;
+        }
+        migrated.push(Migrated {
+            file: name.to_string(),
+            from,
+            to: CURRENT_VERSION,
+        });
+    }
+    Ok(migrated)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn bare_documents_are_version_zero_and_get_upgraded() {
+        let doc = json!([{ "id": "a", "revision": null, "title": "t" }]);
+        let (from, data) = upgrade(TASKS_FILE, doc).unwrap();
+        assert_eq!(from, 0);
+        assert_eq!(data[0]["revision"], 0);
+    }
+
+    #[test]
+    fn enveloped_documents_are_unwrapped() {
+        let doc = json!({ "version": CURRENT_VERSION, "data": { "resetHour": 9 } });
+        let (from, data) = upgrade("settings.json", doc).unwrap();
+        assert_eq!(from, CURRENT_VERSION);
+        assert_eq!(data, json!({ "resetHour": 9 }));
+    }
+
+    #[test]
+    fn newer_versions_are_refused() {
+        let doc = json!({ "version": CURRENT_VERSION + 1, "data": [] });
+        assert!(matches!(
+            upgrade("strikes.json", doc),
+            Err(Error::UnsupportedVersion { .. })
+        ));
+    }
+
+    #[test]
+    fn check_mode_reports_without_writing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("strikes.json");
+        fs::write(&path, "[]").unwrap();
+
+        let planned = migrate_dir(dir.path(), true).unwrap();
+        assert_eq!(planned.len(), 1);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "[]");
+
+        migrate_dir(dir.path(), false).unwrap();
+        let doc: Value = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        assert_eq!(doc, json!({ "version": CURRENT_VERSION, "data": [] }));
+        assert!(migrate_dir(dir.path(), true).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,579 @@
+//! Full-text search over every task the app knows about: active, completed and
+//! deleted ones (rebuilt from their history), their titles, notes and tags, and the
+//! notes written when striking them.
+//!
+//! An in-memory inverted index, built on the first search and again after tasks or
+//! strikes change. Query words match as prefixes ("meet" finds "meeting"), every word
+//! has to match, and hits are ranked BM25-style with titles and tags weighing more
+//! than notes.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::Result;
+use crate::events::{self, Events};
+use crate::history::{self, HistoryQuery, HistoryStore};
+use crate::storage::Storage;
+use crate::strikes::{StrikeAction, StrikeEntry};
+use crate::tasks::Task;
+
+const DEFAULT_LIMIT: usize = 50;
+const MAX_SNIPPETS: usize = 3;
+/// Characters of context kept on each side of the first hit in a long text.
+const CONTEXT: usize = 40;
+/// A word that only starts with the query word counts this much of a whole-word match.
+const PREFIX_WEIGHT: f64 = 0.5;
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Field {
+    Title,
+    Tag,
+    Notes,
+    /// The note of a strike entry.
+    Strike,
+}
+
+impl Field {
+    fn weight(self) -> f64 {
+        match self {
+            Field::Title => 3.0,
+            Field::Tag => 2.0,
+            Field::Notes | Field::Strike => 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Active,
+    Completed,
+    Deleted,
+}
+
+/// A run of snippet text, `hit` where it matched the query.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Part {
+    pub text: String,
+    pub hit: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snippet {
+    pub field: Field,
+    /// The strike's date, for [`Field::Strike`].
+    pub date: Option<String>,
+    pub parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    /// As it is now, or as it was when deleted.
+    pub task: Task,
+    pub status: Status,
+    /// The date of the task's most recent completion, if any was recorded.
+    pub completed_on: Option<String>,
+    pub score: f64,
+    pub snippets: Vec<Snippet>,
+}
+
+/// Lowercased words and where they are in `text` (byte ranges of the original).
+fn words(text: &str) -> Vec<(String, usize, usize)> {
+    let mut out = Vec::new();
+    let mut start = None;
+    for (i, c) in text
+        .char_indices()
+        .chain(std::iter::once((text.len(), ' ')))
+    {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                out.push((text[s..i].to_lowercase(), s, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+struct Text {
+    field: Field,
+    date: Option<String>,
+    text: String,
+}
+
+struct Doc {
+    task: Task,
+    status: Status,
+    completed_on: Option<String>,
+    texts: Vec<Text>,
+    /// Words across all texts, for length normalisation.
+    length: usize,
+}
+
+/// Where one word occurs in one text of one document.
+struct Posting {
+    doc: usize,
+    text: usize,
+    ranges: Vec<(usize, usize)>,
+}
+
+pub struct Index {
+    docs: Vec<Doc>,
+    words: BTreeMap<String, Vec<Posting>>,
+    average_length: f64,
+}
+
+impl Index {
+    /// Indexes `tasks` plus `deleted` (their last known state), with the strike notes
+    /// of both.
+    pub fn build(tasks: Vec<Task>, deleted: Vec<Task>, strikes: &[StrikeEntry]) -> Self {
+        let mut strikes: Vec<_> = strikes.iter().collect();
+        strikes.sort_by_key(|s| s.ts);
+        let mut docs: Vec<Doc> = Vec::new();
+        let mut by_id = HashMap::new();
+        let current = tasks.into_iter().map(|t| (t, false));
+        for (task, deleted) in current.chain(deleted.into_iter().map(|t| (t, true))) {
+            if by_id.contains_key(&task.id) {
+                continue;
+            }
+            let status = match (deleted, task.completed) {
+                (true, _) => Status::Deleted,
+                (false, true) => Status::Completed,
+                (false, false) => Status::Active,
+            };
+            let mut texts = vec![Text {
+                field: Field::Title,
+                date: None,
+                text: task.title.clone(),
+            }];
+            for tag in task.tags.iter().flatten() {
+                texts.push(Text {
+                    field: Field::Tag,
+                    date: None,
+                    text: tag.clone(),
+                });
+            }
+            if let Some(notes) = task.notes.as_ref().filter(|n| !n.trim().is_empty()) {
+                texts.push(Text {
+                    field: Field::Notes,
+                    date: None,
+                    text: notes.clone(),
+                });
+            }
+            by_id.insert(task.id.clone(), docs.len());
+            docs.push(Doc {
+                task,
+                status,
+                completed_on: None,
+                texts,
+                length: 0,
+            });
+        }
+        for strike in strikes {
+            let Some(doc) = by_id.get(&strike.task_id).map(|&d| &mut docs[d]) else {
+                continue;
+            };
+            if strike.action == Some(StrikeAction::Completed) {
+                doc.completed_on = Some(strike.date.clone());
+            }
+            if let Some(note) = strike.note.as_ref().filter(|n| !n.trim().is_empty()) {
+                doc.texts.push(Text {
+                    field: Field::Strike,
+                    date: Some(strike.date.clone()),
+                    text: note.clone(),
+                });
+            }
+        }
+
+        let mut index: BTreeMap<String, Vec<Posting>> = BTreeMap::new();
+        let mut total = 0;
+        for (d, doc) in docs.iter_mut().enumerate() {
+            for (t, text) in doc.texts.iter().enumerate() {
+                for (word, start, end) in words(&text.text) {
+                    doc.length += 1;
+                    let postings = index.entry(word).or_default();
+                    match postings.last_mut() {
+                        Some(p) if p.doc == d && p.text == t => p.ranges.push((start, end)),
+                        _ => postings.push(Posting {
+                            doc: d,
+                            text: t,
+                            ranges: vec![(start, end)],
+                        }),
+                    }
+                }
+            }
+            total += doc.length;
+        }
+        let average_length = match docs.len() {
+            0 => 1.0,
+            n => (total as f64 / n as f64).max(1.0),
+        };
+        Self {
+            docs,
+            words: index,
+            average_length,
+        }
+    }
+
+    /// The best `limit` matches for `query`, best first.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<SearchHit> {
+        let mut terms: Vec<String> = words(query).into_iter().map(|(w, ..)| w).collect();
+        let mut seen = HashSet::new();
+        terms.retain(|t| seen.insert(t.clone()));
+        if terms.is_empty() {
+            return Vec::new();
+        }
+        let n = self.docs.len() as f64;
+        let mut scores: HashMap<usize, f64> = HashMap::new();
+        let mut hits: HashMap<(usize, usize), Vec<(usize, usize)>> = HashMap::new();
+        for (i, term) in terms.iter().enumerate() {
+            // Weighted occurrences per document, over every word starting with `term`.
+            let mut matched: HashMap<usize, f64> = HashMap::new();
+            let mut idf: HashMap<usize, f64> = HashMap::new();
+            for (word, postings) in self.words.range(term.clone()..) {
+                if !word.starts_with(term.as_str()) {
+                    break;
+                }
+                let docs: HashSet<_> = postings.iter().map(|p| p.doc).collect();
+                let df = docs.len() as f64;
+                let word_idf = (1.0 + (n - df + 0.5) / (df + 0.5)).ln();
+                let exact = if word == term { 1.0 } else { PREFIX_WEIGHT };
+                for p in postings {
+                    let field = self.docs[p.doc].texts[p.text].field;
+                    *matched.entry(p.doc).or_default() +=
+                        field.weight() * exact * p.ranges.len() as f64;
+                    let best = idf.entry(p.doc).or_default();
+                    *best = best.max(word_idf);
+                    hits.entry((p.doc, p.text)).or_default().extend(&p.ranges);
+                }
+            }
+            // Every term has to match.
+            if i == 0 {
+                scores = matched.keys().map(|&d| (d, 0.0)).collect();
+            } else {
+                scores.retain(|d, _| matched.contains_key(d));
+            }
+            for (doc, score) in &mut scores {
+                let tf = matched[doc];
+                let norm = 1.0 - B + B * self.docs[*doc].length as f64 / self.average_length;
+                *score += idf[doc] * tf * (K1 + 1.0) / (tf + K1 * norm);
+            }
+        }
+
+        let mut ranked: Vec<_> = scores.into_iter().collect();
+        ranked.sort_by(|(a, sa), (b, sb)| {
+            sb.total_cmp(sa).then(
+                self.docs[*b]
+                    .task
+                    .updated_at
+                    .cmp(&self.docs[*a].task.updated_at),
+            )
+        });
+        ranked.truncate(limit);
+        ranked
+            .into_iter()
+            .map(|(d, score)| {
+                let doc = &self.docs[d];
+                let snippets = (0..doc.texts.len())
+                    .filter_map(|t| {
+                        let ranges = hits.get(&(d, t))?;
+                        Some(snippet(&doc.texts[t], ranges))
+                    })
+                    .take(MAX_SNIPPETS)
+                    .collect();
+                SearchHit {
+                    task: doc.task.clone(),
+                    status: doc.status,
+                    completed_on: doc.completed_on.clone(),
+                    score,
+                    snippets,
+                }
+            })
+            .collect()
+    }
+}
+
+/// The matched part of `text`: whole if short, otherwise around its first hit.
+fn snippet(text: &Text, ranges: &[(usize, usize)]) -> Snippet {
+    let mut ranges = ranges.to_vec();
+    ranges.sort();
+    ranges.dedup();
+    let s = &text.text;
+    let first = ranges.first().map_or(0, |r| r.0);
+    let start = s[..first]
+        .char_indices()
+        .rev()
+        .nth(CONTEXT - 1)
+        .map_or(0, |(i, _)| i);
+    let end = s[first..]
+        .char_indices()
+        .nth(2 * CONTEXT)
+        .map_or(s.len(), |(i, _)| first + i);
+
+    let mut parts = Vec::new();
+    let mut push = |text: &str, hit: bool| {
+        let text = text.replace(['\r', '\n'], " ");
+        if !text.is_empty() {
+            parts.push(Part { text, hit });
+        }
+    };
+    if start > 0 {
+        push("…", false);
+    }
+    let mut at = start;
+    for (from, to) in ranges {
+        if from < at || to > end {
+            continue;
+        }
+        push(&s[at..from], false);
+        push(&s[from..to], true);
+        at = to;
+    }
+    push(&s[at..end], false);
+    if end < s.len() {
+        push("…", false);
+    }
+    Snippet {
+        field: text.field,
+        date: text.date.clone(),
+        parts,
+    }
+}
+
+/// Set when tasks or strikes change, so the next search rebuilds the index.
+#[derive(Clone, Default)]
+pub struct Stale(Arc<AtomicBool>);
+
+impl Stale {
+    /// `inner`, marking the index stale whenever tasks or strikes change (including
+    /// edits made outside the app and files restored from `.bak`).
+    pub fn watch(&self, inner: Arc<dyn Events>) -> Arc<dyn Events> {
+        Arc::new(Watching {
+            inner,
+            stale: self.clone(),
+        })
+    }
+}
+
+struct Watching {
+    inner: Arc<dyn Events>,
+    stale: Stale,
+}
+
+impl Events for Watching {
+    fn emit(&self, event: &str, payload: Value) {
+        if matches!(
+            event,
+            events::TASKS_CHANGED | events::STRIKES_CHANGED | events::DATA_RECOVERED
+        ) {
+            self.stale.0.store(true, Ordering::SeqCst);
+        }
+        self.inner.emit(event, payload);
+    }
+}
+
+pub struct SearchIndex {
+    storage: Arc<dyn Storage>,
+    history: HistoryStore,
+    stale: Stale,
+    index: Mutex<Option<Arc<Index>>>,
+}
+
+impl SearchIndex {
+    pub fn new(storage: Arc<dyn Storage>, history: HistoryStore, stale: Stale) -> Self {
+        Self {
+            storage,
+            history,
+            stale,
+            index: Mutex::new(None),
+        }
+    }
+
+    pub fn search(&self, query: &str, limit: Option<usize>) -> Result<Vec<SearchHit>> {
+        Ok(self.index()?.search(query, limit.unwrap_or(DEFAULT_LIMIT)))
+    }
+
+    fn index(&self) -> Result<Arc<Index>> {
+        let mut index = self.index.lock().unwrap_or_else(|e| e.into_inner());
+        // Cleared before reading, so a change made while building marks it stale again.
+        let stale = self.stale.0.swap(false, Ordering::SeqCst);
+        if let Some(built) = index.as_ref().filter(|_| !stale) {
+            return Ok(built.clone());
+        }
+        // A failed build leaves it stale, so the next search tries again.
+        let built = Arc::new(
+            self.build()
+                .inspect_err(|_| self.stale.0.store(true, Ordering::SeqCst))?,
+        );
+        *index = Some(built.clone());
+        Ok(built)
+    }
+
+    fn build(&self) -> Result<Index> {
+        let tasks = self.storage.load_tasks()?;
+        let live: HashSet<_> = tasks.iter().map(|t| t.id.clone()).collect();
+        let updates = self.history.query(&HistoryQuery::default())?;
+        let deleted = history::last_states(&updates)
+            .into_iter()
+            .filter(|(id, _)| !live.contains(id))
+            .map(|(_, task)| task)
+            .collect();
+        let strikes = self.storage.load_strikes()?;
+        Ok(Index::build(tasks, deleted, &strikes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryStorage;
+    use crate::tasks::{TaskDraft, TaskStore};
+
+    fn task(id: &str, title: &str, notes: Option<&str>, tags: &[&str], completed: bool) -> Task {
+        Task {
+            id: id.into(),
+            revision: 0,
+            title: title.into(),
+            notes: notes.map(str::to_string),
+            completed,
+            created_at: 0,
+            updated_at: 0,
+            due_hour: None,
+            due_date: None,
+            tags: (!tags.is_empty()).then(|| tags.iter().map(|t| t.to_string()).collect()),
+        }
+    }
+
+    fn marked(snippet: &Snippet) -> String {
+        snippet
+            .parts
+            .iter()
+            .map(|p| match p.hit {
+                true => format!("[{}]", p.text),
+                false => p.text.clone(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn ranks_prefix_matches_across_fields() {
+        let tasks = vec![
+            task(
+                "a",
+                "Plan the offsite",
+                Some("Book a meeting room"),
+                &[],
+                false,
+            ),
+            task("b", "Meeting notes", None, &["meetings"], true),
+            task("c", "Groceries", None, &[], false),
+        ];
+        let strikes = [StrikeEntry {
+            task_id: "c".into(),
+            date: "2025-03-14".into(),
+            note: Some("Skipped, meeting ran long".into()),
+            ts: 1,
+            action: Some(StrikeAction::Strike),
+        }];
+        let index = Index::build(tasks, Vec::new(), &strikes);
+
+        let hits = index.search("meet", 10);
+        let ids: Vec<_> = hits.iter().map(|h| h.task.id.as_str()).collect();
+        // Title and tag outweigh notes.
+        assert_eq!(ids[0], "b");
+        assert_eq!(hits[0].status, Status::Completed);
+        assert_eq!(marked(&hits[0].snippets[0]), "[Meeting] notes");
+        assert_eq!(hits[0].snippets[1].field, Field::Tag);
+        let c = hits.iter().find(|h| h.task.id == "c").unwrap();
+        assert_eq!(c.snippets[0].field, Field::Strike);
+        assert_eq!(c.snippets[0].date.as_deref(), Some("2025-03-14"));
+        assert_eq!(marked(&c.snippets[0]), "Skipped, [meeting] ran long");
+
+        // Every word has to match.
+        let hits = index.search("meeting room", 10);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(marked(&hits[0].snippets[0]), "Book a [meeting] [room]");
+        // Repeated words count once, wherever they repeat.
+        assert_eq!(index.search("meeting room meeting", 10), hits);
+        assert!(index.search("  ", 10).is_empty());
+    }
+
+    #[test]
+    fn long_texts_are_cut_around_the_hit() {
+        let notes = format!("{} needle {}", "hay ".repeat(30), "stack ".repeat(30));
+        let index = Index::build(
+            vec![task("a", "T", Some(&notes), &[], false)],
+            Vec::new(),
+            &[],
+        );
+        let text = marked(&index.search("needle", 1)[0].snippets[0]);
+        assert!(text.starts_with("…"));
+        assert!(text.ends_with("…"));
+        assert!(text.contains("[needle]"));
+        assert!(text.chars().count() < notes.chars().count());
+    }
+
+    #[test]
+    fn finds_deleted_tasks_and_rebuilds_after_changes() {
+        let storage: Arc<dyn Storage> = Arc::new(MemoryStorage::default());
+        let stale = Stale::default();
+        let events = stale.watch(Arc::new(()));
+        let tasks = TaskStore::new(storage.clone(), events);
+        let search = SearchIndex::new(storage.clone(), tasks.history(), stale);
+        let draft = |title: &str| TaskDraft {
+            title: title.into(),
+            notes: None,
+            due_hour: None,
+            due_date: None,
+            tags: None,
+        };
+
+        let quarterly = tasks.create(draft("Quarterly taxes")).unwrap();
+        assert_eq!(search.search("tax", None).unwrap().len(), 1);
+
+        tasks.toggle(&quarterly.id).unwrap();
+        tasks.delete(&quarterly.id).unwrap();
+        tasks.create(draft("Tax receipts")).unwrap();
+        let hits = search.search("tax", None).unwrap();
+        let found: Vec<_> = hits
+            .iter()
+            .map(|h| (h.task.title.as_str(), h.status))
+            .collect();
+        assert!(found.contains(&("Quarterly taxes", Status::Deleted)));
+        assert!(found.contains(&("Tax receipts", Status::Active)));
+    }
+
+    #[test]
+    fn tasks_deleted_without_an_edit_are_found() {
+        let storage: Arc<dyn Storage> = Arc::new(MemoryStorage::default());
+        let stale = Stale::default();
+        let tasks = TaskStore::new(storage.clone(), stale.watch(Arc::new(())));
+        let search = SearchIndex::new(storage.clone(), tasks.history(), stale);
+        let dentist = tasks
+            .create(TaskDraft {
+                title: "Call the dentist".into(),
+                ..Default::default()
+            })
+            .unwrap();
+        tasks.delete(&dentist.id).unwrap();
+        let hits = search.search("dentist", None).unwrap();
+        assert_eq!((hits.len(), hits[0].status), (1, Status::Deleted));
+
+        // Retention can drop everything but the deletion record, which is enough.
+        let history = tasks.history().query(&HistoryQuery::default()).unwrap();
+        storage.save_updates(&history[1..]).unwrap();
+        search.stale.0.store(true, Ordering::SeqCst);
+        assert_eq!(search.search("dentist", None).unwrap()[0].task, dentist);
+    }
+}
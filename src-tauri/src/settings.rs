@@ -0,0 +1,53 @@
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::time::timezone;
+
+/// `settings.json`. Only the fields the backend acts on are typed; the rest of the
+/// frontend `AppSettings` (colours, names, flags) round-trips through `extra`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppSettings {
+    /// Hour (0-23) at which the day rolls over.
+    #[serde(default = "default_reset_hour")]
+    pub reset_hour: u8,
+    /// IANA timezone name.
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+    /// Task history older than this many days is dropped by compaction; 0 keeps it all.
+    #[serde(default = "default_history_retention_days")]
+    pub history_retention_days: u32,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl AppSettings {
+    /// The user's timezone; UTC if the saved name is not one.
+    pub fn tz(&self) -> Tz {
+        timezone(&self.timezone).unwrap_or(Tz::UTC)
+    }
+}
+
+fn default_reset_hour() -> u8 {
+    9
+}
+
+fn default_timezone() -> String {
+    "UTC".into()
+}
+
+fn default_history_retention_days() -> u32 {
+    90
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            reset_hour: default_reset_hour(),
+            timezone: default_timezone(),
+            history_retention_days: default_history_retention_days(),
+            extra: Map::new(),
+        }
+    }
+}
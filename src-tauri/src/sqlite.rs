@@ -0,0 +1,641 @@
+//! SQLite storage (cargo feature `sqlite`), laid out like `src/db/schema.ts` so the
+//! desktop and web builds describe the same tables. Strikes are appended row by row
+//! instead of rewriting the whole history.
+
+use std::path::Path;
+use std::sync::Mutex;
+
+use rusqlite::{params, Connection, OptionalExtension, Row, Transaction};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::error::Result;
+use crate::history::{HistoryQuery, TaskUpdate};
+use crate::settings::AppSettings;
+use crate::storage::{check_unique_ids, MonthlyStats, Storage};
+use crate::strikes::{month_of, StrikeAction, StrikeEntry};
+use crate::tasks::Task;
+use crate::time::{now_ms, utc_month};
+
+pub const DB_FILE: &str = "shakshuka.db";
+
+/// Schema steps; `PRAGMA user_version` records how many have run. Append, never edit.
+const SCHEMA: &[&str] = &[
+    r#"
+    CREATE TABLE tasks (
+        id TEXT PRIMARY KEY,
+        revision INTEGER NOT NULL,
+        title TEXT NOT NULL,
+        notes TEXT,
+        completed INTEGER NOT NULL DEFAULT 0,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL,
+        due_hour INTEGER,
+        due_date TEXT,
+        tags TEXT,
+        -- list order as shown in the UI (newest first)
+        position INTEGER NOT NULL
+    );
+    CREATE INDEX tasks_due_date ON tasks (due_date);
+
+    -- tasks.tags exploded for lookups by tag
+    CREATE TABLE task_tags (
+        task_id TEXT NOT NULL REFERENCES tasks (id) ON DELETE CASCADE,
+        tag TEXT NOT NULL,
+        PRIMARY KEY (tag, task_id)
+    );
+
+    -- No foreign key to tasks: history outlives deleted tasks.
+    CREATE TABLE strikes (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        task_id TEXT NOT NULL,
+        date TEXT NOT NULL,
+        note TEXT,
+        action TEXT,
+        created_at INTEGER NOT NULL
+    );
+    CREATE INDEX strikes_date ON strikes (date);
+    CREATE INDEX strikes_task_id ON strikes (task_id);
+
+    CREATE TABLE monthly_stats (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        month TEXT NOT NULL UNIQUE,
+        strikes_count INTEGER NOT NULL DEFAULT 0,
+        expired_count INTEGER NOT NULL DEFAULT 0,
+        completed_count INTEGER NOT NULL DEFAULT 0,
+        tasks_added_count INTEGER NOT NULL DEFAULT 0,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+    );
+
+    -- Single row; fields the backend does not interpret are kept as JSON in `extra`.
+    CREATE TABLE settings (
+        id INTEGER PRIMARY KEY CHECK (id = 1),
+        reset_hour INTEGER NOT NULL DEFAULT 9,
+        timezone TEXT NOT NULL DEFAULT 'UTC',
+        extra TEXT NOT NULL DEFAULT '{}',
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+    );
+"#,
+    r#"
+    CREATE TABLE task_updates (
+        update_id TEXT PRIMARY KEY,
+        task_id TEXT NOT NULL,
+        timestamp INTEGER NOT NULL,
+        diff TEXT NOT NULL,
+        full_snapshot TEXT
+    );
+    CREATE INDEX task_updates_task_id ON task_updates (task_id, timestamp);
+    CREATE INDEX task_updates_timestamp ON task_updates (timestamp);
+"#,
+];
+
+const TASK_COLUMNS: &str =
+    "id, revision, title, notes, completed, created_at, updated_at, due_hour, due_date, tags";
+
+const STRIKE_COLUMNS: &str = "task_id, date, note, created_at, action";
+
+const UPDATE_COLUMNS: &str = "update_id, task_id, timestamp, diff, full_snapshot";
+
+pub struct SqliteStore {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteStore {
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        Self::init(Connection::open(path)?)
+    }
+
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(mut conn: Connection) -> Result<Self> {
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "foreign_keys", true)?;
+        let version: usize = conn.pragma_query_value(None, "user_version", |r| r.get(0))?;
+        let tx = conn.transaction()?;
+        for (i, sql) in SCHEMA.iter().enumerate().skip(version) {
+            tx.execute_batch(sql)?;
+            tx.pragma_update(None, "user_version", i + 1)?;
+        }
+        tx.commit()?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn with_tx<T>(&self, f: impl FnOnce(&Transaction) -> Result<T>) -> Result<T> {
+        let mut conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        let tx = conn.transaction()?;
+        let out = f(&tx)?;
+        tx.commit()?;
+        Ok(out)
+    }
+
+    pub fn load_tasks(&self) -> Result<Vec<Task>> {
+        self.query_tasks("ORDER BY position", [])
+    }
+
+    /// Tasks due on `date` (YYYY-MM-DD), via the `due_date` index.
+    pub fn tasks_due_on(&self, date: &str) -> Result<Vec<Task>> {
+        self.query_tasks("WHERE due_date = ?1 ORDER BY position", [date])
+    }
+
+    pub fn tasks_with_tag(&self, tag: &str) -> Result<Vec<Task>> {
+        self.query_tasks(
+            "WHERE id IN (SELECT task_id FROM task_tags WHERE tag = ?1) ORDER BY position",
+            [tag],
+        )
+    }
+
+    fn query_tasks<P: rusqlite::Params>(&self, clause: &str, params: P) -> Result<Vec<Task>> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        let mut stmt = conn.prepare(&format!("SELECT {TASK_COLUMNS} FROM tasks {clause}"))?;
+        let rows = stmt.query_map(params, task_from_row)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Replaces the task list, counting tasks not seen before towards `tasks_added_count`.
+    pub fn save_tasks(&self, tasks: &[Task]) -> Result<()> {
+        check_unique_ids(tasks)?;
+        self.with_tx(|tx| {
+            let now = now_ms();
+            for task in tasks {
+                let known: bool = tx.query_row(
+                    "SELECT EXISTS (SELECT 1 FROM tasks WHERE id = ?1)",
+                    [&task.id],
+                    |r| r.get(0),
+                )?;
+                if !known {
+                    tx.execute(
+                        "INSERT INTO monthly_stats (month, tasks_added_count, created_at, updated_at)
+                         VALUES (?1, 1, ?2, ?2)
+                         ON CONFLICT (month) DO UPDATE SET
+                             tasks_added_count = tasks_added_count + 1, updated_at = ?2",
+                        params![utc_month(task.created_at), now],
+                    )?;
+                }
+            }
+            tx.execute("DELETE FROM tasks", [])?;
+            let mut insert = tx.prepare(&format!(
+                "INSERT INTO tasks ({TASK_COLUMNS}, position)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)"
+            ))?;
+            let mut insert_tag = tx.prepare("INSERT OR IGNORE INTO task_tags VALUES (?1, ?2)")?;
+            for (position, task) in tasks.iter().enumerate() {
+                let tags = task.tags.as_ref().map(serde_json::to_string).transpose()?;
+                insert.execute(params![
+                    task.id,
+                    task.revision,
+                    task.title,
+                    task.notes,
+                    task.completed,
+                    task.created_at,
+                    task.updated_at,
+                    task.due_hour,
+                    task.due_date,
+                    tags,
+                    position,
+                ])?;
+                for tag in task.tags.iter().flatten() {
+                    insert_tag.execute(params![task.id, tag])?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    pub fn load_strikes(&self) -> Result<Vec<StrikeEntry>> {
+        self.query_strikes("ORDER BY id", [])
+    }
+
+    /// Strikes recorded for days `from..=to` (YYYY-MM-DD), via the `date` index.
+    pub fn strikes_between(&self, from: &str, to: &str) -> Result<Vec<StrikeEntry>> {
+        self.query_strikes("WHERE date BETWEEN ?1 AND ?2 ORDER BY id", [from, to])
+    }
+
+    fn query_strikes<P: rusqlite::Params>(
+        &self,
+        clause: &str,
+        params: P,
+    ) -> Result<Vec<StrikeEntry>> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        let mut stmt = conn.prepare(&format!("SELECT {STRIKE_COLUMNS} FROM strikes {clause}"))?;
+        let rows = stmt.query_map(params, strike_from_row)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Adds one strike without touching the rest of the history.
+    pub fn append_strike(&self, strike: &StrikeEntry) -> Result<()> {
+        self.with_tx(|tx| {
+            insert_strike(tx, strike)?;
+            let (s, e, c) = counts(strike.action);
+            tx.execute(
+                "INSERT INTO monthly_stats
+                     (month, strikes_count, expired_count, completed_count, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?5)
+                 ON CONFLICT (month) DO UPDATE SET
+                     strikes_count = strikes_count + excluded.strikes_count,
+                     expired_count = expired_count + excluded.expired_count,
+                     completed_count = completed_count + excluded.completed_count,
+                     updated_at = excluded.updated_at",
+                params![month_of(&strike.date), s, e, c, now_ms()],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Replaces the whole history (undo, import) and recounts the monthly stats.
+    pub fn save_strikes(&self, strikes: &[StrikeEntry]) -> Result<()> {
+        self.with_tx(|tx| {
+            tx.execute("DELETE FROM strikes", [])?;
+            for strike in strikes {
+                insert_strike(tx, strike)?;
+            }
+            tx.execute(
+                "UPDATE monthly_stats SET strikes_count = 0, expired_count = 0, completed_count = 0",
+                [],
+            )?;
+            tx.execute(
+                "INSERT INTO monthly_stats
+                     (month, strikes_count, expired_count, completed_count, created_at, updated_at)
+                 SELECT substr(date, 1, 7),
+                        SUM(action IS NULL OR action = 'strike'),
+                        SUM(action = 'expired'),
+                        SUM(action = 'completed'),
+                        ?1, ?1
+                 FROM strikes WHERE true GROUP BY 1
+                 ON CONFLICT (month) DO UPDATE SET
+                     strikes_count = excluded.strikes_count,
+                     expired_count = excluded.expired_count,
+                     completed_count = excluded.completed_count,
+                     updated_at = excluded.updated_at",
+                [now_ms()],
+            )?;
+            Ok(())
+        })
+    }
+
+    pub fn monthly_stats(&self, month: &str) -> Result<MonthlyStats> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        let stats = conn
+            .query_row(
+                "SELECT month, strikes_count, expired_count, completed_count, tasks_added_count
+                 FROM monthly_stats WHERE month = ?1",
+                [month],
+                |r| {
+                    Ok(MonthlyStats {
+                        month: r.get(0)?,
+                        strikes_count: r.get(1)?,
+                        expired_count: r.get(2)?,
+                        completed_count: r.get(3)?,
+                        tasks_added_count: r.get(4)?,
+                    })
+                },
+            )
+            .optional()?;
+        Ok(stats.unwrap_or_else(|| MonthlyStats {
+            month: month.to_string(),
+            ..Default::default()
+        }))
+    }
+
+    pub fn append_update(&self, update: &TaskUpdate) -> Result<()> {
+        self.with_tx(|tx| insert_update(tx, update))
+    }
+
+    /// Uses the `(task_id, timestamp)` or `timestamp` index depending on the query.
+    pub fn load_updates(&self, query: &HistoryQuery) -> Result<Vec<TaskUpdate>> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {UPDATE_COLUMNS} FROM task_updates
+             WHERE (?1 IS NULL OR task_id = ?1)
+               AND (?2 IS NULL OR timestamp >= ?2)
+               AND (?3 IS NULL OR timestamp <= ?3)
+             ORDER BY timestamp, rowid"
+        ))?;
+        let rows = stmt.query_map(params![query.task_id, query.from, query.to], |r| {
+            Ok((
+                r.get::<_, String>(0)?,
+                r.get::<_, String>(1)?,
+                r.get::<_, i64>(2)?,
+                r.get::<_, String>(3)?,
+                r.get::<_, Option<String>>(4)?,
+            ))
+        })?;
+        let mut updates = Vec::new();
+        for row in rows {
+            let (update_id, task_id, timestamp, diff, snapshot) = row?;
+            updates.push(TaskUpdate {
+                update_id,
+                task_id,
+                timestamp,
+                diff: serde_json::from_str(&diff)?,
+                full_snapshot: snapshot.map(|s| serde_json::from_str(&s)).transpose()?,
+            });
+        }
+        Ok(updates)
+    }
+
+    pub fn save_updates(&self, updates: &[TaskUpdate]) -> Result<()> {
+        self.with_tx(|tx| {
+            tx.execute("DELETE FROM task_updates", [])?;
+            updates.iter().try_for_each(|u| insert_update(tx, u))
+        })
+    }
+
+    pub fn load_settings(&self) -> Result<Option<AppSettings>> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        let row = conn
+            .query_row(
+                "SELECT reset_hour, timezone, extra FROM settings WHERE id = 1",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?, r.get::<_, String>(2)?)),
+            )
+            .optional()?;
+        row.map(|(reset_hour, timezone, extra): (u8, String, String)| {
+            let mut fields: Map<String, Value> = serde_json::from_str(&extra)?;
+            fields.insert("resetHour".into(), reset_hour.into());
+            fields.insert("timezone".into(), timezone.into());
+            Ok(AppSettings::deserialize(Value::Object(fields))?)
+        })
+        .transpose()
+    }
+
+    /// The day-rollover fields get columns; every other field, typed or not, is kept
+    /// in the `extra` JSON.
+    pub fn save_settings(&self, settings: &AppSettings) -> Result<()> {
+        let mut extra = match serde_json::to_value(settings)? {
+            Value::Object(fields) => fields,
+            _ => Map::new(),
+        };
+        extra.remove("resetHour");
+        extra.remove("timezone");
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        conn.execute(
+            "INSERT INTO settings (id, reset_hour, timezone, extra, created_at, updated_at)
+             VALUES (1, ?1, ?2, ?3, ?4, ?4)
+             ON CONFLICT (id) DO UPDATE SET
+                 reset_hour = excluded.reset_hour,
+                 timezone = excluded.timezone,
+                 extra = excluded.extra,
+                 updated_at = excluded.updated_at",
+            params![
+                settings.reset_hour,
+                settings.timezone,
+                serde_json::to_string(&extra)?,
+                now_ms()
+            ],
+        )?;
+        Ok(())
+    }
+}
+
+impl Storage for SqliteStore {
+    fn load_tasks(&self) -> Result<Vec<Task>> {
+        SqliteStore::load_tasks(self)
+    }
+
+    fn save_tasks(&self, tasks: &[Task]) -> Result<()> {
+        SqliteStore::save_tasks(self, tasks)
+    }
+
+    fn load_strikes(&self) -> Result<Vec<StrikeEntry>> {
+        SqliteStore::load_strikes(self)
+    }
+
+    fn save_strikes(&self, strikes: &[StrikeEntry]) -> Result<()> {
+        SqliteStore::save_strikes(self, strikes)
+    }
+
+    fn append_strike(&self, strike: &StrikeEntry) -> Result<()> {
+        SqliteStore::append_strike(self, strike)
+    }
+
+    fn tasks_due_on(&self, date: &str) -> Result<Vec<Task>> {
+        SqliteStore::tasks_due_on(self, date)
+    }
+
+    fn tasks_with_tag(&self, tag: &str) -> Result<Vec<Task>> {
+        SqliteStore::tasks_with_tag(self, tag)
+    }
+
+    fn strikes_between(&self, from: &str, to: &str) -> Result<Vec<StrikeEntry>> {
+        SqliteStore::strikes_between(self, from, to)
+    }
+
+    /// Read from `monthly_stats`, so tasks deleted since still count as added.
+    fn monthly_stats(&self, month: &str) -> Result<MonthlyStats> {
+        SqliteStore::monthly_stats(self, month)
+    }
+
+    fn load_settings(&self) -> Result<Option<AppSettings>> {
+        SqliteStore::load_settings(self)
+    }
+
+    fn save_settings(&self, settings: &AppSettings) -> Result<()> {
+        SqliteStore::save_settings(self, settings)
+    }
+
+    fn append_update(&self, update: &TaskUpdate) -> Result<()> {
+        SqliteStore::append_update(self, update)
+    }
+
+    fn load_updates(&self, query: &HistoryQuery) -> Result<Vec<TaskUpdate>> {
+        SqliteStore::load_updates(self, query)
+    }
+
+    fn save_updates(&self, updates: &[TaskUpdate]) -> Result<()> {
+        SqliteStore::save_updates(self, updates)
+    }
+}
+
+fn task_from_row(r: &Row) -> rusqlite::Result<Task> {
+    let tags: Option<String> = r.get(9)?;
+    Ok(Task {
+        id: r.get(0)?,
+        revision: r.get(1)?,
+        title: r.get(2)?,
+        notes: r.get(3)?,
+        completed: r.get(4)?,
+        created_at: r.get(5)?,
+        updated_at: r.get(6)?,
+        due_hour: r.get(7)?,
+        due_date: r.get(8)?,
+        // Same leniency as the web API: unreadable tags are dropped, not fatal.
+        tags: tags.and_then(|t| serde_json::from_str(&t).ok()),
+    })
+}
+
+fn strike_from_row(r: &Row) -> rusqlite::Result<StrikeEntry> {
+    let action: Option<String> = r.get(4)?;
+    Ok(StrikeEntry {
+        task_id: r.get(0)?,
+        date: r.get(1)?,
+        note: r.get(2)?,
+        ts: r.get(3)?,
+        action: action.as_deref().and_then(parse_action),
+    })
+}
+
+fn insert_strike(tx: &Transaction, strike: &StrikeEntry) -> Result<()> {
+    tx.execute(
+        &format!("INSERT INTO strikes ({STRIKE_COLUMNS}) VALUES (?1, ?2, ?3, ?4, ?5)"),
+        params![
+            strike.task_id,
+            strike.date,
+            strike.note,
+            strike.ts,
+            strike.action.map(action_str),
+        ],
+    )?;
+    Ok(())
+}
+
+fn insert_update(tx: &Transaction, update: &TaskUpdate) -> Result<()> {
+    let snapshot = update
+        .full_snapshot
+        .as_ref()
+        .map(serde_json::to_string)
+        .transpose()?;
+    tx.execute(
+        &format!(
+            "INSERT OR REPLACE INTO task_updates ({UPDATE_COLUMNS}) VALUES (?1, ?2, ?3, ?4, ?5)"
+        ),
+        params![
+            update.update_id,
+            update.task_id,
+            update.timestamp,
+            serde_json::to_string(&update.diff)?,
+            snapshot,
+        ],
+    )?;
+    Ok(())
+}
+
+/// (strikes, expired, completed) increments for one entry.
+fn counts(action: Option<StrikeAction>) -> (u32, u32, u32) {
+    match action {
+        None | Some(StrikeAction::Strike) => (1, 0, 0),
+        Some(StrikeAction::Expired) => (0, 1, 0),
+        Some(StrikeAction::Completed) => (0, 0, 1),
+    }
+}
+
+fn action_str(action: StrikeAction) -> &'static str {
+    match action {
+        StrikeAction::Strike => "strike",
+        StrikeAction::Completed => "completed",
+        StrikeAction::Expired => "expired",
+    }
+}
+
+fn parse_action(s: &str) -> Option<StrikeAction> {
+    match s {
+        "strike" => Some(StrikeAction::Strike),
+        "completed" => Some(StrikeAction::Completed),
+        "expired" => Some(StrikeAction::Expired),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: &str, due_date: Option<&str>, tags: &[&str]) -> Task {
+        Task {
+            id: id.into(),
+            revision: 0,
+            title: format!("task {id}"),
+            notes: None,
+            completed: false,
+            created_at: 1_760_000_000_000,
+            updated_at: 1_760_000_000_000,
+            due_hour: None,
+            due_date: due_date.map(Into::into),
+            tags: (!tags.is_empty()).then(|| tags.iter().map(|t| t.to_string()).collect()),
+        }
+    }
+
+    fn strike(date: &str, action: Option<StrikeAction>) -> StrikeEntry {
+        StrikeEntry {
+            task_id: "a".into(),
+            date: date.into(),
+            note: None,
+            ts: 1,
+            action,
+        }
+    }
+
+    #[test]
+    fn tasks_round_trip_and_query_by_due_date_and_tag() {
+        let db = SqliteStore::open_in_memory().unwrap();
+        let tasks = vec![
+            task("a", Some("2025-03-01"), &["work"]),
+            task("b", None, &["work", "home"]),
+            task("c", Some("2025-03-01"), &[]),
+        ];
+        db.save_tasks(&tasks).unwrap();
+
+        assert_eq!(db.load_tasks().unwrap(), tasks);
+        let due: Vec<_> = db.tasks_due_on("2025-03-01").unwrap();
+        assert_eq!(
+            due.iter().map(|t| t.id.as_str()).collect::<Vec<_>>(),
+            ["a", "c"]
+        );
+        let work: Vec<_> = db.tasks_with_tag("work").unwrap();
+        assert_eq!(
+            work.iter().map(|t| t.id.as_str()).collect::<Vec<_>>(),
+            ["a", "b"]
+        );
+        assert_eq!(db.monthly_stats("2025-10").unwrap().tasks_added_count, 3);
+
+        db.save_tasks(&tasks[..1]).unwrap();
+        assert!(db.tasks_with_tag("home").unwrap().is_empty());
+        assert_eq!(db.monthly_stats("2025-10").unwrap().tasks_added_count, 3);
+    }
+
+    #[test]
+    fn strikes_are_appended_and_counted_per_month() {
+        let db = SqliteStore::open_in_memory().unwrap();
+        db.append_strike(&strike("2025-03-01", None)).unwrap();
+        db.append_strike(&strike("2025-03-02", Some(StrikeAction::Completed)))
+            .unwrap();
+        db.append_strike(&strike("2025-04-01", Some(StrikeAction::Expired)))
+            .unwrap();
+
+        assert_eq!(
+            db.strikes_between("2025-03-01", "2025-03-31")
+                .unwrap()
+                .len(),
+            2
+        );
+        let march = db.monthly_stats("2025-03").unwrap();
+        assert_eq!((march.strikes_count, march.completed_count), (1, 1));
+
+        let all = db.load_strikes().unwrap();
+        db.save_strikes(&all[1..]).unwrap();
+        let march = db.monthly_stats("2025-03").unwrap();
+        assert_eq!((march.strikes_count, march.completed_count), (0, 1));
+        assert_eq!(db.monthly_stats("2025-04").unwrap().expired_count, 1);
+    }
+
+    #[test]
+    fn settings_keep_unknown_fields() {
+        let db = SqliteStore::open_in_memory().unwrap();
+        assert!(db.load_settings().unwrap().is_none());
+        let settings: AppSettings = serde_json::from_str(
+            r##"{"resetHour":6,"timezone":"Europe/Paris","buttonColor":"#fff"}"##,
+        )
+        .unwrap();
+        db.save_settings(&settings).unwrap();
+        assert_eq!(db.load_settings().unwrap(), Some(settings));
+    }
+}
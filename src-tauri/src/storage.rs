@@ -0,0 +1,465 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+use crate::events::Events;
+use crate::history::{HistoryQuery, Segments, TaskUpdate, HISTORY_DIR};
+use crate::persist::{self, SETTINGS_FILE, STRIKES_FILE};
+use crate::settings::AppSettings;
+use crate::strikes::{month_of, StrikeAction, StrikeEntry};
+use crate::tasks::{is_valid_date, Task, TASKS_FILE};
+use crate::time::utc_month;
+
+/// Per-month counters, as shown on the reports page.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonthlyStats {
+    pub month: String,
+    pub strikes_count: u64,
+    pub expired_count: u64,
+    pub completed_count: u64,
+    pub tasks_added_count: u64,
+}
+
+/// Where tasks, strikes and settings are kept. Stores above this layer own the
+/// locking for read-modify-write sequences; a backend only has to make each call atomic.
+pub trait Storage: Send + Sync {
+    fn load_tasks(&self) -> Result<Vec<Task>>;
+    fn save_tasks(&self, tasks: &[Task]) -> Result<()>;
+    fn load_strikes(&self) -> Result<Vec<StrikeEntry>>;
+    fn save_strikes(&self, strikes: &[StrikeEntry]) -> Result<()>;
+    fn load_settings(&self) -> Result<Option<AppSettings>>;
+    fn save_settings(&self, settings: &AppSettings) -> Result<()>;
+    /// Adds one record to the task history without rewriting it.
+    fn append_update(&self, update: &TaskUpdate) -> Result<()>;
+    /// History records matching `query`, oldest first.
+    fn load_updates(&self, query: &HistoryQuery) -> Result<Vec<TaskUpdate>>;
+    /// Replaces the whole history.
+    fn save_updates(&self, updates: &[TaskUpdate]) -> Result<()>;
+
+    /// Files in the data directory this backend reads on every call, and which are
+    /// therefore watched for external edits.
+    fn watched_files(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// The task list after an edit made outside the app. Backends that watch files
+    /// treat a list that breaks [`check_tasks`] like a corrupt file: quarantined, with
+    /// `data-recovered` emitted, in which case this returns `None`.
+    fn reload_tasks(&self) -> Result<Option<Vec<Task>>> {
+        self.load_tasks().map(Some)
+    }
+
+    /// Backends that can append without rewriting the history override this.
+    fn append_strike(&self, strike: &StrikeEntry) -> Result<()> {
+        let mut strikes = self.load_strikes()?;
+        strikes.push(strike.clone());
+        self.save_strikes(&strikes)
+    }
+
+    // The queries below scan everything by default; indexed backends override them.
+
+    fn tasks_due_on(&self, date: &str) -> Result<Vec<Task>> {
+        let mut tasks = self.load_tasks()?;
+        tasks.retain(|t| t.due_date.as_deref() == Some(date));
+        Ok(tasks)
+    }
+
+    fn tasks_with_tag(&self, tag: &str) -> Result<Vec<Task>> {
+        let mut tasks = self.load_tasks()?;
+        tasks.retain(|t| t.tags.iter().flatten().any(|t| t == tag));
+        Ok(tasks)
+    }
+
+    /// Strikes recorded for days `from..=to` (YYYY-MM-DD).
+    fn strikes_between(&self, from: &str, to: &str) -> Result<Vec<StrikeEntry>> {
+        let mut strikes = self.load_strikes()?;
+        strikes.retain(|s| (from..=to).contains(&s.date.as_str()));
+        Ok(strikes)
+    }
+
+    /// Counts for one `YYYY-MM`; tasks are counted by their UTC creation month.
+    fn monthly_stats(&self, month: &str) -> Result<MonthlyStats> {
+        let mut stats = MonthlyStats {
+            month: month.to_string(),
+            ..Default::default()
+        };
+        for strike in self.load_strikes()? {
+            if month_of(&strike.date) != month {
+                continue;
+            }
+            match strike.action {
+                None | Some(StrikeAction::Strike) => stats.strikes_count += 1,
+                Some(StrikeAction::Expired) => stats.expired_count += 1,
+                Some(StrikeAction::Completed) => stats.completed_count += 1,
+            }
+        }
+        stats.tasks_added_count = self
+            .load_tasks()?
+            .iter()
+            .filter(|t| utc_month(t.created_at) == month)
+            .count() as u64;
+        Ok(stats)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Json,
+    Sqlite,
+    Memory,
+}
+
+impl Backend {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "json" => Some(Backend::Json),
+            "sqlite" => Some(Backend::Sqlite),
+            "memory" => Some(Backend::Memory),
+            _ => None,
+        }
+    }
+
+    /// `--storage` if given, otherwise the `storage` key of `settings.json`, otherwise JSON.
+    /// The key is read from the JSON file whichever backend is active, so it has to be set
+    /// while running on JSON (or by hand) to take effect on the next start.
+    pub fn select(flag: Option<&str>, dir: &Path) -> Result<Self> {
+        if let Some(flag) = flag {
+            return Self::parse(flag)
+                .ok_or_else(|| Error::Invalid(format!("unknown storage backend {flag:?}")));
+        }
+        // Corrupt settings are dealt with (and reported) once the backend loads them.
+        let settings = persist::read_json::<AppSettings>(&dir.join(SETTINGS_FILE))
+            .ok()
+            .flatten();
+        Ok(settings
+            .and_then(|s| s.extra.get("storage")?.as_str().and_then(Self::parse))
+            .unwrap_or(Backend::Json))
+    }
+
+    pub fn open(self, dir: &Path, events: Arc<dyn Events>) -> Result<Arc<dyn Storage>> {
+        Ok(match self {
+            Backend::Json => Arc::new(JsonStorage::new(dir.to_path_buf(), events)),
+            Backend::Memory => {
+                // Starts from a copy of the data on disk, which is then left alone.
+                let memory = MemoryStorage::default();
+                copy(&JsonStorage::new(dir.to_path_buf(), events), &memory)?;
+                Arc::new(memory)
+            }
+            #[cfg(feature = "sqlite")]
+            Backend::Sqlite => {
+                let db = crate::sqlite::SqliteStore::open(&dir.join(crate::sqlite::DB_FILE))?;
+                // First start on SQLite: bring over what the JSON files hold.
+                if db.load_tasks()?.is_empty()
+                    && db.load_strikes()?.is_empty()
+                    && db.load_settings()?.is_none()
+                {
+                    copy(&JsonStorage::new(dir.to_path_buf(), events), &db)?;
+                }
+                Arc::new(db)
+            }
+            #[cfg(not(feature = "sqlite"))]
+            Backend::Sqlite => {
+                return Err(Error::Invalid(
+                    "this build was compiled without SQLite support".into(),
+                ))
+            }
+        })
+    }
+}
+
+/// Every backend refuses a task list with a repeated id: SQLite cannot store one, so
+/// the others must not either, or data written to them could not be copied across.
+pub fn check_unique_ids(tasks: &[Task]) -> Result<()> {
+    let mut seen = std::collections::HashSet::new();
+    match tasks.iter().find(|t| !seen.insert(t.id.as_str())) {
+        Some(t) => Err(Error::Invalid(format!(
+            "task id {} appears more than once; `doctor --repair` keeps the newest copy",
+            t.id
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// What every task list written by the stores satisfies: unique ids, and due dates and
+/// hours that `create_task` would accept.
+pub fn check_tasks(tasks: &[Task]) -> Result<()> {
+    check_unique_ids(tasks)?;
+    for task in tasks {
+        if let Some(hour) = task.due_hour.filter(|h| *h > 23) {
+            return Err(Error::Invalid(format!(
+                "task {}: dueHour {hour} is not in 0-23",
+                task.id
+            )));
+        }
+        if let Some(date) = task.due_date.as_deref().filter(|d| !is_valid_date(d)) {
+            return Err(Error::Invalid(format!(
+                "task {}: dueDate {date} is not YYYY-MM-DD",
+                task.id
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Copies everything in `from` over the contents of `to`.
+pub fn copy(from: &dyn Storage, to: &dyn Storage) -> Result<()> {
+    to.save_tasks(&from.load_tasks()?)?;
+    to.save_strikes(&from.load_strikes()?)?;
+    if let Some(settings) = from.load_settings()? {
+        to.save_settings(&settings)?;
+    }
+    to.save_updates(&from.load_updates(&HistoryQuery::default())?)?;
+    Ok(())
+}
+
+/// One JSON file per collection in the data directory: `tasks.json`, `strikes.json`
+/// and `settings.json`, written atomically and quarantined when corrupt. Task history
+/// is an append-only log under `history/`.
+pub struct JsonStorage {
+    dir: PathBuf,
+    history: Segments,
+    events: Arc<dyn Events>,
+    // Recovery renames files and writers share one `.tmp` name per file.
+    lock: Mutex<()>,
+}
+
+impl JsonStorage {
+    pub fn new(dir: PathBuf, events: Arc<dyn Events>) -> Self {
+        Self {
+            history: Segments::new(dir.join(HISTORY_DIR)),
+            dir,
+            events,
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn guard(&self) -> std::sync::MutexGuard<'_, ()> {
+        self.lock.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn load<T: serde::de::DeserializeOwned>(&self, name: &str) -> Result<Option<T>> {
+        persist::load_json(&self.dir.join(name), self.events.as_ref())
+    }
+
+    fn save<T: Serialize + ?Sized>(&self, name: &str, value: &T) -> Result<()> {
+        persist::write_json(&self.dir.join(name), value)
+    }
+}
+
+impl Storage for JsonStorage {
+    fn watched_files(&self) -> &'static [&'static str] {
+        &[TASKS_FILE, STRIKES_FILE, SETTINGS_FILE]
+    }
+
+    fn load_tasks(&self) -> Result<Vec<Task>> {
+        let _guard = self.guard();
+        Ok(self.load(TASKS_FILE)?.unwrap_or_default())
+    }
+
+    fn reload_tasks(&self) -> Result<Option<Vec<Task>>> {
+        let _guard = self.guard();
+        let path = self.dir.join(TASKS_FILE);
+        let (tasks, recovered) =
+            persist::load_checked(&path, self.events.as_ref(), |t: &Vec<Task>| check_tasks(t))?;
+        Ok((!recovered).then(|| tasks.unwrap_or_default()))
+    }
+
+    fn save_tasks(&self, tasks: &[Task]) -> Result<()> {
+        check_unique_ids(tasks)?;
+        let _guard = self.guard();
+        self.save(TASKS_FILE, tasks)
+    }
+
+    fn load_strikes(&self) -> Result<Vec<StrikeEntry>> {
+        let _guard = self.guard();
+        Ok(self.load(STRIKES_FILE)?.unwrap_or_default())
+    }
+
+    fn save_strikes(&self, strikes: &[StrikeEntry]) -> Result<()> {
+        let _guard = self.guard();
+        self.save(STRIKES_FILE, strikes)
+    }
+
+    fn append_strike(&self, strike: &StrikeEntry) -> Result<()> {
+        let _guard = self.guard();
+        let mut strikes: Vec<StrikeEntry> = self.load(STRIKES_FILE)?.unwrap_or_default();
+        strikes.push(strike.clone());
+        self.save(STRIKES_FILE, &strikes)
+    }
+
+    fn load_settings(&self) -> Result<Option<AppSettings>> {
+        let _guard = self.guard();
+        self.load(SETTINGS_FILE)
+    }
+
+    fn save_settings(&self, settings: &AppSettings) -> Result<()> {
+        let _guard = self.guard();
+        self.save(SETTINGS_FILE, settings)
+    }
+
+    fn append_update(&self, update: &TaskUpdate) -> Result<()> {
+        let _guard = self.guard();
+        self.history.append(update)
+    }
+
+    fn load_updates(&self, query: &HistoryQuery) -> Result<Vec<TaskUpdate>> {
+        let _guard = self.guard();
+        self.history.read(query)
+    }
+
+    fn save_updates(&self, updates: &[TaskUpdate]) -> Result<()> {
+        let _guard = self.guard();
+        self.history.replace(updates)
+    }
+}
+
+/// Keeps everything in memory and forgets it on exit. Used by tests, and by
+/// `--storage memory` for throwaway sessions on a copy of the real data.
+#[derive(Default)]
+pub struct MemoryStorage {
+    state: Mutex<MemoryState>,
+}
+
+#[derive(Default)]
+struct MemoryState {
+    tasks: Vec<Task>,
+    strikes: Vec<StrikeEntry>,
+    settings: Option<AppSettings>,
+    updates: Vec<TaskUpdate>,
+}
+
+impl MemoryStorage {
+    fn state(&self) -> std::sync::MutexGuard<'_, MemoryState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn load_tasks(&self) -> Result<Vec<Task>> {
+        Ok(self.state().tasks.clone())
+    }
+
+    fn save_tasks(&self, tasks: &[Task]) -> Result<()> {
+        check_unique_ids(tasks)?;
+        self.state().tasks = tasks.to_vec();
+        Ok(())
+    }
+
+    fn load_strikes(&self) -> Result<Vec<StrikeEntry>> {
+        Ok(self.state().strikes.clone())
+    }
+
+    fn save_strikes(&self, strikes: &[StrikeEntry]) -> Result<()> {
+        self.state().strikes = strikes.to_vec();
+        Ok(())
+    }
+
+    fn append_strike(&self, strike: &StrikeEntry) -> Result<()> {
+        self.state().strikes.push(strike.clone());
+        Ok(())
+    }
+
+    fn load_settings(&self) -> Result<Option<AppSettings>> {
+        Ok(self.state().settings.clone())
+    }
+
+    fn save_settings(&self, settings: &AppSettings) -> Result<()> {
+        self.state().settings = Some(settings.clone());
+        Ok(())
+    }
+
+    fn append_update(&self, update: &TaskUpdate) -> Result<()> {
+        self.state().updates.push(update.clone());
+        Ok(())
+    }
+
+    fn load_updates(&self, query: &HistoryQuery) -> Result<Vec<TaskUpdate>> {
+        let mut updates = self.state().updates.clone();
+        updates.retain(|u| query.matches(u));
+        updates.sort_by_key(|u| u.timestamp);
+        Ok(updates)
+    }
+
+    fn save_updates(&self, updates: &[TaskUpdate]) -> Result<()> {
+        self.state().updates = updates.to_vec();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn flag_wins_over_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(Backend::select(None, dir.path()).unwrap(), Backend::Json);
+
+        fs::write(
+            dir.path().join(SETTINGS_FILE),
+            r#"{"resetHour":9,"storage":"memory"}"#,
+        )
+        .unwrap();
+        assert_eq!(Backend::select(None, dir.path()).unwrap(), Backend::Memory);
+        assert_eq!(
+            Backend::select(Some("json"), dir.path()).unwrap(),
+            Backend::Json
+        );
+        assert!(Backend::select(Some("mongo"), dir.path()).is_err());
+    }
+
+    #[test]
+    fn json_storage_round_trips_through_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let json = JsonStorage::new(dir.path().to_path_buf(), Arc::new(()));
+        let memory = MemoryStorage::default();
+        memory
+            .append_strike(&StrikeEntry {
+                task_id: "a".into(),
+                date: "2025-03-01".into(),
+                note: None,
+                ts: 1,
+                action: None,
+            })
+            .unwrap();
+        memory.save_settings(&AppSettings::default()).unwrap();
+
+        assert!(json.load_settings().unwrap().is_none());
+        copy(&memory, &json).unwrap();
+        assert_eq!(json.load_strikes().unwrap(), memory.load_strikes().unwrap());
+        assert_eq!(json.load_settings().unwrap(), Some(AppSettings::default()));
+        assert!(dir.path().join(STRIKES_FILE).is_file());
+    }
+
+    #[test]
+    fn every_backend_rejects_repeated_task_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let task = Task {
+            id: "a".into(),
+            revision: 0,
+            title: "A".into(),
+            notes: None,
+            completed: false,
+            created_at: 0,
+            updated_at: 0,
+            due_hour: None,
+            due_date: None,
+            tags: None,
+        };
+        let twice = [task.clone(), task];
+        let json = JsonStorage::new(dir.path().to_path_buf(), Arc::new(()));
+        assert!(matches!(json.save_tasks(&twice), Err(Error::Invalid(_))));
+        assert!(!dir.path().join(TASKS_FILE).exists());
+        let memory = MemoryStorage::default();
+        assert!(matches!(memory.save_tasks(&twice), Err(Error::Invalid(_))));
+        #[cfg(feature = "sqlite")]
+        {
+            let db = crate::sqlite::SqliteStore::open_in_memory().unwrap();
+            assert!(matches!(db.save_tasks(&twice), Err(Error::Invalid(_))));
+        }
+    }
+}
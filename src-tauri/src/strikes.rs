@@ -0,0 +1,212 @@
+use std::collections::{BTreeSet, HashSet};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::events::{self, Events};
+use crate::storage::{MonthlyStats, Storage};
+use crate::tasks::is_valid_date;
+
+/// One entry of `strikes.json`; field names match the frontend `StrikeEntry` type.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StrikeEntry {
+    pub task_id: String,
+    /// YYYY-MM-DD in the user's timezone.
+    pub date: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// Epoch milliseconds.
+    pub ts: i64,
+    /// Missing on entries written before actions were recorded; those count as strikes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action: Option<StrikeAction>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StrikeAction {
+    Strike,
+    Completed,
+    Expired,
+}
+
+/// Payload of `strikes-changed`: the tasks whose history gained or lost entries.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StrikesChanged {
+    pub task_ids: Vec<String>,
+}
+
+impl StrikesChanged {
+    pub fn appended(entries: &[StrikeEntry]) -> Self {
+        let ids: BTreeSet<_> = entries.iter().map(|e| e.task_id.clone()).collect();
+        Self {
+            task_ids: ids.into_iter().collect(),
+        }
+    }
+
+    /// Entries are told apart by task and timestamp, which is what undo matches on.
+    pub fn between(before: &[StrikeEntry], after: &[StrikeEntry]) -> Self {
+        let key = |e: &StrikeEntry| (e.task_id.clone(), e.ts);
+        let old: HashSet<_> = before.iter().map(key).collect();
+        let new: HashSet<_> = after.iter().map(key).collect();
+        let ids: BTreeSet<_> = old
+            .symmetric_difference(&new)
+            .map(|(id, _)| id.clone())
+            .collect();
+        Self {
+            task_ids: ids.into_iter().collect(),
+        }
+    }
+}
+
+/// `YYYY-MM` of a `YYYY-MM-DD` date.
+pub fn month_of(date: &str) -> &str {
+    date.get(..7).unwrap_or(date)
+}
+
+/// Strike history queries and appends. Whole-history rewrites (undo, import) still
+/// go through `write_data_file`.
+pub struct StrikeStore {
+    storage: Arc<dyn Storage>,
+    events: Arc<dyn Events>,
+}
+
+impl StrikeStore {
+    pub fn new(storage: Arc<dyn Storage>, events: Arc<dyn Events>) -> Self {
+        Self { storage, events }
+    }
+
+    /// Records new entries without rewriting the history on backends that support it.
+    pub fn append(&self, entries: &[StrikeEntry]) -> Result<()> {
+        for entry in entries {
+            check_date(&entry.date)?;
+        }
+        for entry in entries {
+            self.storage.append_strike(entry)?;
+        }
+        let changed = StrikesChanged::appended(entries);
+        self.events
+            .emit(events::STRIKES_CHANGED, serde_json::to_value(changed)?);
+        Ok(())
+    }
+
+    /// Entries for the days `from..=to`.
+    pub fn between(&self, from: &str, to: &str) -> Result<Vec<StrikeEntry>> {
+        check_date(from)?;
+        check_date(to)?;
+        self.storage.strikes_between(from, to)
+    }
+
+    pub fn monthly_stats(&self, month: &str) -> Result<MonthlyStats> {
+        if !is_valid_date(&format!("{month}-01")) {
+            return Err(Error::Invalid(format!("{month:?} is not a YYYY-MM month")));
+        }
+        self.storage.monthly_stats(month)
+    }
+}
+
+fn check_date(date: &str) -> Result<()> {
+    if is_valid_date(date) {
+        Ok(())
+    } else {
+        Err(Error::Invalid(format!("{date:?} is not a YYYY-MM-DD date")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::Recorded;
+    use crate::storage::MemoryStorage;
+    use crate::tasks::Task;
+
+    fn entry(task_id: &str, date: &str, ts: i64, action: Option<StrikeAction>) -> StrikeEntry {
+        StrikeEntry {
+            task_id: task_id.into(),
+            date: date.into(),
+            note: None,
+            ts,
+            action,
+        }
+    }
+
+    #[test]
+    fn appends_are_announced_and_queried_by_day() {
+        let storage = Arc::new(MemoryStorage::default());
+        let events = Arc::new(Recorded::default());
+        let store = StrikeStore::new(storage, events.clone());
+
+        store
+            .append(&[
+                entry("b", "2025-02-28", 1, None),
+                entry("a", "2025-03-01", 2, Some(StrikeAction::Strike)),
+                entry("a", "2025-03-02", 3, Some(StrikeAction::Completed)),
+            ])
+            .unwrap();
+        // A bad date rejects the whole batch.
+        assert!(store
+            .append(&[
+                entry("c", "2025-03-03", 4, None),
+                entry("c", "2025-02-30", 5, None)
+            ])
+            .is_err());
+
+        let events = events.0.lock().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].0, events::STRIKES_CHANGED);
+        assert_eq!(events[0].1["taskIds"], serde_json::json!(["a", "b"]));
+
+        let ts = |entries: Vec<StrikeEntry>| entries.iter().map(|e| e.ts).collect::<Vec<_>>();
+        assert_eq!(
+            ts(store.between("2025-03-01", "2025-03-31").unwrap()),
+            [2, 3]
+        );
+        assert_eq!(
+            ts(store.between("2025-02-28", "2025-03-01").unwrap()),
+            [1, 2]
+        );
+        assert!(store
+            .between("2025-03-02", "2025-03-01")
+            .unwrap()
+            .is_empty());
+        assert!(store.between("2025-3-01", "2025-03-31").is_err());
+    }
+
+    #[test]
+    fn monthly_stats_count_each_action() {
+        let storage = Arc::new(MemoryStorage::default());
+        let store = StrikeStore::new(storage.clone(), Arc::new(()));
+        let task: Task = serde_json::from_str(
+            // 2025-03-01T00:00Z
+            r#"{"id":"a","revision":1,"title":"t","completed":false,"createdAt":1740787200000,"updatedAt":1740787200000}"#,
+        )
+        .unwrap();
+        storage.save_tasks(&[task]).unwrap();
+        store
+            .append(&[
+                entry("a", "2025-03-01", 1, None),
+                entry("a", "2025-03-02", 2, Some(StrikeAction::Strike)),
+                entry("a", "2025-03-03", 3, Some(StrikeAction::Expired)),
+                entry("a", "2025-03-04", 4, Some(StrikeAction::Completed)),
+                entry("a", "2025-04-01", 5, Some(StrikeAction::Completed)),
+            ])
+            .unwrap();
+
+        assert_eq!(
+            store.monthly_stats("2025-03").unwrap(),
+            MonthlyStats {
+                month: "2025-03".into(),
+                strikes_count: 2,
+                expired_count: 1,
+                completed_count: 1,
+                tasks_added_count: 1,
+            }
+        );
+        assert_eq!(store.monthly_stats("2025-04").unwrap().completed_count, 1);
+        assert_eq!(store.monthly_stats("2025-04").unwrap().tasks_added_count, 0);
+        assert!(store.monthly_stats("2025-13").is_err());
+    }
+}
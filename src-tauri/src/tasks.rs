@@ -0,0 +1,575 @@
+use std::sync::{Arc, Mutex};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::events::{self, Events};
+use crate::history::{HistoryStore, TaskUpdate};
+use crate::storage::Storage;
+use crate::time::now_ms;
+
+pub const TASKS_FILE: &str = "tasks.json";
+
+const MAX_TITLE_LEN: usize = 200;
+const MAX_NOTES_LEN: usize = 1000;
+const MAX_TAG_LEN: usize = 50;
+
+/// A task as stored in `tasks.json`; field names match the frontend `Task` type.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Task {
+    pub id: String,
+    /// Incremented on every edit.
+    pub revision: u64,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    pub completed: bool,
+    pub created_at: i64,
+    pub updated_at: i64,
+    /// Optional daily deadline, 0-23.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_hour: Option<u8>,
+    /// YYYY-MM-DD in the user's timezone.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_date: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+}
+
+/// The user-editable part of a task, sent by `create_task` and `update_task`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskDraft {
+    pub title: String,
+    #[serde(default)]
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub due_hour: Option<u8>,
+    #[serde(default)]
+    pub due_date: Option<String>,
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+}
+
+impl TaskDraft {
+    /// Trims and checks the draft, returning the cleaned copy.
+    pub fn validate(self) -> Result<TaskDraft> {
+        let title = self.title.trim().to_string();
+        if title.is_empty() {
+            return Err(Error::Invalid("Task title cannot be empty".into()));
+        }
+        if title.chars().count() > MAX_TITLE_LEN {
+            return Err(Error::Invalid(format!(
+                "Task title must be {MAX_TITLE_LEN} characters or less"
+            )));
+        }
+        let notes = self
+            .notes
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty());
+        if notes
+            .as_ref()
+            .is_some_and(|n| n.chars().count() > MAX_NOTES_LEN)
+        {
+            return Err(Error::Invalid(format!(
+                "Task notes must be {MAX_NOTES_LEN} characters or less"
+            )));
+        }
+        if let Some(hour) = self.due_hour {
+            if hour > 23 {
+                return Err(Error::Invalid(format!("dueHour {hour} is not in 0-23")));
+            }
+        }
+        let due_date = self.due_date.filter(|d| !d.is_empty());
+        if let Some(date) = &due_date {
+            if !is_valid_date(date) {
+                return Err(Error::Invalid(format!("dueDate {date} is not YYYY-MM-DD")));
+            }
+        }
+        let tags: Vec<String> = self
+            .tags
+            .unwrap_or_default()
+            .into_iter()
+            .map(|t| t.trim().chars().take(MAX_TAG_LEN).collect::<String>())
+            .filter(|t| !t.is_empty())
+            .collect();
+        Ok(TaskDraft {
+            title,
+            notes,
+            due_hour: self.due_hour,
+            due_date,
+            tags: (!tags.is_empty()).then_some(tags),
+        })
+    }
+}
+
+/// Checks that `s` is a `YYYY-MM-DD` date that exists, the form the frontend writes
+/// for `dueDate`.
+pub fn is_valid_date(s: &str) -> bool {
+    let shaped = s.bytes().enumerate().all(|(i, c)| match i {
+        4 | 7 => c == b'-',
+        _ => c.is_ascii_digit(),
+    });
+    s.len() == 10 && shaped && NaiveDate::parse_from_str(s, "%Y-%m-%d").is_ok()
+}
+
+/// Payload of `tasks-changed`: tasks written (with their new revision) and removed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TasksChanged {
+    pub changed: Vec<TaskRevision>,
+    pub deleted: Vec<String>,
+    /// Set when the file was edited outside the app, where revisions may not have been bumped.
+    pub external: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskRevision {
+    pub id: String,
+    pub revision: u64,
+}
+
+impl TasksChanged {
+    pub fn written(tasks: &[Task]) -> Self {
+        Self {
+            changed: tasks
+                .iter()
+                .map(|t| TaskRevision {
+                    id: t.id.clone(),
+                    revision: t.revision,
+                })
+                .collect(),
+            deleted: Vec::new(),
+            external: false,
+        }
+    }
+}
+
+/// Owns the task list. Every operation loads, mutates and saves it under one lock,
+/// then tells every window what changed.
+pub struct TaskStore {
+    storage: Arc<dyn Storage>,
+    history: HistoryStore,
+    events: Arc<dyn Events>,
+    lock: Mutex<()>,
+}
+
+impl TaskStore {
+    pub fn new(storage: Arc<dyn Storage>, events: Arc<dyn Events>) -> Self {
+        Self {
+            history: HistoryStore::new(storage.clone()),
+            storage,
+            events,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// The history this store records edits into.
+    pub fn history(&self) -> HistoryStore {
+        self.history.clone()
+    }
+
+    pub fn list(&self) -> Result<Vec<Task>> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        self.load()
+    }
+
+    /// Tasks with `dueDate` equal to `date`.
+    pub fn due_on(&self, date: &str) -> Result<Vec<Task>> {
+        if !is_valid_date(date) {
+            return Err(Error::Invalid(format!("{date:?} is not a YYYY-MM-DD date")));
+        }
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        self.storage.tasks_due_on(date)
+    }
+
+    pub fn with_tag(&self, tag: &str) -> Result<Vec<Task>> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        self.storage.tasks_with_tag(tag.trim())
+    }
+
+    pub fn create(&self, draft: TaskDraft) -> Result<Task> {
+        let draft = draft.validate()?;
+        let now = now_ms();
+        let task = Task {
+            id: uuid::Uuid::new_v4().to_string(),
+            revision: 0,
+            title: draft.title,
+            notes: draft.notes,
+            completed: false,
+            created_at: now,
+            updated_at: now,
+            due_hour: draft.due_hour,
+            due_date: draft.due_date,
+            tags: draft.tags,
+        };
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut tasks = self.load()?;
+        tasks.insert(0, task.clone());
+        self.save(&tasks)?;
+        self.history.append(&TaskUpdate::created(&task)?)?;
+        drop(_guard);
+        self.written(task)
+    }
+
+    /// Applies `draft` to a task last seen at `base_revision`. If it has been edited
+    /// since, nothing is written and the stored copy comes back in [`Error::Conflict`].
+    pub fn update(&self, id: &str, base_revision: u64, draft: TaskDraft) -> Result<Task> {
+        let draft = draft.validate()?;
+        self.modify(id, |task| {
+            if task.revision != base_revision {
+                return Err(Error::Conflict {
+                    current: Box::new(task.clone()),
+                });
+            }
+            task.title = draft.title;
+            task.notes = draft.notes;
+            task.due_hour = draft.due_hour;
+            task.due_date = draft.due_date;
+            task.tags = draft.tags;
+            Ok(())
+        })
+    }
+
+    pub fn toggle(&self, id: &str) -> Result<Task> {
+        self.modify(id, |task| {
+            task.completed = !task.completed;
+            Ok(())
+        })
+    }
+
+    /// Puts a task's fields back the way history record `update_id` left them. This
+    /// is a new edit with its own revision and history record, so it can be reverted too.
+    pub fn revert(&self, id: &str, update_id: &str) -> Result<Task> {
+        let old = self.history.task_after(id, update_id)?;
+        self.modify(id, |task| {
+            task.title = old.title;
+            task.notes = old.notes;
+            task.completed = old.completed;
+            task.due_hour = old.due_hour;
+            task.due_date = old.due_date;
+            task.tags = old.tags;
+            Ok(())
+        })
+    }
+
+    /// Overwrites the whole list, as done by a restore, import or doctor repair; no
+    /// command exposes it, since it skips the revision check. A task whose content
+    /// changes gets a revision past both copies and a history record, so windows still
+    /// holding the old copy get a conflict instead of writing over the new one. New tasks
+    /// and tasks left out are recorded as created and deleted, as by [`TaskStore::create`]
+    /// and [`TaskStore::delete`].
+    pub fn replace_all(&self, tasks: Vec<Task>) -> Result<()> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let current = self.load()?;
+        let mut changes = Vec::new();
+        let mut created = Vec::new();
+        let tasks: Vec<Task> = tasks
+            .into_iter()
+            .map(|mut task| match current.iter().find(|t| t.id == task.id) {
+                Some(old) if same_content(old, &task) => old.clone(),
+                Some(old) => {
+                    task.revision = task.revision.max(old.revision);
+                    let task = bump(&mut task);
+                    changes.push((old.clone(), task.clone()));
+                    task
+                }
+                None => {
+                    created.push(task.clone());
+                    task
+                }
+            })
+            .collect();
+        let removed: Vec<&Task> = current
+            .iter()
+            .filter(|old| !tasks.iter().any(|t| t.id == old.id))
+            .collect();
+        self.save(&tasks)?;
+        let now = now_ms();
+        // Stamped now rather than `createdAt`, so a task restored after its deletion
+        // comes back after the deletion record.
+        for task in &created {
+            let update = TaskUpdate {
+                timestamp: now,
+                ..TaskUpdate::created(task)?
+            };
+            self.history.append(&update)?;
+        }
+        for (before, after) in &changes {
+            self.history.record(before, after)?;
+        }
+        for task in &removed {
+            self.history.append(&TaskUpdate::deleted(task, now)?)?;
+        }
+        let deleted = removed.iter().map(|t| t.id.clone()).collect();
+        drop(_guard);
+        self.notify(TasksChanged {
+            deleted,
+            ..TasksChanged::written(&tasks)
+        })
+    }
+
+    /// Removes a task. Its history stays, ending in a deletion record that holds the
+    /// whole task, so it can still be found and looked at.
+    pub fn delete(&self, id: &str) -> Result<Task> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut tasks = self.load()?;
+        let task = tasks.remove(position(&tasks, id)?);
+        self.save(&tasks)?;
+        self.history
+            .append(&TaskUpdate::deleted(&task, now_ms())?)?;
+        drop(_guard);
+        self.notify(TasksChanged {
+            deleted: vec![task.id.clone()],
+            ..Default::default()
+        })?;
+        Ok(task)
+    }
+
+    /// Applies `edit` to one task, bumps its revision and records the change in the
+    /// task history (under the same lock, so history order matches revision order).
+    fn modify(&self, id: &str, edit: impl FnOnce(&mut Task) -> Result<()>) -> Result<Task> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut tasks = self.load()?;
+        let idx = position(&tasks, id)?;
+        let before = tasks[idx].clone();
+        edit(&mut tasks[idx])?;
+        let task = bump(&mut tasks[idx]);
+        self.save(&tasks)?;
+        self.history.record(&before, &task)?;
+        drop(_guard);
+        self.written(task)
+    }
+
+    fn written(&self, task: Task) -> Result<Task> {
+        self.notify(TasksChanged::written(std::slice::from_ref(&task)))?;
+        Ok(task)
+    }
+
+    fn notify(&self, changed: TasksChanged) -> Result<()> {
+        self.events
+            .emit(events::TASKS_CHANGED, serde_json::to_value(changed)?);
+        Ok(())
+    }
+
+    fn load(&self) -> Result<Vec<Task>> {
+        self.storage.load_tasks()
+    }
+
+    fn save(&self, tasks: &[Task]) -> Result<()> {
+        self.storage.save_tasks(tasks)
+    }
+}
+
+fn bump(task: &mut Task) -> Task {
+    task.revision += 1;
+    task.updated_at = now_ms();
+    task.clone()
+}
+
+/// Whether two copies differ only in revision and edit time.
+fn same_content(a: &Task, b: &Task) -> bool {
+    let plain = |t: &Task| Task {
+        revision: 0,
+        updated_at: 0,
+        ..t.clone()
+    };
+    plain(a) == plain(b)
+}
+
+fn position(tasks: &[Task], id: &str) -> Result<usize> {
+    tasks
+        .iter()
+        .position(|t| t.id == id)
+        .ok_or_else(|| Error::TaskNotFound(id.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::Recorded;
+    use crate::storage::MemoryStorage;
+    use serde_json::json;
+
+    fn store() -> TaskStore {
+        TaskStore::new(Arc::new(MemoryStorage::default()), Arc::new(()))
+    }
+
+    fn draft(title: &str) -> TaskDraft {
+        TaskDraft {
+            title: title.into(),
+            notes: None,
+            due_hour: None,
+            due_date: None,
+            tags: None,
+        }
+    }
+
+    #[test]
+    fn dates_must_exist() {
+        assert!(is_valid_date("2024-02-29"));
+        for bad in [
+            "2025-02-30",
+            "2025-13-01",
+            "2025-3-01",
+            "+2025-03-01",
+            "2025-03-01T",
+        ] {
+            assert!(!is_valid_date(bad), "{bad}");
+        }
+    }
+
+    #[test]
+    fn edits_bump_the_revision() {
+        let store = store();
+        let first = store.create(draft("first")).unwrap();
+        let second = store.create(draft("  second  ")).unwrap();
+        assert_eq!(second.title, "second");
+        assert_eq!(store.list().unwrap(), [second.clone(), first.clone()]);
+
+        let toggled = store.toggle(&first.id).unwrap();
+        assert!(toggled.completed);
+        assert_eq!(toggled.revision, 1);
+        let renamed = store.update(&first.id, 1, draft("renamed")).unwrap();
+        assert_eq!((renamed.title.as_str(), renamed.revision), ("renamed", 2));
+
+        store.delete(&second.id).unwrap();
+        assert_eq!(store.list().unwrap(), [renamed]);
+    }
+
+    #[test]
+    fn edits_are_recorded_in_history() {
+        let storage = Arc::new(MemoryStorage::default());
+        let store = TaskStore::new(storage.clone(), Arc::new(()));
+        let task = store.create(draft("first")).unwrap();
+        store.update(&task.id, 0, draft("second")).unwrap();
+        store.toggle(&task.id).unwrap();
+
+        let history = storage.load_updates(&Default::default()).unwrap();
+        assert_eq!(history.len(), 3);
+        assert!(history[0].is_creation());
+        assert_eq!(history[1].diff["title"]["old"], "first");
+        assert_eq!(history[2].diff["completed"]["new"], true);
+        assert_eq!(history[2].full_snapshot.as_ref().unwrap()["revision"], 2);
+
+        store.delete(&task.id).unwrap();
+        let history = storage.load_updates(&Default::default()).unwrap();
+        assert!(history[3].is_deletion());
+        assert_eq!(history[3].diff["title"]["old"], "second");
+    }
+
+    #[test]
+    fn revert_restores_an_old_version_as_a_new_revision() {
+        let store = store();
+        let task = store.create(draft("first")).unwrap();
+        let second = store.update(&task.id, 0, draft("second")).unwrap();
+        store.update(&task.id, 1, draft("third")).unwrap();
+
+        let first_edit = &store.history().query(&Default::default()).unwrap()[1];
+        let reverted = store.revert(&task.id, &first_edit.update_id).unwrap();
+        assert_eq!(reverted.title, "second");
+        assert_eq!(reverted.revision, 3);
+        assert_eq!(reverted.created_at, second.created_at);
+        assert_eq!(store.history().query(&Default::default()).unwrap().len(), 4);
+        assert!(store.revert(&task.id, "missing").is_err());
+    }
+
+    #[test]
+    fn replaced_tasks_move_past_both_revisions() {
+        let store = store();
+        let task = store.create(draft("first")).unwrap();
+        let current = store.update(&task.id, 0, draft("second")).unwrap();
+        let unchanged = store.create(draft("unchanged")).unwrap();
+
+        store
+            .replace_all(vec![task.clone(), unchanged.clone()])
+            .unwrap();
+        let tasks = store.list().unwrap();
+        assert_eq!((tasks[0].title.as_str(), tasks[0].revision), ("first", 2));
+        assert_eq!(tasks[1], unchanged);
+        assert!(store
+            .update(&task.id, current.revision, draft("stale"))
+            .is_err());
+
+        let history = store.history().query(&Default::default()).unwrap();
+        assert_eq!(history.len(), 4);
+        assert_eq!(history[3].diff["title"]["old"], "second");
+    }
+
+    #[test]
+    fn replaced_lists_record_creations_and_deletions() {
+        let store = store();
+        let gone = store.create(draft("gone")).unwrap();
+        let new = Task {
+            id: "new".into(),
+            title: "new".into(),
+            ..gone.clone()
+        };
+        store.replace_all(vec![new.clone()]).unwrap();
+
+        let history = store.history();
+        let records = history.query(&Default::default()).unwrap();
+        assert_eq!(records.len(), 3);
+        assert!(records[1].is_creation() && records[1].task_id == "new");
+        assert!(records[2].is_deletion() && records[2].task_id == gone.id);
+        assert!(history.task_at("new", records[1].timestamp - 1).is_err());
+        assert_eq!(history.task_at("new", records[1].timestamp).unwrap(), new);
+
+        // Brought back after its deletion, it is live again in its history.
+        store.replace_all(vec![new, gone.clone()]).unwrap();
+        assert_eq!(history.task_at(&gone.id, now_ms()).unwrap(), gone);
+    }
+
+    #[test]
+    fn stale_updates_are_rejected_with_the_current_copy() {
+        let store = store();
+        let task = store.create(draft("shared")).unwrap();
+        let planner = store.update(&task.id, 0, draft("from planner")).unwrap();
+
+        match store.update(&task.id, 0, draft("from dashboard")) {
+            Err(Error::Conflict { current }) => assert_eq!(*current, planner),
+            other => panic!("expected a conflict, got {other:?}"),
+        }
+        assert_eq!(store.list().unwrap(), [planner]);
+    }
+
+    #[test]
+    fn every_write_is_announced_with_its_revision() {
+        let events = Arc::new(Recorded::default());
+        let store = TaskStore::new(Arc::new(MemoryStorage::default()), events.clone());
+        let a = store.create(draft("a")).unwrap();
+        store.toggle(&a.id).unwrap();
+        let b = Task {
+            id: "b".into(),
+            ..a.clone()
+        };
+        store.replace_all(vec![b]).unwrap();
+        assert!(store.delete(&a.id).is_err());
+
+        let events = events.0.lock().unwrap();
+        let payloads: Vec<_> = events.iter().map(|(_, p)| p.clone()).collect();
+        assert!(events.iter().all(|(name, _)| name == events::TASKS_CHANGED));
+        assert_eq!(
+            payloads,
+            [
+                json!({ "changed": [{ "id": a.id, "revision": 0 }], "deleted": [], "external": false }),
+                json!({ "changed": [{ "id": a.id, "revision": 1 }], "deleted": [], "external": false }),
+                json!({ "changed": [{ "id": "b", "revision": 0 }], "deleted": [a.id], "external": false }),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_drafts_and_unknown_ids_are_rejected() {
+        let store = store();
+        assert!(matches!(store.create(draft(" ")), Err(Error::Invalid(_))));
+        let mut late = draft("late");
+        late.due_hour = Some(24);
+        assert!(matches!(store.create(late), Err(Error::Invalid(_))));
+        assert!(matches!(store.toggle("nope"), Err(Error::TaskNotFound(_))));
+        assert!(store.list().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,319 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::de::DeserializeOwned;
+
+use crate::crypto;
+use crate::error::Result;
+use crate::events::{self, Events};
+use crate::persist::{self, SETTINGS_FILE, STRIKES_FILE};
+use crate::schema;
+use crate::settings::AppSettings;
+use crate::storage::Storage;
+use crate::strikes::{StrikeEntry, StrikesChanged};
+use crate::tasks::{Task, TaskRevision, TasksChanged, TASKS_FILE};
+
+/// Editors and sync tools often write a file in several steps; wait this long after
+/// the last event before reading it.
+const SETTLE: Duration = Duration::from_millis(300);
+
+/// Watches the data directory for edits made outside the app (scripts, Syncthing)
+/// and tells the windows about them. Dropping it stops the watch.
+pub struct DataWatcher {
+    _watcher: RecommendedWatcher,
+}
+
+impl DataWatcher {
+    /// Watches the files `storage` reads from disk. Backends that keep their data
+    /// elsewhere have nothing to watch, and no thread is started.
+    pub fn start(
+        dir: PathBuf,
+        storage: Arc<dyn Storage>,
+        events: Arc<dyn Events>,
+    ) -> Result<Option<Self>> {
+        let files = storage.watched_files();
+        if files.is_empty() {
+            return Ok(None);
+        }
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx).map_err(watch_error)?;
+        watcher
+            .watch(&dir, RecursiveMode::NonRecursive)
+            .map_err(watch_error)?;
+
+        let mut state = Snapshot::load(dir, storage, events)?;
+        thread::spawn(move || {
+            // Each event is a `notify::Result<Event>`; errors just mean nothing to collect.
+            while let Ok(mut event) = rx.recv() {
+                let mut touched = BTreeSet::new();
+                loop {
+                    if let Ok(event) = event {
+                        touched.extend(
+                            event
+                                .paths
+                                .iter()
+                                .filter_map(|p| p.file_name()?.to_str())
+                                .filter(|name| files.contains(name))
+                                .map(str::to_string),
+                        );
+                    }
+                    event = match rx.recv_timeout(SETTLE) {
+                        Ok(next) => next,
+                        Err(RecvTimeoutError::Timeout) => break,
+                        Err(RecvTimeoutError::Disconnected) => return,
+                    };
+                }
+                for name in &touched {
+                    if let Err(e) = state.reload(name) {
+                        eprintln!("could not reload {name}: {e}");
+                    }
+                }
+            }
+        });
+        Ok(Some(Self { _watcher: watcher }))
+    }
+}
+
+fn watch_error(e: notify::Error) -> crate::error::Error {
+    crate::error::Error::Invalid(format!("could not watch the data directory: {e}"))
+}
+
+/// What the files held when last looked at, to work out what an edit changed.
+struct Snapshot {
+    dir: PathBuf,
+    storage: Arc<dyn Storage>,
+    events: Arc<dyn Events>,
+    tasks: Vec<Task>,
+    strikes: Vec<StrikeEntry>,
+    settings: Option<AppSettings>,
+}
+
+impl Snapshot {
+    fn load(dir: PathBuf, storage: Arc<dyn Storage>, events: Arc<dyn Events>) -> Result<Self> {
+        Ok(Self {
+            tasks: storage.load_tasks()?,
+            strikes: storage.load_strikes()?,
+            settings: storage.load_settings()?,
+            dir,
+            storage,
+            events,
+        })
+    }
+
+    /// Re-reads one file after it changed on disk. The app's own writes are only
+    /// remembered (the stores announce those themselves); anything else is loaded
+    /// through the backend, which quarantines it if it does not validate, and announced.
+    fn reload(&mut self, name: &str) -> Result<()> {
+        let path = self.dir.join(name);
+        match fs::read(&path) {
+            Ok(bytes) if persist::is_own_write(&path, &bytes) => return self.remember(name, bytes),
+            Ok(_) => {}
+            // Deleted or mid-rename: the next event will bring it back.
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        }
+        match name {
+            TASKS_FILE => {
+                let Some(tasks) = self.storage.reload_tasks()? else {
+                    // Quarantined and announced as `data-recovered`; the windows reload.
+                    self.tasks = self.storage.load_tasks()?;
+                    return Ok(());
+                };
+                let changed = diff_tasks(&self.tasks, &tasks);
+                self.tasks = tasks;
+                if !changed.changed.is_empty() || !changed.deleted.is_empty() {
+                    self.events
+                        .emit(events::TASKS_CHANGED, serde_json::to_value(changed)?);
+                }
+            }
+            STRIKES_FILE => {
+                let strikes = self.storage.load_strikes()?;
+                let changed = StrikesChanged::between(&self.strikes, &strikes);
+                self.strikes = strikes;
+                if !changed.task_ids.is_empty() {
+                    self.events
+                        .emit(events::STRIKES_CHANGED, serde_json::to_value(changed)?);
+                }
+            }
+            SETTINGS_FILE => {
+                let settings = self.storage.load_settings()?;
+                if settings != self.settings {
+                    self.settings = settings.clone();
+                    let settings = settings.unwrap_or_default();
+                    self.events
+                        .emit(events::SETTINGS_CHANGED, serde_json::to_value(settings)?);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Takes `bytes`, which the app wrote itself, as the new state of `name`, so the
+    /// next outside edit is compared with what was really there before it.
+    fn remember(&mut self, name: &str, bytes: Vec<u8>) -> Result<()> {
+        match name {
+            TASKS_FILE => self.tasks = decode(name, bytes)?,
+            STRIKES_FILE => self.strikes = decode(name, bytes)?,
+            SETTINGS_FILE => self.settings = Some(decode(name, bytes)?),
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// A data file's contents as the app wrote them: possibly encrypted, in the version
+/// envelope.
+fn decode<T: DeserializeOwned>(name: &str, bytes: Vec<u8>) -> Result<T> {
+    let plain = {
+        let _guard = crypto::guard();
+        crypto::open(bytes)?
+    };
+    let (_, data) = schema::upgrade(name, serde_json::from_slice(&plain)?)?;
+    Ok(serde_json::from_value(data)?)
+}
+
+/// Tasks added or edited in any way (external edits need not bump the revision),
+/// and ids that disappeared.
+fn diff_tasks(before: &[Task], after: &[Task]) -> TasksChanged {
+    let changed = after
+        .iter()
+        .filter(|t| !before.contains(t))
+        .map(|t| TaskRevision {
+            id: t.id.clone(),
+            revision: t.revision,
+        })
+        .collect();
+    let deleted = before
+        .iter()
+        .filter(|old| !after.iter().any(|t| t.id == old.id))
+        .map(|old| old.id.clone())
+        .collect();
+    TasksChanged {
+        changed,
+        deleted,
+        external: true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::Recorded;
+    use crate::storage::JsonStorage;
+
+    #[test]
+    fn own_writes_are_skipped_and_external_edits_announced() {
+        let dir = tempfile::tempdir().unwrap();
+        let events = Arc::new(Recorded::default());
+        let storage = Arc::new(JsonStorage::new(dir.path().to_path_buf(), Arc::new(())));
+        let mut snapshot =
+            Snapshot::load(dir.path().into(), storage.clone(), events.clone()).unwrap();
+
+        let task: Task = serde_json::from_str(
+            r#"{"id":"a","revision":0,"title":"t","completed":false,"createdAt":1,"updatedAt":1}"#,
+        )
+        .unwrap();
+        storage.save_tasks(std::slice::from_ref(&task)).unwrap();
+        snapshot.reload(TASKS_FILE).unwrap();
+        assert!(events.0.lock().unwrap().is_empty());
+
+        // A script renames the task without bumping its revision.
+        let edited = Task {
+            title: "renamed".into(),
+            ..task
+        };
+        fs::write(
+            dir.path().join(TASKS_FILE),
+            serde_json::to_vec(&[&edited]).unwrap(),
+        )
+        .unwrap();
+        snapshot.reload(TASKS_FILE).unwrap();
+        fs::write(dir.path().join(TASKS_FILE), "[]").unwrap();
+        snapshot.reload(TASKS_FILE).unwrap();
+
+        let events = events.0.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].0, events::TASKS_CHANGED);
+        assert_eq!(events[0].1["changed"][0]["id"], "a");
+        assert_eq!(events[0].1["external"], true);
+        assert_eq!(events[1].1["deleted"][0], "a");
+    }
+
+    #[test]
+    fn own_writes_update_what_later_edits_are_compared_with() {
+        let dir = tempfile::tempdir().unwrap();
+        let events = Arc::new(Recorded::default());
+        let storage = Arc::new(JsonStorage::new(dir.path().to_path_buf(), Arc::new(())));
+        let mut snapshot =
+            Snapshot::load(dir.path().into(), storage.clone(), events.clone()).unwrap();
+
+        let task: Task = serde_json::from_str(
+            r#"{"id":"a","revision":0,"title":"t","completed":false,"createdAt":1,"updatedAt":1}"#,
+        )
+        .unwrap();
+        storage.save_tasks(std::slice::from_ref(&task)).unwrap();
+        snapshot.reload(TASKS_FILE).unwrap();
+        assert_eq!(snapshot.tasks, std::slice::from_ref(&task));
+
+        // The same list written by hand, without the envelope: nothing changed.
+        fs::write(
+            dir.path().join(TASKS_FILE),
+            serde_json::to_vec(&[&task]).unwrap(),
+        )
+        .unwrap();
+        snapshot.reload(TASKS_FILE).unwrap();
+        assert!(events.0.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn invalid_external_edits_are_recovered_not_announced() {
+        let dir = tempfile::tempdir().unwrap();
+        let events = Arc::new(Recorded::default());
+        let storage = Arc::new(JsonStorage::new(dir.path().to_path_buf(), events.clone()));
+        let mut snapshot =
+            Snapshot::load(dir.path().into(), storage.clone(), events.clone()).unwrap();
+
+        let task: Task = serde_json::from_str(
+            r#"{"id":"a","revision":0,"title":"t","completed":false,"createdAt":1,"updatedAt":1}"#,
+        )
+        .unwrap();
+        // Twice, so the first generation is kept as `.bak`.
+        storage.save_tasks(std::slice::from_ref(&task)).unwrap();
+        storage.save_tasks(std::slice::from_ref(&task)).unwrap();
+        snapshot.reload(TASKS_FILE).unwrap();
+
+        for bad in [
+            serde_json::to_vec(&[&task, &task]).unwrap(),
+            serde_json::to_vec(&[Task {
+                due_hour: Some(24),
+                ..task.clone()
+            }])
+            .unwrap(),
+            serde_json::to_vec(&[Task {
+                due_date: Some("2025-02-30".into()),
+                ..task.clone()
+            }])
+            .unwrap(),
+        ] {
+            fs::write(dir.path().join(TASKS_FILE), bad).unwrap();
+            snapshot.reload(TASKS_FILE).unwrap();
+            assert_eq!(snapshot.tasks, std::slice::from_ref(&task));
+            assert_eq!(storage.load_tasks().unwrap(), std::slice::from_ref(&task));
+        }
+
+        let events = events.0.lock().unwrap();
+        assert_eq!(events.len(), 3);
+        assert!(events
+            .iter()
+            .all(|(name, _)| name == events::DATA_RECOVERED));
+        assert!(events.iter().all(|(_, r)| r["restoredFromBackup"] == true));
+    }
+}
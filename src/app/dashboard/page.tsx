@@ -23,7 +23,7 @@ import { useState, useEffect, useRef } from "react";
 import { useRouter } from "next/navigation";
 
 // Tauri desktop app imports (file system operations)
-import { readTextFile, writeTextFile, exists, BaseDirectory } from "@tauri-apps/plugin-fs";
+import { invoke } from "@tauri-apps/api/core";
 
 // UI component imports
 import { Button } from "@/components/ui/button";
@@ -46,6 +46,8 @@ import {
 } from "@/lib/local-storage";
 import { getQuirkyNickname, getGreeting } from "@/lib/quirky-nicknames";
 import { useKeyboardShortcuts } from "@/hooks/useKeyboardShortcuts";
+import { listTasks } from "@/lib/task-store";
+import { useStoreEvent } from "@/lib/store-events";
 import { toast } from "sonner";
 
 // ============================================================================
@@ -73,16 +75,6 @@ interface Task {
   dueHour?: number;     // Optional: hour of day task is due (0-23)
 }
 
-// ============================================================================
-// CONSTANTS
-// ============================================================================
-
-/**
- * File name for storing tasks in Tauri desktop app
- * Location: AppData directory
- */
-const TASKS_FILE = "tasks.json";
-
 // ============================================================================
 // DATA LOADING FUNCTIONS
 // ============================================================================
@@ -93,7 +85,7 @@ const TASKS_FILE = "tasks.json";
  * 
  * Flow:
  * 1. Check if running in Tauri desktop app
- * 2. If Tauri: Read from AppData/tasks.json
+ * 2. If Tauri: Ask the Rust task store (list_tasks)
  * 3. If Web: Fetch from /api/tasks endpoint
  * 4. Return parsed task array or empty array on error
  */
@@ -101,12 +93,7 @@ async function loadTasks(): Promise<Task[]> {
   try {
     // Desktop app path: Read from local file system
     if (await isTauri()) {
-      const ok = await exists(TASKS_FILE, { baseDir: BaseDirectory.AppData });
-      if (!ok) return []; // File doesn't exist yet
-      
-      const txt = await readTextFile(TASKS_FILE, { baseDir: BaseDirectory.AppData });
-      const data = JSON.parse(txt);
-      return Array.isArray(data) ? (data as Task[]) : [];
+      return await listTasks();
     }
     
     // Web app path: Fetch from API endpoint
@@ -126,18 +113,15 @@ async function loadTasks(): Promise<Task[]> {
  * @returns Promise<DashboardWidget[]> - Array of widgets to display
  * 
  * Storage locations:
- * - Tauri: App/dashboard-widgets.json
+ * - Tauri: <data dir>/dashboard-widgets.json
  * - Web: localStorage key "dashboard-widgets"
  */
 async function loadDashboardWidgets(): Promise<DashboardWidget[]> {
   try {
-    // Desktop app path: Read from app data directory
+    // Desktop app path: Read from the data directory via Rust
     if (await isTauri()) {
-      const ok = await exists("dashboard-widgets.json", { baseDir: BaseDirectory.App });
-      if (!ok) return [];
-      
-      const txt = await readTextFile("dashboard-widgets.json", { baseDir: BaseDirectory.App });
-      return JSON.parse(txt);
+      const data = await invoke<DashboardWidget[] | null>("read_data_file", { name: "dashboard-widgets.json" });
+      return data ?? [];
     }
     
     // Web app path: Read from localStorage
@@ -153,18 +137,14 @@ async function loadDashboardWidgets(): Promise<DashboardWidget[]> {
  * @param widgets - Array of widgets to save
  * 
  * Storage locations:
- * - Tauri: App/dashboard-widgets.json
+ * - Tauri: <data dir>/dashboard-widgets.json
  * - Web: localStorage key "dashboard-widgets"
  */
 async function saveDashboardWidgets(widgets: DashboardWidget[]): Promise<void> {
   try {
-    // Desktop app path: Write to app data directory
+    // Desktop app path: Write to the data directory via Rust (atomic)
     if (await isTauri()) {
-      await writeTextFile(
-        "dashboard-widgets.json", 
-        JSON.stringify(widgets, null, 2), 
-        { baseDir: BaseDirectory.App }
-      );
+      await invoke("write_data_file", { name: "dashboard-widgets.json", data: widgets });
     } else {
       // Web app path: Write to localStorage
       localStorage.setItem("dashboard-widgets", JSON.stringify(widgets));
@@ -221,6 +201,16 @@ export default function DashboardPage() {
    * Value: numeric count
    */
   const [widgetStats, setWidgetStats] = useState<Record<string, number>>({});
+
+  /**
+   * Bumped when another window changes tasks, strikes or settings,
+   * so the statistics below are recalculated
+   */
+  const [dataVersion, setDataVersion] = useState(0);
+  const bumpDataVersion = () => setDataVersion(v => v + 1);
+  useStoreEvent("tasks-changed", bumpDataVersion);
+  useStoreEvent("strikes-changed", bumpDataVersion);
+  useStoreEvent("settings-changed", bumpDataVersion);
   
   // ==========================================================================
   // REFS
@@ -305,7 +295,7 @@ export default function DashboardPage() {
   // ==========================================================================
   
   /**
-   * Runs once on mount to:
+   * Runs on mount (and again whenever dataVersion changes) to:
    * 1. Load custom widgets configuration
    * 2. Load all necessary data (settings, strikes, tasks)
    * 3. Calculate statistics for each widget type
@@ -413,7 +403,7 @@ export default function DashboardPage() {
     return () => {
       mounted = false;
     };
-  }, []);
+  }, [dataVersion]);
 
   // ==========================================================================
   // EVENT HANDLERS
@@ -6,6 +6,8 @@ import ErrorReporter from "@/components/ErrorReporter";
 import { ThemeProvider } from "@/components/ThemeProvider";
 import { Toaster } from "@/components/ui/sonner";
 import { ColorCustomizer } from "@/components/ColorCustomizer";
+import { DataRecoveryListener } from "@/components/tauri/DataRecoveryListener";
+import { UnlockGate } from "@/components/tauri/UnlockGate";
 import Script from "next/script";
 
 export const metadata: Metadata = {
@@ -58,8 +60,9 @@ export default function RootLayout({
               </div>
             </nav>
           </header>
-          {children}
+          <UnlockGate>{children}</UnlockGate>
           <Toaster />
+          <DataRecoveryListener />
           <VisualEditsMessenger />
         </ThemeProvider>
       </body>
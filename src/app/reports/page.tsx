@@ -7,10 +7,13 @@ import { Input } from "@/components/ui/input";
 import { Label } from "@/components/ui/label";
 import { ScrollArea } from "@/components/ui/scroll-area";
 import { Button } from "@/components/ui/button";
-import { Plus } from "lucide-react";
+import { Copy, Download, Plus } from "lucide-react";
 import { loadSettings, loadStrikes, type StrikeEntry, formatDateInTZ, isTauri } from "@/lib/local-storage";
-import { readTextFile, exists, BaseDirectory } from "@tauri-apps/plugin-fs";
+import { invoke } from "@tauri-apps/api/core";
+import { listTasks } from "@/lib/task-store";
+import { useStoreEvent } from "@/lib/store-events";
 import { toast } from "sonner";
+import { exportStrikesCsv, strikeReport, downloadCsv, type ReportPeriod } from "@/lib/reports";
 
 // Minimal task shape
 interface Task {
@@ -21,16 +24,10 @@ interface Task {
   dueHour?: number;
 }
 
-const TASKS_FILE = "tasks.json";
-
 async function loadTasks(): Promise<Task[]> {
   try {
     if (await isTauri()) {
-      const ok = await exists(TASKS_FILE, { baseDir: BaseDirectory.App });
-      if (!ok) return [];
-      const txt = await readTextFile(TASKS_FILE, { baseDir: BaseDirectory.App });
-      const data = JSON.parse(txt);
-      return Array.isArray(data) ? (data as Task[]) : [];
+      return await listTasks();
     }
     const res = await fetch("/api/tasks");
     if (!res.ok) return [];
@@ -47,6 +44,7 @@ export default function ReportsPage() {
   const [timezone, setTimezone] = useState<string>("UTC");
   const [resetHour, setResetHour] = useState<number>(9);
   const [dateFilter, setDateFilter] = useState<string>(""); // YYYY-MM-DD
+  const [tauri, setTauri] = useState(false);
 
   useEffect(() => {
     let mounted = true;
@@ -57,12 +55,21 @@ export default function ReportsPage() {
       setResetHour(s.resetHour);
       setStrikes(st);
       setTasks(t);
+      setTauri(await isTauri());
       // default to today in user TZ
       setDateFilter(formatDateInTZ(Date.now(), s.timezone));
     })();
     return () => { mounted = false; };
   }, []);
 
+  // Changes made in other windows
+  useStoreEvent("tasks-changed", () => { loadTasks().then(setTasks); });
+  useStoreEvent("strikes-changed", () => { loadStrikes().then(setStrikes); });
+  useStoreEvent("settings-changed", s => {
+    setTimezone(s.timezone);
+    setResetHour(s.resetHour);
+  });
+
   // month key adjusted by resetHour
   const now = new Date();
   const monthKey = useMemo(() => {
@@ -107,6 +114,31 @@ export default function ReportsPage() {
     return task?.title || taskId;
   };
 
+  // Desktop: this month's strike history as CSV
+  const handleExportCsv = async () => {
+    const [y, m] = monthKey.split("-").map(Number);
+    const last = new Date(y, m, 0).getDate();
+    try {
+      const csv = await exportStrikesCsv(`${monthKey}-01`, `${monthKey}-${String(last).padStart(2, "0")}`);
+      downloadCsv(csv, `shakshuka-strikes-${monthKey}.csv`);
+    } catch (error) {
+      console.error("CSV export failed:", error);
+      toast.error("CSV export failed");
+    }
+  };
+
+  // Desktop: Markdown report of the selected day or its week, copied for pasting into a status update
+  const handleCopyReport = async (period: ReportPeriod) => {
+    try {
+      const markdown = await strikeReport(period, dateFilter || formatDateInTZ(Date.now(), timezone));
+      await navigator.clipboard.writeText(markdown);
+      toast.success(period === "day" ? "Daily report copied" : "Weekly report copied");
+    } catch (error) {
+      console.error("Report failed:", error);
+      toast.error("Could not create the report");
+    }
+  };
+
   // Handler to add widget to dashboard
   const addToHomepage = async (widgetType: string, widgetTitle: string) => {
     try {
@@ -259,8 +291,24 @@ export default function ReportsPage() {
       </div>
 
       <Card>
-        <CardHeader>
+        <CardHeader className="flex flex-row flex-wrap items-center justify-between gap-2">
           <CardTitle>Daily detail</CardTitle>
+          {tauri && (
+            <div className="flex flex-wrap gap-2">
+              <Button size="sm" variant="outline" onClick={() => handleCopyReport("day")}>
+                <Copy className="h-4 w-4 mr-1" />
+                Daily report
+              </Button>
+              <Button size="sm" variant="outline" onClick={() => handleCopyReport("week")}>
+                <Copy className="h-4 w-4 mr-1" />
+                Weekly report
+              </Button>
+              <Button size="sm" variant="outline" onClick={handleExportCsv} title="This month's strike history">
+                <Download className="h-4 w-4 mr-1" />
+                CSV
+              </Button>
+            </div>
+          )}
         </CardHeader>
         <CardContent className="space-y-4">
           <div className="grid gap-2 max-w-xs">
@@ -311,10 +359,8 @@ type DashboardWidget = {
 async function loadDashboardWidgets(): Promise<DashboardWidget[]> {
   try {
     if (await isTauri()) {
-      const ok = await exists("dashboard-widgets.json", { baseDir: BaseDirectory.App });
-      if (!ok) return [];
-      const txt = await readTextFile("dashboard-widgets.json", { baseDir: BaseDirectory.App });
-      return JSON.parse(txt);
+      const data = await invoke<DashboardWidget[] | null>("read_data_file", { name: "dashboard-widgets.json" });
+      return data ?? [];
     }
     const raw = localStorage.getItem("dashboard-widgets");
     return raw ? JSON.parse(raw) : [];
@@ -326,8 +372,7 @@ async function loadDashboardWidgets(): Promise<DashboardWidget[]> {
 async function saveDashboardWidgets(widgets: DashboardWidget[]): Promise<void> {
   try {
     if (await isTauri()) {
-      const { writeTextFile } = await import("@tauri-apps/plugin-fs");
-      await writeTextFile("dashboard-widgets.json", JSON.stringify(widgets, null, 2), { baseDir: BaseDirectory.App });
+      await invoke("write_data_file", { name: "dashboard-widgets.json", data: widgets });
     } else {
       localStorage.setItem("dashboard-widgets", JSON.stringify(widgets));
     }
@@ -5,11 +5,18 @@ import { Card, CardContent, CardHeader, CardTitle } from "@/components/ui/card";
 import { Label } from "@/components/ui/label";
 import { Input } from "@/components/ui/input";
 import { Button } from "@/components/ui/button";
-import { loadSettings, saveSettings, type AppSettings, loadStrikes, loadUpdates, loadUsedMessages, saveStrikes, saveUpdates, saveUsedMessages } from "@/lib/local-storage";
+import { loadSettings, saveSettings, type AppSettings, loadStrikes, loadUpdates, loadUsedMessages, saveStrikes, saveUpdates, saveUsedMessages, getDataDir } from "@/lib/local-storage";
 import { toast } from "sonner";
-import { Download, Upload, RefreshCw } from "lucide-react";
+import { Download, Upload, RefreshCw, CalendarDays, Stethoscope } from "lucide-react";
 import { getVersion } from "@tauri-apps/api/app";
-import { readTextFile, writeTextFile, exists, BaseDirectory } from "@tauri-apps/plugin-fs";
+import { listTasks, compactHistory } from "@/lib/task-store";
+import { listBackups, createBackup, previewRestore, restoreBackup, type BackupInfo } from "@/lib/backups";
+import { encryptionStatus, changePassphrase } from "@/lib/encryption";
+import { exportAll, importAll, askImportMode, describeRejected } from "@/lib/bundle";
+import { exportTasksIcs, downloadIcs } from "@/lib/calendar";
+import { sourceOf, previewTaskImport, importTasks } from "@/lib/importers";
+import { runDoctor, describeIssues } from "@/lib/doctor";
+import { listProfiles, switchProfile, type ProfileList } from "@/lib/profiles";
 import { check } from "@tauri-apps/plugin-updater";
 import { relaunch } from "@tauri-apps/plugin-process";
 
@@ -63,7 +70,8 @@ async function isTauri(): Promise<boolean> {
  * The app supports TWO storage backends depending on runtime environment:
  * 
  * 1. TAURI (Desktop App):
- *    - Uses filesystem: BaseDirectory.AppData
+ *    - Uses the Rust task store (list_tasks and the per-task commands; imports go through import_all)
+ *    - Files are written atomically; the previous version is kept as <file>.bak
  *    - Location: Platform-specific app data folder
  *      * Windows: %APPDATA%/com.shakshuka.app/
  *      * macOS: ~/Library/Application Support/com.shakshuka.app/
@@ -80,10 +88,8 @@ async function isTauri(): Promise<boolean> {
  * IMPORTANT: Both systems must maintain compatible data formats!
  */
 
-const TASKS_FILE = "tasks.json";
-
 /**
- * Fetch tasks from Tauri filesystem (Desktop app only)
+ * Fetch tasks from the Rust task store (Desktop app only)
  * 
  * @returns {Promise<any[]>} Array of tasks or empty array if error/not found
  */
@@ -91,11 +97,7 @@ async function fetchTasksTauri() {
   try {
     const available = await isTauri();
     if (!available) return [];
-    const fileExists = await exists(TASKS_FILE, { baseDir: BaseDirectory.AppData });
-    if (!fileExists) return [];
-    const text = await readTextFile(TASKS_FILE, { baseDir: BaseDirectory.AppData });
-    const data = JSON.parse(text);
-    return Array.isArray(data) ? data : [];
+    return await listTasks();
   } catch {
     return [];
   }
@@ -124,23 +126,6 @@ async function fetchTasksAPI() {
   }
 }
 
-/**
- * Save tasks to Tauri filesystem (Desktop app only)
- * 
- * @param {any[]} tasks - Array of task objects to save
- */
-async function saveTasksTauri(tasks: any[]) {
-  try {
-    const available = await isTauri();
-    if (!available) return;
-    await writeTextFile(TASKS_FILE, JSON.stringify(tasks, null, 2), {
-      baseDir: BaseDirectory.AppData,
-    });
-  } catch {
-    // Silent fail - filesystem errors are non-critical
-  }
-}
-
 /**
  * Save tasks to API endpoint (Web version)
  * 
@@ -182,6 +167,9 @@ async function saveTasksAPI(tasks: any[]) {
  *   updates: {...},
  *   usedMessages: {...}
  * }
+ *
+ * DESKTOP APP: the Rust `export_all` command builds a typed, versioned bundle
+ * instead (src-tauri/src/bundle.rs), encrypted when encryption is on.
  * 
  * USE CASES:
  * - Manual backups before major changes
@@ -194,6 +182,7 @@ async function saveTasksAPI(tasks: any[]) {
  */
 async function exportData() {
   try {
+    if (await isTauri()) return await exportAll();
     const [tasks, settings, strikes, updates, usedMessages] = await Promise.all([
       (await isTauri()) ? fetchTasksTauri() : fetchTasksAPI(),
       loadSettings(),
@@ -229,7 +218,7 @@ async function exportData() {
  * 
  * IMPORT PROCESS:
  * 1. Validate backup file structure
- * 2. Restore tasks to appropriate storage (Tauri file OR API)
+ * 2. Restore tasks to the API (the desktop app imports through importAll instead)
  * 3. Restore localStorage data (settings, strikes, etc)
  * 4. Reload page to apply changes
  * 
@@ -245,12 +234,8 @@ async function importData(data: any) {
       throw new Error("Invalid import data format");
     }
     
-    // Restore tasks to appropriate storage backend
-    if ((await isTauri())) {
-      await saveTasksTauri(data.tasks);
-    } else {
-      await saveTasksAPI(data.tasks);
-    }
+    // Restore tasks (web only: the desktop app imports through importAll)
+    await saveTasksAPI(data.tasks);
     
     // Restore localStorage data
     if (data.settings) await saveSettings(data.settings);
@@ -277,20 +262,42 @@ export default function SettingsPage() {
   const [checking, setChecking] = useState(false);
   const [updating, setUpdating] = useState(false);
   const [totalSize, setTotalSize] = useState<number | null>(null);
+  const [dataDir, setDataDir] = useState<string | null>(null);
+  const [profiles, setProfiles] = useState<ProfileList | null>(null);
+  const [newProfile, setNewProfile] = useState("");
+  const [compacting, setCompacting] = useState(false);
+  const [backups, setBackups] = useState<BackupInfo[]>([]);
+  const [backingUp, setBackingUp] = useState(false);
+  const [encrypted, setEncrypted] = useState(false);
+  const [currentPassphrase, setCurrentPassphrase] = useState("");
+  const [newPassphrase, setNewPassphrase] = useState("");
+  const [confirmPassphrase, setConfirmPassphrase] = useState("");
+  const [rekeying, setRekeying] = useState(false);
   const fileInputRef = useRef<HTMLInputElement>(null);
+  const otherImportRef = useRef<HTMLInputElement>(null);
 
   useEffect(() => {
     let mounted = true;
     
     const loadData = async () => {
       try {
-        const [s, tauri] = await Promise.all([
+        const [s, tauri, dir] = await Promise.all([
           loadSettings(),
-          isTauri()
+          isTauri(),
+          getDataDir()
         ]);
         if (mounted) {
           setSettings(s);
           setIsTauriApp(tauri);
+          setDataDir(dir);
+        }
+        if (tauri) {
+          const [list, status, profileList] = await Promise.all([listBackups(), encryptionStatus(), listProfiles()]);
+          if (mounted) {
+            setBackups(list);
+            setEncrypted(status.enabled);
+            setProfiles(profileList);
+          }
         }
       } catch (error) {
         console.error("Failed to load settings:", error);
@@ -325,6 +332,91 @@ export default function SettingsPage() {
     }
   };
 
+  const handleCreateBackup = async () => {
+    setBackingUp(true);
+    try {
+      const backup = await createBackup();
+      setBackups(await listBackups());
+      if (backup.skipped.length > 0) {
+        toast.warning(
+          `Backup created without ${backup.skipped.join(", ")}, which could not be read`
+        );
+      } else {
+        toast.success("Backup created");
+      }
+    } catch (error) {
+      console.error("Backup failed:", error);
+      toast.error("Backup failed");
+    } finally {
+      setBackingUp(false);
+    }
+  };
+
+  const handleRestoreBackup = async (backup: BackupInfo) => {
+    try {
+      const p = await previewRestore(backup.id);
+      const lines = [
+        `Restore the ${backup.kind} backup from ${new Date(backup.createdAt).toLocaleString()}?`,
+        "",
+        `Tasks: ${p.tasksAdded.length} added, ${p.tasksRemoved.length} removed, ${p.tasksChanged.length} changed`,
+        `Strikes: ${p.strikesCurrent} now, ${p.strikesInBackup} in backup (${p.strikesAdded} added, ${p.strikesRemoved} removed)`,
+        `Edit history: ${p.updatesCurrent} entries now, ${p.updatesInBackup} in backup`,
+        p.settingsChanged ? "Settings will change." : "Settings are unchanged.",
+        "",
+        "A safety backup of your current data is taken first.",
+      ];
+      if (p.tasksRemoved.length > 0) {
+        lines.splice(3, 0, `Removed: ${p.tasksRemoved.slice(0, 5).map(t => t.title).join(", ")}${p.tasksRemoved.length > 5 ? ", ..." : ""}`);
+      }
+      if (!confirm(lines.join("\n"))) return;
+      await restoreBackup(backup.id);
+      toast.success("Backup restored! Page will reload.");
+      setTimeout(() => window.location.reload(), 1000);
+    } catch (error) {
+      console.error("Restore failed:", error);
+      toast.error("Restore failed");
+    }
+  };
+
+  // An empty new passphrase turns encryption off
+  const handleChangePassphrase = async () => {
+    const next = newPassphrase || null;
+    if (next !== null && next !== confirmPassphrase) {
+      toast.error("The new passphrases do not match");
+      return;
+    }
+    if (next === null && !confirm("Turn encryption off? Your data will be stored unencrypted.")) return;
+    setRekeying(true);
+    try {
+      await changePassphrase(encrypted ? currentPassphrase : null, next);
+      setEncrypted(next !== null);
+      setCurrentPassphrase("");
+      setNewPassphrase("");
+      setConfirmPassphrase("");
+      toast.success(next === null ? "Encryption turned off" : encrypted ? "Passphrase changed" : "Encryption turned on");
+    } catch (error) {
+      console.error("Failed to change passphrase:", error);
+      const e = error as { kind?: string; message?: string };
+      toast.error(e?.kind === "badPassphrase" ? "Current passphrase is wrong" : e?.message ?? "Failed to change passphrase");
+    } finally {
+      setRekeying(false);
+    }
+  };
+
+  const handleCompactHistory = async () => {
+    setCompacting(true);
+    try {
+      const report = await compactHistory();
+      const kb = (report.bytesReclaimed / 1024).toFixed(1);
+      toast.success(`History compacted: ${report.updatesRemoved} old entries removed, ${kb} KB reclaimed`);
+    } catch (error) {
+      console.error("Failed to compact history:", error);
+      toast.error("Failed to compact history");
+    } finally {
+      setCompacting(false);
+    }
+  };
+
   const handleExport = async () => {
     try {
       const data = await exportData();
@@ -343,6 +435,81 @@ export default function SettingsPage() {
     }
   };
 
+  const handleExportIcs = async () => {
+    try {
+      downloadIcs(await exportTasksIcs(), `shakshuka-tasks-${new Date().toISOString().split('T')[0]}.ics`);
+      toast.success("Tasks exported for your calendar");
+    } catch (error) {
+      console.error("Calendar export failed:", error);
+      toast.error("Calendar export failed");
+    }
+  };
+
+  // Todoist CSV, Taskwarrior JSON or todo.txt: previewed, confirmed, then merged in
+  const handleImportOther = async (event: React.ChangeEvent<HTMLInputElement>) => {
+    const file = event.target.files?.[0];
+    event.target.value = "";
+    if (!file) return;
+    try {
+      const text = await file.text();
+      const source = sourceOf(file.name);
+      const preview = await previewTaskImport(source, text, file.name);
+      const skipped = preview.rejected.length > 0
+        ? `\n\n${preview.rejected.length} records will be skipped:\n${describeRejected(preview.rejected)}`
+        : "";
+      const warned = preview.warnings.length > 0
+        ? `\n\n${preview.warnings.length} imported without a due date:\n${describeRejected(preview.warnings)}`
+        : "";
+      if (preview.tasks.length === 0) {
+        toast.error(`No tasks to import from ${file.name}`, { description: describeRejected(preview.rejected) || undefined });
+        return;
+      }
+      if (!confirm(`Import ${preview.tasks.length} tasks from ${file.name}?${skipped}${warned}`)) return;
+      const report = await importTasks(source, text, file.name);
+      toast.success(`Imported ${report.tasksImported} tasks. Page will reload.`);
+      setTimeout(() => window.location.reload(), 2000);
+    } catch (error) {
+      console.error("Import failed:", error);
+      toast.error("Import failed. Please check the file format.");
+    }
+  };
+
+  // The app restarts into the chosen profile; unsaved settings on this page are lost
+  const handleSwitchProfile = async (name: string) => {
+    const profile = name.trim();
+    if (!profile || profile === profiles?.active) return;
+    if (!confirm(`Switch to the "${profile}" profile? The app will restart.`)) return;
+    try {
+      await switchProfile(profile);
+    } catch (error) {
+      console.error("Profile switch failed:", error);
+      toast.error("Could not switch profile", { description: String(error) });
+    }
+  };
+
+  // Reports problems in the data files, then repairs them (after a backup) if confirmed
+  const handleCheckData = async () => {
+    try {
+      const check = await runDoctor(false);
+      if (check.issues.length === 0) {
+        toast.success("No problems found in your data");
+        return;
+      }
+      const repairable = check.issues.filter((i) => i.fix).length;
+      if (repairable === 0) {
+        toast.error(`${check.issues.length} problems found that cannot be repaired`, { description: describeIssues(check.issues) });
+        return;
+      }
+      if (!confirm(`${check.issues.length} problems found:\n${describeIssues(check.issues)}\n\nRepair ${repairable} of them? A backup is taken first.`)) return;
+      const report = await runDoctor(true);
+      toast.success(`Repaired ${repairable} problems. Page will reload.`, { description: report.backup ? `Backup: ${report.backup}` : undefined });
+      setTimeout(() => window.location.reload(), 2000);
+    } catch (error) {
+      console.error("Data check failed:", error);
+      toast.error("Data check failed");
+    }
+  };
+
   const handleImport = async (event: React.ChangeEvent<HTMLInputElement>) => {
     const file = event.target.files?.[0];
     if (!file) return;
@@ -350,6 +517,23 @@ export default function SettingsPage() {
     try {
       const text = await file.text();
       const data = JSON.parse(text);
+      if (isTauriApp) {
+        // Validated and written by the backend; invalid records are reported, not imported
+        const mode = askImportMode();
+        if (mode) {
+          const report = await importAll(data, mode);
+          toast.success(`Imported ${report.tasksImported} tasks and ${report.strikesImported} strikes. Page will reload.`);
+          if (report.rejected.length > 0) {
+            toast.warning(`${report.rejected.length} records were invalid and skipped`, {
+              description: describeRejected(report.rejected),
+              duration: 15000,
+            });
+          }
+          setTimeout(() => window.location.reload(), 3000);
+        }
+        if (fileInputRef.current) fileInputRef.current.value = "";
+        return;
+      }
       await importData(data);
       
       const newSettings = await loadSettings();
@@ -527,6 +711,31 @@ export default function SettingsPage() {
               <Upload className="h-4 w-4 mr-2" />
               Import Data
             </Button>
+            {isTauriApp && (
+              <Button onClick={handleExportIcs} variant="outline">
+                <CalendarDays className="h-4 w-4 mr-2" />
+                Export to Calendar
+              </Button>
+            )}
+            {isTauriApp && (
+              <Button onClick={() => otherImportRef.current?.click()} variant="outline">
+                <Upload className="h-4 w-4 mr-2" />
+                Import from Another App
+              </Button>
+            )}
+            {isTauriApp && (
+              <Button onClick={handleCheckData} variant="outline">
+                <Stethoscope className="h-4 w-4 mr-2" />
+                Check Data
+              </Button>
+            )}
+            <input
+              ref={otherImportRef}
+              type="file"
+              accept=".csv,.json,.txt"
+              onChange={handleImportOther}
+              className="hidden"
+            />
             <input
               ref={fileInputRef}
               type="file"
@@ -537,10 +746,52 @@ export default function SettingsPage() {
           </div>
           <p className="text-xs text-muted-foreground">
             Export your tasks, strikes, and settings as a JSON backup. Import to restore from a previous backup.
+            {isTauriApp && " Export to Calendar saves tasks with a due date or hour as an .ics file for your calendar app."}
+            {isTauriApp && " Import from Another App reads a Todoist CSV, Taskwarrior JSON (task export) or todo.txt file."}
+            {isTauriApp && " Check Data looks for strikes of deleted tasks, duplicate ids and invalid dates or hours, and offers to repair them."}
           </p>
+          {dataDir && (
+            <p className="text-xs text-muted-foreground break-all">
+              Data folder: <code>{dataDir}</code>
+            </p>
+          )}
         </CardContent>
       </Card>
       
+      {isTauriApp && profiles && (
+        <Card>
+          <CardHeader>
+            <CardTitle>Profile</CardTitle>
+          </CardHeader>
+          <CardContent className="space-y-4">
+            <div className="flex flex-wrap gap-2">
+              {profiles.names.map((name) => (
+                <Button
+                  key={name}
+                  variant={name === profiles.active ? "default" : "outline"}
+                  onClick={() => handleSwitchProfile(name)}
+                >
+                  {name}
+                </Button>
+              ))}
+            </div>
+            <div className="flex gap-2 max-w-md">
+              <Input
+                placeholder="New profile, e.g. work"
+                value={newProfile}
+                onChange={(e) => setNewProfile(e.target.value)}
+              />
+              <Button variant="outline" onClick={() => handleSwitchProfile(newProfile)} disabled={!newProfile.trim()}>
+                Create
+              </Button>
+            </div>
+            <p className="text-xs text-muted-foreground">
+              Each profile keeps its own tasks, strikes, settings and planner. Start the app with <code>--profile name</code> to open a specific one.
+            </p>
+          </CardContent>
+        </Card>
+      )}
+
       <Card>
         <CardHeader>
           <CardTitle>Personal</CardTitle>
@@ -599,6 +850,105 @@ export default function SettingsPage() {
         </CardContent>
       </Card>
 
+      {/* BACKUPS SECTION - Desktop App Only */}
+      {isTauriApp && (
+        <Card>
+          <CardHeader>
+            <CardTitle>Backups</CardTitle>
+          </CardHeader>
+          <CardContent className="space-y-4">
+            <p className="text-xs text-muted-foreground">
+              The app keeps compressed daily, weekly and monthly backups in the <code>backups</code> folder of your data directory.
+            </p>
+            {backups.length === 0 ? (
+              <p className="text-sm text-muted-foreground">No backups yet.</p>
+            ) : (
+              <div className="space-y-1 max-h-60 overflow-y-auto">
+                {backups.map((b) => (
+                  <div key={b.id} className="flex items-center justify-between text-sm">
+                    <span>{new Date(b.createdAt).toLocaleString()}</span>
+                    <span className="flex items-center gap-2 text-xs text-muted-foreground">
+                      {b.kind} · {(b.size / 1024).toFixed(1)} KB
+                      <Button size="sm" variant="ghost" onClick={() => handleRestoreBackup(b)}>
+                        Restore
+                      </Button>
+                    </span>
+                  </div>
+                ))}
+              </div>
+            )}
+            <Button onClick={handleCreateBackup} disabled={backingUp} variant="outline">
+              {backingUp ? "Backing up..." : "Back Up Now"}
+            </Button>
+          </CardContent>
+        </Card>
+      )}
+
+      {/* ENCRYPTION SECTION - Desktop App Only */}
+      {isTauriApp && (
+        <Card>
+          <CardHeader>
+            <CardTitle>Encryption</CardTitle>
+          </CardHeader>
+          <CardContent className="space-y-4">
+            <p className="text-xs text-muted-foreground">
+              {encrypted
+                ? "Your data and backups are encrypted. The passphrase is asked for when the app starts."
+                : "Encrypt your data and backups with a passphrase. It cannot be recovered if you forget it."}
+            </p>
+            <div className="grid gap-2 max-w-xs">
+              {encrypted && (
+                <>
+                  <Label htmlFor="currentPassphrase">Current passphrase</Label>
+                  <Input id="currentPassphrase" type="password" value={currentPassphrase} onChange={(e) => setCurrentPassphrase(e.target.value)} />
+                </>
+              )}
+              <Label htmlFor="newPassphrase">New passphrase</Label>
+              <Input id="newPassphrase" type="password" value={newPassphrase} onChange={(e) => setNewPassphrase(e.target.value)} />
+              <Label htmlFor="confirmPassphrase">Confirm new passphrase</Label>
+              <Input id="confirmPassphrase" type="password" value={confirmPassphrase} onChange={(e) => setConfirmPassphrase(e.target.value)} />
+              {encrypted && (
+                <p className="text-xs text-muted-foreground">Leave the new passphrase empty to turn encryption off.</p>
+              )}
+            </div>
+            <Button
+              onClick={handleChangePassphrase}
+              disabled={rekeying || (!encrypted && !newPassphrase) || (encrypted && !currentPassphrase)}
+              variant="outline"
+            >
+              {rekeying ? "Re-encrypting..." : encrypted ? (newPassphrase ? "Change Passphrase" : "Turn Off Encryption") : "Turn On Encryption"}
+            </Button>
+          </CardContent>
+        </Card>
+      )}
+
+      {/* TASK HISTORY SECTION - Desktop App Only */}
+      {isTauriApp && (
+        <Card>
+          <CardHeader>
+            <CardTitle>Task History</CardTitle>
+          </CardHeader>
+          <CardContent className="space-y-4">
+            <div className="grid gap-2 max-w-xs">
+              <Label htmlFor="historyRetentionDays">Keep edit history for (days)</Label>
+              <Input
+                id="historyRetentionDays"
+                type="number"
+                min={0}
+                value={settings.historyRetentionDays ?? 90}
+                onChange={(e) => setSettings((s) => ({ ...s, historyRetentionDays: Math.max(0, Number(e.target.value) || 0) }))}
+              />
+              <p className="text-xs text-muted-foreground">
+                Older edits are removed when the app starts. Use 0 to keep everything.
+              </p>
+            </div>
+            <Button onClick={handleCompactHistory} disabled={compacting} variant="outline">
+              {compacting ? "Compacting..." : "Compact History Now"}
+            </Button>
+          </CardContent>
+        </Card>
+      )}
+
       <Card>
         <CardHeader>
           <CardTitle>Appearance</CardTitle>
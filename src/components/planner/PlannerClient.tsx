@@ -3,10 +3,23 @@
 import { useEffect, useMemo, useRef, useState } from "react";
 import { Card } from "@/components/ui/card";
 import { Button } from "@/components/ui/button";
-import { Calendar, ChevronLeft, ChevronRight, GripVertical } from "lucide-react";
+import { Calendar, ChevronLeft, ChevronRight, Download, GripVertical, Upload } from "lucide-react";
 import { getVersion } from "@tauri-apps/api/app";
-import { readTextFile, exists, BaseDirectory } from "@tauri-apps/plugin-fs";
 import type { Task } from "@/components/tasks/Tasks";
+import { listTasks } from "@/lib/task-store";
+import { useStoreEvent } from "@/lib/store-events";
+import {
+  listSchedule,
+  saveSchedule,
+  exportPlannerIcs,
+  importBusyIcs,
+  busyBlocks,
+  clearBusy,
+  type BusyBlock,
+  type ScheduledTask,
+} from "@/lib/planner";
+import { downloadIcs } from "@/lib/calendar";
+import { toast } from "sonner";
 import {
   Dialog,
   DialogContent,
@@ -26,17 +39,11 @@ async function isTauri(): Promise<boolean> {
   }
 }
 
-const TASKS_FILE = "tasks.json";
-
 async function fetchTasksTauri(): Promise<Task[]> {
   try {
     const available = await isTauri();
     if (!available) return [];
-    const fileExists = await exists(TASKS_FILE, { baseDir: BaseDirectory.AppData });
-    if (!fileExists) return [];
-    const text = await readTextFile(TASKS_FILE, { baseDir: BaseDirectory.AppData });
-    const data = JSON.parse(text);
-    return Array.isArray(data) ? (data as Task[]) : [];
+    return await listTasks();
   } catch {
     return [];
   }
@@ -57,15 +64,6 @@ async function fetchTasksAPI(): Promise<Task[]> {
   }
 }
 
-type ScheduledTask = {
-  taskId: string;
-  task: Task;
-  startHour: number; // 0-23
-  startMinute: number; // 0 or 30
-  durationMinutes: number; // 30, 60, 90, 120, 150, 180
-  date: string; // YYYY-MM-DD format
-};
-
 type ContextMenu = {
   x: number;
   y: number;
@@ -120,6 +118,14 @@ export const PlannerClient = () => {
   } | null>(null);
   const contextMenuRef = useRef<HTMLDivElement>(null);
   const scheduleRef = useRef<HTMLDivElement>(null);
+  const [tauri, setTauri] = useState(false);
+  // Desktop: the schedule as last loaded from or saved to the backend (JSON), so
+  // changes announced by other windows are not saved straight back
+  const savedRef = useRef<string | null>(null);
+  // Desktop: meetings from the imported calendar for the day shown, read-only
+  const [busy, setBusy] = useState<BusyBlock[]>([]);
+  const [busyVersion, setBusyVersion] = useState(0);
+  const icsInputRef = useRef<HTMLInputElement>(null);
 
   const currentDate = useMemo(() => {
     const date = new Date();
@@ -136,27 +142,119 @@ export const PlannerClient = () => {
       const data = tauri ? await fetchTasksTauri() : await fetchTasksAPI();
       setTasks(data.filter(t => !t.completed));
 
-      // Load scheduled tasks from localStorage
-      const stored = localStorage.getItem("planner_schedule");
-      if (stored) {
+      // Scheduled tasks from localStorage (web, or not yet moved to the desktop store)
+      let stored: ScheduledTask[] = [];
+      const raw = localStorage.getItem("planner_schedule");
+      if (raw) {
         try {
-          const parsed = JSON.parse(stored);
+          const parsed = JSON.parse(raw);
           if (Array.isArray(parsed)) {
-            setScheduled(parsed as ScheduledTask[]);
+            stored = parsed as ScheduledTask[];
           }
         } catch (e) {
           console.error("Failed to parse scheduled tasks", e);
         }
       }
+
+      if (!tauri) {
+        setScheduled(stored);
+        return;
+      }
+      try {
+        let blocks = await listSchedule();
+        // One-time move of the old localStorage schedule into the Rust store
+        if (blocks.length === 0 && stored.length > 0) {
+          await saveSchedule(stored);
+          blocks = stored;
+        }
+        if (raw) localStorage.removeItem("planner_schedule");
+        savedRef.current = JSON.stringify(blocks);
+        setScheduled(blocks);
+        setTauri(true);
+      } catch (e) {
+        console.error("Failed to load the schedule", e);
+        setScheduled(stored);
+      }
     })();
   }, []);
 
-  // Save scheduled tasks to localStorage whenever they change
+  // Tasks edited in other windows
+  useStoreEvent("tasks-changed", () => {
+    fetchTasksTauri().then(data => setTasks(data.filter(t => !t.completed)));
+  });
+
+  // Schedule edited in other windows
+  useStoreEvent("planner-changed", (blocks) => {
+    savedRef.current = JSON.stringify(blocks);
+    setScheduled(blocks);
+  });
+
+  // Save scheduled tasks whenever they change: to the Rust store on desktop, localStorage on web
   useEffect(() => {
+    if (tauri) {
+      const json = JSON.stringify(scheduled);
+      if (json === savedRef.current) return;
+      savedRef.current = json;
+      saveSchedule(scheduled).catch((e) => {
+        console.error("Failed to save the schedule", e);
+        toast.error("Failed to save the schedule");
+      });
+      return;
+    }
     if (scheduled.length > 0 || localStorage.getItem("planner_schedule")) {
       localStorage.setItem("planner_schedule", JSON.stringify(scheduled));
     }
-  }, [scheduled]);
+  }, [scheduled, tauri]);
+
+  // Busy time for the day shown; reloaded when a calendar is imported or cleared
+  useEffect(() => {
+    if (!tauri) return;
+    busyBlocks(currentDateString, currentDateString)
+      .then(setBusy)
+      .catch((e) => console.error("Failed to load busy time", e));
+  }, [tauri, currentDateString, busyVersion]);
+
+  useStoreEvent("busy-changed", () => setBusyVersion(v => v + 1));
+
+  const handleImportBusy = async (e: React.ChangeEvent<HTMLInputElement>) => {
+    const file = e.target.files?.[0];
+    e.target.value = "";
+    if (!file) return;
+    try {
+      const report = await importBusyIcs(await file.text());
+      toast.success(`Imported ${report.events} events (${report.recurring} recurring)`, {
+        description: [
+          report.skipped > 0 ? `${report.skipped} cancelled or free events skipped` : "",
+          ...report.notes,
+        ].filter(Boolean).join("\n") || undefined,
+      });
+    } catch (err) {
+      console.error("Calendar import failed", err);
+      toast.error("Calendar import failed", {
+        description: err && typeof err === "object" && "message" in err ? String(err.message) : undefined,
+      });
+    }
+  };
+
+  const handleClearBusy = async () => {
+    if (!confirm("Remove the imported calendar from the planner?")) return;
+    try {
+      await clearBusy();
+    } catch (e) {
+      console.error("Failed to clear the calendar", e);
+      toast.error("Failed to clear the calendar");
+    }
+  };
+
+  const handleExportIcs = async () => {
+    try {
+      downloadIcs(await exportPlannerIcs(), `shakshuka-planner-${getDateString(new Date())}.ics`);
+      toast.success("Schedule exported for your calendar");
+    } catch (e) {
+      console.error("Calendar export failed", e);
+      toast.error("Calendar export failed");
+    }
+  };
 
   // Auto-scroll to current time when viewing today
   useEffect(() => {
@@ -322,6 +420,11 @@ export const PlannerClient = () => {
           s => s.startHour === slot.hour && s.startMinute === slot.minute
         );
 
+        const busyInSlot = busy.filter(
+          b => !b.allDay && b.startHour === slot.hour &&
+            b.startMinute >= slot.minute && b.startMinute < slot.minute + 30
+        );
+
         const isDropTarget = dragOverSlot?.hour === slot.hour && 
                             dragOverSlot?.minute === slot.minute &&
                             dragOverSlot?.date === currentDateString;
@@ -341,6 +444,19 @@ export const PlannerClient = () => {
               {slot.label}
             </div>
             <div className="flex-1 p-2 relative">
+              {busyInSlot.map((b, i) => (
+                <div
+                  key={`busy-${b.uid}-${i}`}
+                  className="absolute left-2 right-2 bg-muted/60 border border-dashed border-muted-foreground/40 rounded-md px-2 py-1 pointer-events-none"
+                  style={{
+                    top: `${((b.startMinute - slot.minute) / 30) * 60 + 4}px`,
+                    height: `${Math.max((b.durationMinutes / 30) * 60 - 8, 20)}px`,
+                  }}
+                  title="From your imported calendar"
+                >
+                  <p className="text-xs font-medium truncate text-muted-foreground">{b.summary}</p>
+                </div>
+              ))}
               {tasksInSlot.map((st) => {
                 const globalIndex = scheduled.indexOf(st);
                 const heightMultiplier = st.durationMinutes / 30;
@@ -385,6 +501,30 @@ export const PlannerClient = () => {
         <Calendar className="h-6 w-6" />
         <h1 className="text-2xl font-semibold">Daily Planner</h1>
         <div className="ml-auto flex items-center gap-2">
+          {tauri && (
+            <>
+              <input
+                ref={icsInputRef}
+                type="file"
+                accept=".ics,text/calendar"
+                className="hidden"
+                onChange={handleImportBusy}
+              />
+              <Button
+                size="sm"
+                variant="outline"
+                onClick={() => icsInputRef.current?.click()}
+                title="Show meetings from an .ics calendar export as busy time"
+              >
+                <Upload className="h-4 w-4 mr-1" />
+                Meetings
+              </Button>
+              <Button size="sm" variant="outline" onClick={handleExportIcs} title="Export the schedule as an .ics file">
+                <Download className="h-4 w-4 mr-1" />
+                .ics
+              </Button>
+            </>
+          )}
           <Button
             size="sm"
             variant="outline"
@@ -451,6 +591,25 @@ export const PlannerClient = () => {
             <p className="text-xs text-muted-foreground mt-1">
               Drag tasks from the left. Right-click scheduled tasks to adjust duration.
             </p>
+            {busy.length > 0 && (
+              <div className="mt-2 flex flex-wrap items-center gap-2">
+                {busy.filter(b => b.allDay).map((b, i) => (
+                  <span
+                    key={`allday-${b.uid}-${i}`}
+                    className="text-xs rounded-md border border-dashed border-muted-foreground/40 bg-muted/60 px-2 py-0.5 text-muted-foreground"
+                  >
+                    {b.summary}
+                  </span>
+                ))}
+                <button
+                  type="button"
+                  className="ml-auto text-xs text-muted-foreground underline"
+                  onClick={handleClearBusy}
+                >
+                  Clear meetings
+                </button>
+              </div>
+            )}
           </div>
           <div ref={scheduleRef} className="overflow-y-auto max-h-[calc(100vh-250px)]">
             {renderTimeSlots()}
@@ -8,14 +8,18 @@ import { Textarea } from "@/components/ui/textarea";
 import { Label } from "@/components/ui/label";
 import { Plus, Trash2, Search, X, Edit, Eye, History, Undo, Download, Upload } from "lucide-react";
 import { getVersion } from "@tauri-apps/api/app";
-import { readTextFile, writeTextFile, exists, mkdir, BaseDirectory } from "@tauri-apps/plugin-fs";
 import { Dialog, DialogContent, DialogFooter, DialogHeader, DialogTitle, DialogTrigger } from "@/components/ui/dialog";
 import { Sheet, SheetContent, SheetHeader, SheetTitle, SheetTrigger } from "@/components/ui/sheet";
-import { loadSettings, loadStrikes, saveStrikes, type StrikeEntry, formatDateInTZ, loadUpdates, saveUpdates, type TaskUpdate, loadUsedMessages, saveUsedMessages, saveSettings } from "@/lib/local-storage";
+import { loadSettings, loadStrikes, saveStrikes, appendStrikes, type StrikeEntry, formatDateInTZ, loadUpdates, saveUpdates, type TaskUpdate, loadUsedMessages, saveUsedMessages, saveSettings } from "@/lib/local-storage";
 import { Tabs, TabsContent, TabsList, TabsTrigger } from "@/components/ui/tabs";
 import { useRouter } from "next/navigation";
 import { toast } from "sonner";
 import { getRandomCompletionMessage } from "@/lib/completion-messages";
+import * as taskStore from "@/lib/task-store";
+import { useStoreEvent } from "@/lib/store-events";
+import { clearLegacyBackups } from "@/lib/backups";
+import { exportAll, importAll, askImportMode, describeRejected } from "@/lib/bundle";
+import { searchTasks, type SearchHit } from "@/lib/search";
 import confetti from "canvas-confetti";
 
 export type Task = {
@@ -54,37 +58,16 @@ async function isTauri(): Promise<boolean> {
   }
 }
 
-// File path within the app data directory
-const TASKS_FILE = "tasks.json";
-
 async function fetchTasksTauri(): Promise<Task[]> {
   try {
     const available = await isTauri();
     if (!available) return [];
-    const fileExists = await exists(TASKS_FILE, { baseDir: BaseDirectory.AppData });
-    if (!fileExists) return [];
-    const text = await readTextFile(TASKS_FILE, { baseDir: BaseDirectory.AppData });
-    const data = JSON.parse(text);
-    return Array.isArray(data) ? (data as Task[]) : [];
+    return await taskStore.listTasks();
   } catch {
     return [];
   }
 }
 
-async function saveTasksTauri(tasks: Task[]): Promise<void> {
-  try {
-    const available = await isTauri();
-    if (!available) return;
-    // Ensure base directory exists (noop if already present)
-    await mkdir(".", { baseDir: BaseDirectory.AppData, recursive: true });
-    await writeTextFile(TASKS_FILE, JSON.stringify(tasks, null, 2), {
-      baseDir: BaseDirectory.AppData,
-    });
-  } catch {
-    // ignore
-  }
-}
-
 // Existing API persistence (fallback for web)
 async function fetchTasksAPI(): Promise<Task[]> {
   try {
@@ -113,29 +96,6 @@ async function saveTasksAPI(tasks: Task[]): Promise<void> {
   }).catch(() => {});
 }
 
-// Helper to check and perform weekly auto-backup
-async function checkAndBackup(): Promise<void> {
-  try {
-    const lastBackup = localStorage.getItem("lastBackupDate");
-    const now = Date.now();
-    const oneWeek = 7 * 24 * 60 * 60 * 1000;
-    
-    if (!lastBackup || (now - parseInt(lastBackup)) > oneWeek) {
-      const data = await exportData();
-      const blob = new Blob([JSON.stringify(data, null, 2)], { type: "application/json" });
-      const timestamp = new Date().toISOString().replace(/[:.]/g, "-");
-      
-      // Save to localStorage as backup reference
-      localStorage.setItem("lastBackupDate", now.toString());
-      localStorage.setItem(`backup_${timestamp}`, JSON.stringify(data));
-      
-      console.log("Auto-backup completed:", timestamp);
-    }
-  } catch (error) {
-    console.error("Auto-backup failed:", error);
-  }
-}
-
 // Helper to sanitize and validate task input
 function sanitizeTaskInput(input: string, maxLength: number = 200): string {
   // Strip HTML tags
@@ -169,6 +129,8 @@ function validateTaskInput(input: string): { valid: boolean; error?: string } {
 // Add export/import helper functions after saveTasksAPI
 async function exportData() {
   try {
+    // Desktop: a typed, versioned bundle from the backend (src-tauri/src/bundle.rs)
+    if (await isTauri()) return await exportAll();
     const [tasks, settings, strikes, updates, usedMessages] = await Promise.all([
       (await isTauri()) ? fetchTasksTauri() : fetchTasksAPI(),
       loadSettings(),
@@ -201,12 +163,8 @@ async function importData(data: any): Promise<void> {
       throw new Error("Invalid import data format");
     }
     
-    // Import tasks
-    if ((await isTauri())) {
-      await saveTasksTauri(data.tasks);
-    } else {
-      await saveTasksAPI(data.tasks);
-    }
+    // Import tasks (web only: the desktop app imports through importAll)
+    await saveTasksAPI(data.tasks);
     
     // Import other data
     if (data.settings) await saveSettings(data.settings);
@@ -298,6 +256,8 @@ export const Tasks = forwardRef<TasksHandle, { compact?: boolean }>(({ compact =
   // search & filter - now inline above tabs
   const [query, setQuery] = useState("");
   const [selectedTags, setSelectedTags] = useState<string[]>([]);
+  // Desktop: matches from the search index, including completed and deleted tasks
+  const [searchHits, setSearchHits] = useState<SearchHit[]>([]);
   
   // Load once - check for first-time setup
   useEffect(() => {
@@ -363,32 +323,41 @@ export const Tasks = forwardRef<TasksHandle, { compact?: boolean }>(({ compact =
       }
       
       // Check and perform auto-backup
-      await checkAndBackup();
+      // Backups are taken by the desktop app (src-tauri/src/backups.rs)
+      if (tauri) clearLegacyBackups();
     })();
     return () => {
       mounted = false;
-      // Force immediate save on unmount using latest tasks from ref
-      if (hasLoadedRef.current && tasksRef.current) {
-        if (useTauriRef.current) {
-          saveTasksTauri(tasksRef.current);
-        } else {
-          saveTasksAPI(tasksRef.current);
-        }
+      // Force immediate save on unmount using latest tasks from ref (desktop saves per mutation)
+      if (hasLoadedRef.current && tasksRef.current && !useTauriRef.current) {
+        saveTasksAPI(tasksRef.current);
       }
       if (saveTimeout.current) clearTimeout(saveTimeout.current);
     };
   }, []);
 
+  // Other windows write through the same Rust store; pick up their changes
+  useStoreEvent("tasks-changed", ({ changed, deleted, external }) => {
+    if (!useTauriRef.current) return;
+    const current = new Map(tasksRef.current.map(t => [t.id, t.revision]));
+    const stale = external || deleted.some(id => current.has(id)) || changed.some(c => current.get(c.id) !== c.revision);
+    if (stale) fetchTasksTauri().then(setTasks);
+  });
+  useStoreEvent("strikes-changed", () => {
+    loadStrikes().then(setStrikes);
+  });
+  useStoreEvent("settings-changed", settings => {
+    setResetHour(settings.resetHour);
+    setTimezone(settings.timezone);
+    setButtonColor(settings.buttonColor || "#007AFF");
+  });
+
   // Add beforeunload handler to save before page close/reload
   useEffect(() => {
     const handleBeforeUnload = (e: BeforeUnloadEvent) => {
       // Force immediate save before page unloads
-      if (hasLoadedRef.current && tasksRef.current) {
-        if (useTauriRef.current) {
-          saveTasksTauri(tasksRef.current);
-        } else {
-          saveTasksAPI(tasksRef.current);
-        }
+      if (hasLoadedRef.current && tasksRef.current && !useTauriRef.current) {
+        saveTasksAPI(tasksRef.current);
       }
     };
 
@@ -449,44 +418,33 @@ export const Tasks = forwardRef<TasksHandle, { compact?: boolean }>(({ compact =
     return () => clearInterval(intervalId);
   }, [resetHour, timezone]);
 
-  // Persist on change
+  // Persist on change (web only - the desktop task store persists each mutation itself)
   useEffect(() => {
     if (!hasLoadedRef.current) return;
     // keep latest tasks in ref for interval autosave and cleanup
     tasksRef.current = tasks;
+    if (useTauriRef.current) return;
     if (saveTimeout.current) clearTimeout(saveTimeout.current);
     saveTimeout.current = setTimeout(() => {
-      if (useTauriRef.current) {
-        saveTasksTauri(tasks);
-      } else {
-        saveTasksAPI(tasks);
-      }
+      saveTasksAPI(tasks);
     }, 400);
 
     // Flush pending save using latest tasks from ref
     return () => {
       if (saveTimeout.current) {
         clearTimeout(saveTimeout.current);
-        if (useTauriRef.current) {
-          saveTasksTauri(tasksRef.current);
-        } else {
-          saveTasksAPI(tasksRef.current);
-        }
+        saveTasksAPI(tasksRef.current);
       }
     };
   }, [tasks]);
 
-  // Autosave every 10 seconds
+  // Autosave every 10 seconds (web only)
   useEffect(() => {
     if (!hasLoadedRef.current) return;
     const id = setInterval(() => {
       const current = tasksRef.current;
-      if (!current) return;
-      if (useTauriRef.current) {
-        saveTasksTauri(current);
-      } else {
-        saveTasksAPI(current);
-      }
+      if (!current || useTauriRef.current) return;
+      saveTasksAPI(current);
     }, 10000);
     return () => clearInterval(id);
   }, []);
@@ -588,6 +546,27 @@ export const Tasks = forwardRef<TasksHandle, { compact?: boolean }>(({ compact =
     return textOk && tagsOk;
   };
 
+  useEffect(() => {
+    const q = query.trim();
+    if (!useTauriRef.current || q.length < 2) {
+      setSearchHits([]);
+      return;
+    }
+    let cancelled = false;
+    const timer = setTimeout(async () => {
+      try {
+        const hits = await searchTasks(q, 20);
+        if (!cancelled) setSearchHits(hits);
+      } catch (error) {
+        console.error("Search failed:", error);
+      }
+    }, 200);
+    return () => {
+      cancelled = true;
+      clearTimeout(timer);
+    };
+  }, [query, tasks]);
+
   const activeFiltered = useMemo(() => activeTasks.filter(matchesTask), [activeTasks, query, selectedTags]);
   const expiredFiltered = useMemo(() => expiredTasks.filter(matchesTask), [expiredTasks, query, selectedTags]);
   const completedFiltered = useMemo(() => completedTasks.filter(matchesTask), [completedTasks, query, selectedTags]);
@@ -675,6 +654,23 @@ export const Tasks = forwardRef<TasksHandle, { compact?: boolean }>(({ compact =
     try {
       const text = await file.text();
       const data = JSON.parse(text);
+      if (useTauriRef.current) {
+        // Validated and written by the backend, which reports what it left out
+        const mode = askImportMode();
+        if (mode) {
+          const report = await importAll(data, mode);
+          toast.success(`Imported ${report.tasksImported} tasks and ${report.strikesImported} strikes. Page will reload.`);
+          if (report.rejected.length > 0) {
+            toast.warning(`${report.rejected.length} records were invalid and skipped`, {
+              description: describeRejected(report.rejected),
+              duration: 15000,
+            });
+          }
+          setTimeout(() => window.location.reload(), 3000);
+        }
+        if (fileInputRef.current) fileInputRef.current.value = "";
+        return;
+      }
       await importData(data);
       
       // Reload all data
@@ -718,6 +714,29 @@ export const Tasks = forwardRef<TasksHandle, { compact?: boolean }>(({ compact =
       .split(",")
       .map(t => sanitizeTaskInput(t, 50))
       .filter(Boolean);
+    const resetForm = () => {
+      setTitle("");
+      setNotes("");
+      setDueDate("");
+      setTagsInput("");
+      setAddOpen(false);
+      inputRef.current?.focus();
+    };
+    if (useTauriRef.current) {
+      taskStore
+        .createTask({
+          title: sanitized,
+          notes: sanitizeTaskInput(notes, 1000) || undefined,
+          dueDate: dueDate || undefined,
+          tags: tags.length ? tags : undefined,
+        })
+        .then(task => {
+          setTasks(prev => [task, ...prev]);
+          resetForm();
+        })
+        .catch(e => toast.error(taskStore.storeErrorMessage(e)));
+      return;
+    }
     const now = Date.now();
     const newTask: Task = {
       id: generateUUID(),
@@ -731,19 +750,30 @@ export const Tasks = forwardRef<TasksHandle, { compact?: boolean }>(({ compact =
       ...(tags.length ? { tags } : {}),
     };
     setTasks(prev => [newTask, ...prev]);
-    setTitle("");
-    setNotes("");
-    setDueDate("");
-    setTagsInput("");
-    setAddOpen(false);
-    inputRef.current?.focus();
+    resetForm();
+  };
+
+  // Swap in the copy the desktop task store returned
+  const replaceTask = (task: Task) => {
+    setTasks(prev => prev.map(t => (t.id === task.id ? task : t)));
   };
 
   const toggleTask = (id: string) => {
+    if (useTauriRef.current) {
+      taskStore.toggleTask(id).then(replaceTask).catch(e => toast.error(taskStore.storeErrorMessage(e)));
+      return;
+    }
     setTasks(prev => prev.map(t => (t.id === id ? { ...t, completed: !t.completed, revision: t.revision + 1, updatedAt: Date.now() } : t)));
   };
 
   const removeTask = (id: string) => {
+    if (useTauriRef.current) {
+      taskStore
+        .deleteTask(id)
+        .then(() => setTasks(prev => prev.filter(t => t.id !== id)))
+        .catch(e => toast.error(taskStore.storeErrorMessage(e)));
+      return;
+    }
     setTasks(prev => prev.filter(t => t.id !== id));
   };
 
@@ -786,7 +816,7 @@ export const Tasks = forwardRef<TasksHandle, { compact?: boolean }>(({ compact =
       .map(t => sanitizeTaskInput(t, 50))
       .filter(Boolean);
 
-    const newTask: Task = {
+    let newTask: Task = {
       ...oldTask,
       title: sanitized,
       notes: sanitizeTaskInput(editNotes, 1000) || undefined,
@@ -796,7 +826,33 @@ export const Tasks = forwardRef<TasksHandle, { compact?: boolean }>(({ compact =
       updatedAt: Date.now()
     };
 
-    await recordUpdate(oldTask, newTask);
+    if (useTauriRef.current) {
+      try {
+        newTask = await taskStore.updateTask(oldTask.id, oldTask.revision, {
+          title: newTask.title,
+          notes: newTask.notes,
+          dueHour: newTask.dueHour,
+          dueDate: newTask.dueDate,
+          tags: newTask.tags,
+        });
+      } catch (e) {
+        if (taskStore.isConflict(e)) {
+          // Another window saved first: show its version and keep the editor open
+          replaceTask(e.current);
+          toast.error("This task was changed in another window. Review the latest version and save again.");
+        } else {
+          toast.error(taskStore.storeErrorMessage(e));
+        }
+        return;
+      }
+    }
+
+    if (useTauriRef.current) {
+      // The Rust store logs the edit itself; pick up the new entry
+      setUpdates(await loadUpdates());
+    } else {
+      await recordUpdate(oldTask, newTask);
+    }
     
     setTasks(prev => prev.map(t => t.id === detailTaskId ? newTask : t));
     setIsEditing(false);
@@ -837,6 +893,18 @@ export const Tasks = forwardRef<TasksHandle, { compact?: boolean }>(({ compact =
       .sort((a, b) => b.timestamp - a.timestamp); // newest first
   };
 
+  // Restore a task to the version recorded by one of its history entries (desktop only)
+  const restoreVersion = async (update: TaskUpdate) => {
+    try {
+      const restored = await taskStore.revertTask(update.taskId, update.updateId);
+      replaceTask(restored);
+      setUpdates(await loadUpdates());
+      toast.success("Previous version restored");
+    } catch (e) {
+      toast.error(taskStore.storeErrorMessage(e));
+    }
+  };
+
   // Get strike notes for a task
   const getTaskStrikeNotes = (taskId: string) => {
     return strikes
@@ -887,9 +955,8 @@ export const Tasks = forwardRef<TasksHandle, { compact?: boolean }>(({ compact =
       ts: strikeTimestamp,
       action: "strike",
     };
-    const next = [...strikes, entry];
-    setStrikes(next);
-    await saveStrikes(next);
+    setStrikes(prev => [...prev, entry]);
+    await appendStrikes([entry]);
     setStrikeTaskId(null);
     setStrikeNote("");
     setStrikingTaskId(null);
@@ -922,10 +989,14 @@ export const Tasks = forwardRef<TasksHandle, { compact?: boolean }>(({ compact =
       ts: Date.now(),
       action: "completed",
     };
-    const next = [...strikes, entry];
-    setStrikes(next);
-    await saveStrikes(next);
-    setTasks(prev => prev.map(t => (t.id === taskId ? { ...t, completed: true } : t)));
+    setStrikes(prev => [...prev, entry]);
+    await appendStrikes([entry]);
+    const target = tasks.find(t => t.id === taskId);
+    if (useTauriRef.current && target && !target.completed) {
+      await taskStore.toggleTask(taskId).then(replaceTask).catch(e => toast.error(taskStore.storeErrorMessage(e)));
+    } else {
+      setTasks(prev => prev.map(t => (t.id === taskId ? { ...t, completed: true } : t)));
+    }
     setStrikeTaskId(null);
     setStrikeNote("");
     
@@ -1209,6 +1280,39 @@ export const Tasks = forwardRef<TasksHandle, { compact?: boolean }>(({ compact =
                 )}
               </div>
             </div>
+            {searchHits.length > 0 && (
+              <div className="space-y-1 max-h-72 overflow-y-auto rounded-md border p-2">
+                <Label className="text-xs text-muted-foreground">Everywhere, including completed and deleted tasks:</Label>
+                {searchHits.map(hit => {
+                  const live = tasks.find(t => t.id === hit.task.id);
+                  const mark = (parts: SearchHit["snippets"][number]["parts"]) =>
+                    parts.map((part, j) => part.hit ? <mark key={j}>{part.text}</mark> : <span key={j}>{part.text}</span>);
+                  const title = hit.snippets.find(sn => sn.field === "title");
+                  return (
+                    <button
+                      key={hit.task.id}
+                      type="button"
+                      disabled={!live}
+                      onClick={() => live && openTaskDetail(live)}
+                      className="block w-full text-left rounded px-2 py-1 text-sm hover:bg-muted disabled:cursor-default"
+                    >
+                      <div className="flex items-center gap-2">
+                        <span className="font-medium truncate">{title ? mark(title.parts) : hit.task.title}</span>
+                        <span className="text-xs text-muted-foreground shrink-0">
+                          {hit.status === "deleted" ? "deleted" : hit.status === "completed" ? `completed${hit.completedOn ? ` ${hit.completedOn}` : ""}` : "active"}
+                        </span>
+                      </div>
+                      {hit.snippets.filter(sn => sn.field !== "title").map((sn, i) => (
+                        <div key={i} className="text-xs text-muted-foreground truncate">
+                          {sn.field === "strike" ? `Strike ${sn.date}: ` : sn.field === "tag" ? "#" : ""}
+                          {mark(sn.parts)}
+                        </div>
+                      ))}
+                    </button>
+                  );
+                })}
+              </div>
+            )}
             {allTags.length > 0 && (
               <div className="space-y-2">
                 <Label className="text-xs text-muted-foreground">Filter by tags:</Label>
@@ -1552,6 +1656,31 @@ export const Tasks = forwardRef<TasksHandle, { compact?: boolean }>(({ compact =
                           ))}
                         </div>
                       )}
+                      {useTauriRef.current && getTaskUpdates(detailTask.id).length > 0 && (
+                        <>
+                          <h3 className="text-sm font-semibold pt-2">Edits</h3>
+                          <div className="space-y-2">
+                            {getTaskUpdates(detailTask.id).map((update) => (
+                              <div key={update.updateId} className="p-3 border rounded-md bg-muted/30 flex items-center justify-between gap-2">
+                                <div>
+                                  <p className="text-xs text-muted-foreground">
+                                    {new Date(update.timestamp).toLocaleString()}
+                                  </p>
+                                  <p className="text-sm">
+                                    {/* Only creation and deletion records touch the id */}
+                                    {update.diff.id
+                                      ? (update.diff.id.old == null ? "Created" : "Deleted")
+                                      : `Changed ${Object.keys(update.diff).filter(k => k !== "revision" && k !== "updatedAt").join(", ") || "nothing visible"}`}
+                                  </p>
+                                </div>
+                                <Button size="sm" variant="ghost" onClick={() => restoreVersion(update)}>
+                                  <Undo className="h-4 w-4 mr-1" /> Restore
+                                </Button>
+                              </div>
+                            ))}
+                          </div>
+                        </>
+                      )}
                     </div>
                   ) : isEditing ? (
                     <>
@@ -1622,14 +1751,14 @@ export const Tasks = forwardRef<TasksHandle, { compact?: boolean }>(({ compact =
                           <p>Last updated: {new Date(detailTask.updatedAt).toLocaleString()}</p>
                           <p>Revision: {detailTask.revision}</p>
                         </div>
-                        {getTaskStrikeNotes(detailTask.id).length > 0 && (
+                        {(getTaskStrikeNotes(detailTask.id).length > 0 || (useTauriRef.current && getTaskUpdates(detailTask.id).length > 0)) && (
                           <Button 
                             size="sm" 
                             variant="outline" 
                             onClick={() => setShowUpdateHistory(true)}
                             className="mt-2"
                           >
-                            <History className="h-4 w-4 mr-1" /> View Strike Notes ({getTaskStrikeNotes(detailTask.id).length})
+                            <History className="h-4 w-4 mr-1" /> View History ({getTaskStrikeNotes(detailTask.id).length + (useTauriRef.current ? getTaskUpdates(detailTask.id).length : 0)})
                           </Button>
                         )}
                       </div>
@@ -0,0 +1,36 @@
+"use client";
+
+import { useEffect } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { toast } from "sonner";
+
+// Payload of the Rust `data-recovered` event (src-tauri/src/persist.rs)
+type Recovery = {
+  file: string;
+  error: string;
+  quarantinedTo: string;
+  restoredFromBackup: boolean;
+};
+
+export const DataRecoveryListener = () => {
+  useEffect(() => {
+    let unlisten: (() => void) | undefined;
+    listen<Recovery>("data-recovered", ({ payload }) => {
+      const what = payload.restoredFromBackup
+        ? "restored from the previous backup"
+        : "could not be recovered and was reset";
+      toast.warning(`${payload.file} was damaged and ${what}`, {
+        description: `The damaged copy was kept at ${payload.quarantinedTo}`,
+        duration: 15000,
+      });
+    })
+      .then(fn => { unlisten = fn; })
+      .catch(() => {
+        // Not running in Tauri (web/SSR) – safely ignore
+      });
+    return () => unlisten?.();
+  }, []);
+
+  // No UI – this component just listens
+  return null;
+};
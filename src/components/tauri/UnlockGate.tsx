@@ -0,0 +1,68 @@
+"use client";
+
+import { useEffect, useState } from "react";
+import { Card, CardContent, CardHeader, CardTitle } from "@/components/ui/card";
+import { Input } from "@/components/ui/input";
+import { Button } from "@/components/ui/button";
+import { encryptionStatus, unlock } from "@/lib/encryption";
+
+// Holds the app back until an encrypted data directory has been unlocked.
+// The Rust side only loads the data once the passphrase is accepted.
+export const UnlockGate = ({ children }: { children: React.ReactNode }) => {
+  const [locked, setLocked] = useState(false);
+  const [passphrase, setPassphrase] = useState("");
+  const [error, setError] = useState<string | null>(null);
+  const [unlocking, setUnlocking] = useState(false);
+
+  useEffect(() => {
+    encryptionStatus()
+      .then((s) => setLocked(s.locked))
+      .catch(() => {
+        // Not running in Tauri (web/SSR) – nothing to unlock
+      });
+  }, []);
+
+  const onUnlock = async (e: React.FormEvent) => {
+    e.preventDefault();
+    setUnlocking(true);
+    setError(null);
+    try {
+      await unlock(passphrase);
+      setPassphrase("");
+      setLocked(false);
+    } catch (err) {
+      const kind = (err as { kind?: string })?.kind;
+      setError(kind === "badPassphrase" ? "Wrong passphrase" : String((err as { message?: string })?.message ?? err));
+    } finally {
+      setUnlocking(false);
+    }
+  };
+
+  if (!locked) return <>{children}</>;
+
+  return (
+    <main className="mx-auto max-w-sm px-4 py-16">
+      <Card>
+        <CardHeader>
+          <CardTitle>Unlock Shakshuka</CardTitle>
+        </CardHeader>
+        <CardContent>
+          <form onSubmit={onUnlock} className="space-y-4">
+            <p className="text-sm text-muted-foreground">Your data is encrypted. Enter your passphrase to open it.</p>
+            <Input
+              type="password"
+              autoFocus
+              value={passphrase}
+              onChange={(e) => setPassphrase(e.target.value)}
+              placeholder="Passphrase"
+            />
+            {error && <p className="text-sm text-destructive">{error}</p>}
+            <Button type="submit" disabled={unlocking || !passphrase} className="w-full">
+              {unlocking ? "Unlocking..." : "Unlock"}
+            </Button>
+          </form>
+        </CardContent>
+      </Card>
+    </main>
+  );
+};
@@ -4,8 +4,9 @@ import { useEffect, useMemo, useState } from "react";
 import { Card, CardContent } from "@/components/ui/card";
 import { Badge } from "@/components/ui/badge";
 import { isTauri } from "@/lib/local-storage";
-import { loadSettings, loadStrikes, saveStrikes, type StrikeEntry, formatDateInTZ } from "@/lib/local-storage";
-import { readTextFile, exists, BaseDirectory } from "@tauri-apps/plugin-fs";
+import { loadSettings, loadStrikes, appendStrikes, type StrikeEntry, formatDateInTZ } from "@/lib/local-storage";
+import { listTasks } from "@/lib/task-store";
+import { useStoreEvent } from "@/lib/store-events";
 
 // Mirror of Task type (subset) to avoid import cycle
 interface Task {
@@ -17,17 +18,11 @@ interface Task {
   dueDate?: string; // YYYY-MM-DD optional absolute due date
 }
 
-const TASKS_FILE = "tasks.json";
-
 async function loadTasks(): Promise<Task[]> {
   try {
     const tauri = await isTauri();
     if (tauri) {
-      const ok = await exists(TASKS_FILE, { baseDir: BaseDirectory.App });
-      if (!ok) return [];
-      const txt = await readTextFile(TASKS_FILE, { baseDir: BaseDirectory.App });
-      const data = JSON.parse(txt);
-      return Array.isArray(data) ? (data as Task[]) : [];
+      return await listTasks();
     } else {
       const res = await fetch("/api/tasks");
       if (!res.ok) return [];
@@ -71,6 +66,14 @@ export const Counters = ({ compact = false }: { compact?: boolean }) => {
     return () => window.removeEventListener("strikes-updated", handleStrikesUpdated);
   }, []);
 
+  // Changes made in other windows
+  useStoreEvent("tasks-changed", () => { loadTasks().then(setTasks); });
+  useStoreEvent("strikes-changed", () => { loadStrikes().then(setStrikes); });
+  useStoreEvent("settings-changed", s => {
+    setTimezone(s.timezone);
+    setResetHour(s.resetHour);
+  });
+
   const now = new Date();
   const today = useMemo(() => formatDateInTZ(now.getTime(), timezone), [now, timezone]);
   const hour = now.getHours();
@@ -140,9 +143,8 @@ export const Counters = ({ compact = false }: { compact?: boolean }) => {
         ts: Date.now(),
         action: "expired",
       }));
-      const next = [...strikes, ...newEntries];
-      setStrikes(next);
-      await saveStrikes(next);
+      setStrikes(prev => [...prev, ...newEntries]);
+      await appendStrikes(newEntries);
     })();
     // only rerun when day/hour/tasks or strikes change materially
   }, [tasks, timezone, today, hour, strikes]);
@@ -0,0 +1,60 @@
+"use client";
+
+import { invoke } from "@tauri-apps/api/core";
+
+// Desktop-only wrappers around the Rust backup commands (src-tauri/src/commands/backups.rs).
+// Daily, weekly and monthly backups are taken by the app itself; see src-tauri/src/backups.rs.
+
+export type BackupKind = "daily" | "weekly" | "monthly" | "manual" | "safety";
+
+export type BackupInfo = {
+  id: string;
+  kind: BackupKind;
+  createdAt: number; // epoch ms
+  size: number; // compressed bytes
+  skipped: string[]; // loose files that could not be read; empty when listed
+};
+
+// Newest first
+export async function listBackups(): Promise<BackupInfo[]> {
+  return invoke<BackupInfo[]>("list_backups");
+}
+
+export async function createBackup(): Promise<BackupInfo> {
+  return invoke<BackupInfo>("create_backup");
+}
+
+export type TaskSummary = { id: string; title: string };
+
+// What restoring a backup would change, compared with the current data
+export type RestorePreview = {
+  backup?: BackupInfo;
+  tasksAdded: TaskSummary[];
+  tasksRemoved: TaskSummary[];
+  tasksChanged: TaskSummary[];
+  strikesCurrent: number;
+  strikesInBackup: number;
+  strikesAdded: number;
+  strikesRemoved: number;
+  updatesCurrent: number;
+  updatesInBackup: number;
+  settingsChanged: boolean;
+};
+
+export async function previewRestore(backupId: string): Promise<RestorePreview> {
+  return invoke<RestorePreview>("preview_restore", { backupId });
+}
+
+// Resolves to the safety backup taken of the data that was replaced
+export async function restoreBackup(backupId: string): Promise<BackupInfo> {
+  return invoke<BackupInfo>("restore_backup", { backupId });
+}
+
+// Older builds kept weekly backups in localStorage; they now live in the data directory
+export function clearLegacyBackups() {
+  if (typeof window === "undefined") return;
+  for (const key of Object.keys(localStorage)) {
+    if (key.startsWith("backup_")) localStorage.removeItem(key);
+  }
+  localStorage.removeItem("lastBackupDate");
+}
@@ -0,0 +1,74 @@
+"use client";
+
+import { invoke } from "@tauri-apps/api/core";
+import type { BackupInfo } from "@/lib/backups";
+
+// Desktop-only wrappers around the Rust export/import commands (src-tauri/src/commands/bundle.rs).
+// The bundle format and its validation live in src-tauri/src/bundle.rs.
+
+export type ImportMode = "merge" | "replace";
+
+// A record left out of an import
+export type Rejected = {
+  section: "tasks" | "strikes" | "updates" | "settings" | "planner" | "files";
+  index?: number;
+  id?: string;
+  reason: string;
+};
+
+export type ImportReport = {
+  mode: ImportMode;
+  tasksImported: number;
+  strikesImported: number;
+  updatesImported: number;
+  settingsImported: boolean;
+  filesImported: string[];
+  rejected: Rejected[];
+  backup: BackupInfo; // safety backup of the data before the import
+};
+
+type CommandError = { kind?: string; message?: string };
+
+// Retries `run` with a passphrase from prompt() while the backend answers "locked"
+async function withPassphrase<T>(run: (passphrase?: string) => Promise<T>, question: string): Promise<T> {
+  try {
+    return await run();
+  } catch (error) {
+    if ((error as CommandError)?.kind !== "locked") throw error;
+    const passphrase = window.prompt(question);
+    if (!passphrase) throw error;
+    return run(passphrase);
+  }
+}
+
+// The whole data set as a versioned bundle; encrypted with the passphrase when encryption is on
+export async function exportAll(): Promise<unknown> {
+  return withPassphrase(
+    (passphrase) => invoke("export_all", { passphrase }),
+    "Your data is encrypted. Enter your passphrase to export it:",
+  );
+}
+
+// Checks every record of `file` and writes the valid ones; see ImportReport.rejected for the rest
+export async function importAll(file: unknown, mode: ImportMode): Promise<ImportReport> {
+  return withPassphrase(
+    (passphrase) => invoke<ImportReport>("import_all", { file, mode, passphrase }),
+    "This export is encrypted. Enter its passphrase:",
+  );
+}
+
+export function describeRejected(rejected: Rejected[]): string {
+  return rejected
+    .slice(0, 5)
+    .map((r) => `${r.section}${r.index !== undefined ? ` #${r.index + 1}` : ""}${r.id ? ` (${r.id})` : ""}: ${r.reason}`)
+    .concat(rejected.length > 5 ? [`...and ${rejected.length - 5} more`] : [])
+    .join("\n");
+}
+
+// Merge keeps current data and adds to it; replace overwrites. null when the user backs out.
+export function askImportMode(): ImportMode | null {
+  if (confirm("Merge the imported data into your current data?\n\nOK merges (newer edits win). Cancel lets you replace everything instead.")) {
+    return "merge";
+  }
+  return confirm("Replace ALL current data with the imported file? A safety backup is taken first.") ? "replace" : null;
+}
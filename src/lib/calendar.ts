@@ -0,0 +1,22 @@
+"use client";
+
+import { invoke } from "@tauri-apps/api/core";
+
+// Desktop-only wrappers around the Rust calendar commands (src-tauri/src/commands/calendar.rs).
+
+// Tasks with a dueDate/dueHour as an iCalendar file of VTODOs
+export async function exportTasksIcs(): Promise<string> {
+  return invoke<string>("export_tasks_ics");
+}
+
+export function downloadIcs(ics: string, name: string) {
+  const blob = new Blob([ics], { type: "text/calendar" });
+  const url = URL.createObjectURL(blob);
+  const a = document.createElement("a");
+  a.href = url;
+  a.download = name;
+  document.body.appendChild(a);
+  a.click();
+  document.body.removeChild(a);
+  URL.revokeObjectURL(url);
+}
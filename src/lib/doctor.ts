@@ -0,0 +1,32 @@
+"use client";
+
+import { invoke } from "@tauri-apps/api/core";
+
+// Desktop-only wrapper around the Rust data checker (src-tauri/src/commands/doctor.rs).
+
+export type Issue = {
+  file: string;
+  index: number | null; // position in the file's array; null for the whole file
+  id: string | null;
+  problem: string;
+  fix: string | null; // what a repair does; null if it cannot be repaired
+};
+
+export type DoctorReport = {
+  issues: Issue[];
+  repaired: boolean;
+  backup: string | null; // id of the safety backup taken before repairing
+};
+
+// Checks every data file; with repair, fixes what it can after a backup
+export async function runDoctor(repair: boolean): Promise<DoctorReport> {
+  return invoke<DoctorReport>("doctor", { repair });
+}
+
+export function describeIssues(issues: Issue[]): string {
+  return issues
+    .slice(0, 5)
+    .map((i) => `${i.file}${i.index != null ? ` #${i.index + 1}` : ""}${i.id ? ` (${i.id})` : ""}: ${i.problem}${i.fix ? ` -> ${i.fix}` : ""}`)
+    .concat(issues.length > 5 ? [`...and ${issues.length - 5} more`] : [])
+    .join("\n");
+}
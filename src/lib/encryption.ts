@@ -0,0 +1,26 @@
+"use client";
+
+import { invoke } from "@tauri-apps/api/core";
+
+// Desktop-only wrappers around the Rust encryption commands (src-tauri/src/commands/crypto.rs).
+// With a passphrase set, every data file, history segment and backup is encrypted on disk.
+
+export type EncryptionStatus = {
+  enabled: boolean;
+  locked: boolean; // enabled and the passphrase has not been entered yet
+};
+
+export async function encryptionStatus(): Promise<EncryptionStatus> {
+  return invoke<EncryptionStatus>("encryption_status");
+}
+
+// Rejects with { kind: "badPassphrase" } on a wrong passphrase
+export async function unlock(passphrase: string): Promise<void> {
+  return invoke("unlock", { passphrase });
+}
+
+// `current` is the passphrase in use (null when turning encryption on);
+// a null `next` turns encryption off. Everything is re-encrypted before this resolves.
+export async function changePassphrase(current: string | null, next: string | null): Promise<void> {
+  return invoke("change_passphrase", { current, new: next });
+}
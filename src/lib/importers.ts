@@ -0,0 +1,34 @@
+"use client";
+
+import { invoke } from "@tauri-apps/api/core";
+import type { Task } from "@/components/tasks/Tasks";
+import type { ImportReport, Rejected } from "@/lib/bundle";
+
+// Desktop-only wrappers around the Rust importers for other tools (src-tauri/src/commands/importers.rs).
+
+export type TaskSource = "todoist" | "taskwarrior" | "todotxt";
+
+// What an import would write; nothing is saved by the preview
+export type TaskImport = {
+  source: TaskSource;
+  tasks: Task[];
+  rejected: Rejected[];
+  warnings: Rejected[]; // imported, but with an unreadable due date dropped
+};
+
+// Todoist exports CSV, `task export` JSON; anything else is read as todo.txt
+export function sourceOf(fileName: string): TaskSource {
+  const name = fileName.toLowerCase();
+  if (name.endsWith(".csv")) return "todoist";
+  if (name.endsWith(".json")) return "taskwarrior";
+  return "todotxt";
+}
+
+export async function previewTaskImport(source: TaskSource, text: string, name?: string): Promise<TaskImport> {
+  return invoke<TaskImport>("preview_task_import", { source, text, name });
+}
+
+// Merges the valid tasks into the current ones after a safety backup
+export async function importTasks(source: TaskSource, text: string, name?: string): Promise<ImportReport> {
+  return invoke<ImportReport>("import_tasks", { source, text, name });
+}
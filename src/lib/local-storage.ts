@@ -1,7 +1,7 @@
 "use client";
 
 import { getVersion } from "@tauri-apps/api/app";
-import { readTextFile, writeTextFile, exists, mkdir, BaseDirectory } from "@tauri-apps/plugin-fs";
+import { invoke } from "@tauri-apps/api/core";
 
 export async function isTauri(): Promise<boolean> {
   try {
@@ -16,10 +16,8 @@ async function readJSON<T>(file: string, fallback: T): Promise<T> {
   const tauri = await isTauri();
   if (tauri) {
     try {
-      const fileExists = await exists(file, { baseDir: BaseDirectory.AppConfig });
-      if (!fileExists) return fallback;
-      const text = await readTextFile(file, { baseDir: BaseDirectory.AppConfig });
-      return JSON.parse(text) as T;
+      const data = await invoke<T | null>("read_data_file", { name: file });
+      return data ?? fallback;
     } catch {
       return fallback;
     }
@@ -37,8 +35,8 @@ async function writeJSON<T>(file: string, data: T): Promise<void> {
   const tauri = await isTauri();
   if (tauri) {
     try {
-      await mkdir(".", { baseDir: BaseDirectory.AppConfig, recursive: true });
-      await writeTextFile(file, JSON.stringify(data, null, 2), { baseDir: BaseDirectory.AppConfig });
+      // Rust side writes atomically and keeps the previous generation as <file>.bak
+      await invoke("write_data_file", { name: file, data });
     } catch {}
   } else {
     try {
@@ -47,6 +45,16 @@ async function writeJSON<T>(file: string, data: T): Promise<void> {
   }
 }
 
+// Directory the desktop app keeps every data file in (resolved in Rust, honours --data-dir)
+export async function getDataDir(): Promise<string | null> {
+  if (!(await isTauri())) return null;
+  try {
+    return await invoke<string>("data_dir");
+  } catch {
+    return null;
+  }
+}
+
 export type StrikeEntry = {
   taskId: string;
   date: string; // YYYY-MM-DD in user TZ
@@ -59,8 +67,8 @@ export type TaskUpdate = {
   updateId: string; // UUID
   taskId: string;
   timestamp: number;
-  diff: Record<string, { old: any; new: any }>; // what changed
-  fullSnapshot: any; // complete task state after change
+  diff: Record<string, { old: any; new: any }>; // what changed; every field, id included, on creation and deletion
+  fullSnapshot?: any; // complete task state after change; compaction keeps it only at checkpoints
 };
 
 const STRIKES_FILE = "strikes.json";
@@ -77,6 +85,7 @@ export type AppSettings = {
   firstTimeSetupCompleted?: boolean; // track if setup dialog has been shown
   usedMessageIds?: string[]; // track which completion messages have been shown
   showPomodoroTimer?: boolean; // show pomodoro timer on dashboard
+  historyRetentionDays?: number; // task history kept by the desktop app, 0 = forever (default 90)
 };
 
 export async function loadSettings(): Promise<AppSettings> {
@@ -99,6 +108,17 @@ export async function saveStrikes(entries: StrikeEntry[]) {
   await writeJSON<StrikeEntry[]>(STRIKES_FILE, entries);
 }
 
+// Adds entries to the history; the desktop backend appends instead of rewriting the file
+export async function appendStrikes(entries: StrikeEntry[]) {
+  if (await isTauri()) {
+    try {
+      await invoke("append_strikes", { entries });
+    } catch {}
+  } else {
+    await saveStrikes([...(await loadStrikes()), ...entries]);
+  }
+}
+
 export async function loadUpdates(): Promise<TaskUpdate[]> {
   return readJSON<TaskUpdate[]>(UPDATES_FILE, []);
 }
@@ -0,0 +1,62 @@
+"use client";
+
+import { invoke } from "@tauri-apps/api/core";
+import type { Task } from "@/components/tasks/Tasks";
+
+// Desktop-only wrappers around the Rust planner commands (src-tauri/src/commands/planner.rs).
+// The schedule used to live in localStorage["planner_schedule"]; the planner moves it over once.
+
+export type ScheduledTask = {
+  taskId: string;
+  task: Task;
+  startHour: number; // 0-23
+  startMinute: number; // 0 or 30
+  durationMinutes: number; // 30, 60, 90, 120, 150, 180
+  date: string; // YYYY-MM-DD format
+};
+
+export async function listSchedule(): Promise<ScheduledTask[]> {
+  return invoke<ScheduledTask[]>("list_schedule");
+}
+
+// Replaces the whole schedule; rejected as a whole if any block is invalid
+export async function saveSchedule(blocks: ScheduledTask[]): Promise<void> {
+  return invoke("save_schedule", { blocks });
+}
+
+// The schedule as iCalendar events in the user's timezone
+export async function exportPlannerIcs(): Promise<string> {
+  return invoke<string>("export_planner_ics");
+}
+
+// A read-only piece of a meeting from the imported calendar, on one day in the user's timezone
+export type BusyBlock = {
+  uid: string;
+  summary: string;
+  date: string; // YYYY-MM-DD
+  startHour: number;
+  startMinute: number; // any minute, unlike planner blocks
+  durationMinutes: number; // split at midnight
+  allDay: boolean;
+};
+
+export type BusyImport = {
+  events: number;
+  recurring: number;
+  skipped: number; // cancelled, free or zero-length
+  notes: string[];
+};
+
+// Replaces the imported calendar with the events of an .ics file
+export async function importBusyIcs(ics: string): Promise<BusyImport> {
+  return invoke<BusyImport>("import_busy_ics", { ics });
+}
+
+// Busy time for the days from..to (inclusive), recurring meetings expanded
+export async function busyBlocks(from: string, to: string): Promise<BusyBlock[]> {
+  return invoke<BusyBlock[]>("busy_blocks", { from, to });
+}
+
+export async function clearBusy(): Promise<void> {
+  return invoke("clear_busy");
+}
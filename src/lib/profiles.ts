@@ -0,0 +1,20 @@
+"use client";
+
+import { invoke } from "@tauri-apps/api/core";
+
+// Desktop-only wrappers around the Rust profile commands (src-tauri/src/commands/profiles.rs).
+// Each profile has its own tasks, strikes, settings and planner in its own folder.
+
+export type ProfileList = {
+  active: string;
+  names: string[]; // "default" first
+};
+
+export async function listProfiles(): Promise<ProfileList> {
+  return invoke<ProfileList>("list_profiles");
+}
+
+// Creates the profile if it is new; the app restarts into it
+export async function switchProfile(name: string): Promise<void> {
+  return invoke("switch_profile", { name });
+}
@@ -0,0 +1,29 @@
+"use client";
+
+import { invoke } from "@tauri-apps/api/core";
+
+// Desktop-only wrappers around the Rust report exports (src-tauri/src/commands/strikes.rs).
+
+export type ReportPeriod = "day" | "week"; // a week runs Monday to Sunday
+
+// Strike entries dated from..to (YYYY-MM-DD, inclusive) as CSV: date,taskId,title,action,note,ts
+export async function exportStrikesCsv(from: string, to: string): Promise<string> {
+  return invoke<string>("export_strikes_csv", { from, to });
+}
+
+// Completed, struck and expired tasks of the day or week holding `date`, grouped by tag
+export async function strikeReport(period: ReportPeriod, date: string): Promise<string> {
+  return invoke<string>("strike_report", { period, date });
+}
+
+export function downloadCsv(csv: string, name: string) {
+  const blob = new Blob([csv], { type: "text/csv" });
+  const url = URL.createObjectURL(blob);
+  const a = document.createElement("a");
+  a.href = url;
+  a.download = name;
+  document.body.appendChild(a);
+  a.click();
+  document.body.removeChild(a);
+  URL.revokeObjectURL(url);
+}
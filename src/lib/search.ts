@@ -0,0 +1,26 @@
+"use client";
+
+import { invoke } from "@tauri-apps/api/core";
+import type { Task } from "@/components/tasks/Tasks";
+
+// Desktop-only wrapper around the Rust search index (src-tauri/src/commands/search.rs).
+// Covers active, completed and deleted tasks, their notes and tags, and strike notes.
+
+export type SearchField = "title" | "tag" | "notes" | "strike";
+
+export type SearchHit = {
+  task: Task; // as it was when deleted, for deleted tasks
+  status: "active" | "completed" | "deleted";
+  completedOn: string | null; // YYYY-MM-DD of the last recorded completion
+  score: number;
+  snippets: {
+    field: SearchField;
+    date: string | null; // the strike's date, for strike notes
+    parts: { text: string; hit: boolean }[];
+  }[];
+};
+
+// Every word must match, as a prefix of a word in the task; best match first
+export async function searchTasks(query: string, limit?: number): Promise<SearchHit[]> {
+  return invoke<SearchHit[]>("search", { query, limit });
+}
@@ -0,0 +1,48 @@
+"use client";
+
+import { useEffect, useRef } from "react";
+import { listen } from "@tauri-apps/api/event";
+import type { AppSettings } from "@/lib/local-storage";
+import type { BusyImport, ScheduledTask } from "@/lib/planner";
+
+// Broadcast by the Rust stores to every window after each write (src-tauri/src/events.rs)
+export type TasksChanged = {
+  changed: { id: string; revision: number }[];
+  deleted: string[];
+  external: boolean; // edited outside the app (script, sync tool); revisions may be unchanged
+};
+
+export type StrikesChanged = {
+  taskIds: string[];
+};
+
+type StoreEvents = {
+  "tasks-changed": TasksChanged;
+  "strikes-changed": StrikesChanged;
+  "settings-changed": AppSettings;
+  "planner-changed": ScheduledTask[];
+  "busy-changed": BusyImport;
+};
+
+// Subscribes for the lifetime of the component; a no-op outside Tauri
+export function useStoreEvent<E extends keyof StoreEvents>(event: E, handler: (payload: StoreEvents[E]) => void) {
+  const handlerRef = useRef(handler);
+  handlerRef.current = handler;
+
+  useEffect(() => {
+    let unlisten: (() => void) | undefined;
+    let disposed = false;
+    listen<StoreEvents[E]>(event, ({ payload }) => handlerRef.current(payload))
+      .then(fn => {
+        if (disposed) fn();
+        else unlisten = fn;
+      })
+      .catch(() => {
+        // Not running in Tauri (web/SSR) – safely ignore
+      });
+    return () => {
+      disposed = true;
+      unlisten?.();
+    };
+  }, [event]);
+}
@@ -0,0 +1,85 @@
+"use client";
+
+import { invoke } from "@tauri-apps/api/core";
+import type { Task } from "@/components/tasks/Tasks";
+import type { TaskUpdate } from "@/lib/local-storage";
+
+// Desktop-only wrappers around the Rust task store commands (src-tauri/src/commands/tasks.rs)
+
+export type TaskDraft = {
+  title: string;
+  notes?: string;
+  dueHour?: number;
+  dueDate?: string;
+  tags?: string[];
+};
+
+// Commands reject with this shape (see src-tauri/src/error.rs)
+export type StoreError = {
+  kind: string;
+  message: string;
+  current?: Task; // set when kind === "conflict"
+};
+
+// update_task rejected the edit because the task changed since `baseRevision`
+export function isConflict(e: unknown): e is StoreError & { current: Task } {
+  return !!e && typeof e === "object" && (e as StoreError).kind === "conflict" && !!(e as StoreError).current;
+}
+
+export function storeErrorMessage(e: unknown): string {
+  if (e && typeof e === "object" && "message" in e) return String((e as StoreError).message);
+  return String(e);
+}
+
+export async function listTasks(): Promise<Task[]> {
+  return invoke<Task[]>("list_tasks");
+}
+
+export async function createTask(draft: TaskDraft): Promise<Task> {
+  return invoke<Task>("create_task", { draft });
+}
+
+// `baseRevision` is the revision the edit started from; stale edits reject with a conflict
+export async function updateTask(id: string, baseRevision: number, draft: TaskDraft): Promise<Task> {
+  return invoke<Task>("update_task", { id, baseRevision, draft });
+}
+
+export async function deleteTask(id: string): Promise<Task> {
+  return invoke<Task>("delete_task", { id });
+}
+
+export async function toggleTask(id: string): Promise<Task> {
+  return invoke<Task>("toggle_task", { id });
+}
+
+// Puts the task back the way history entry `updateId` left it, as a new revision
+export async function revertTask(id: string, updateId: string): Promise<Task> {
+  return invoke<Task>("revert_task", { id, updateId });
+}
+
+// Task history (src-tauri/src/commands/history.rs); bounds are epoch ms, inclusive
+export type HistoryQuery = {
+  taskId?: string;
+  from?: number;
+  to?: number;
+};
+
+export type CompactionReport = {
+  updatesRemoved: number;
+  snapshotsDropped: number;
+  bytesReclaimed: number;
+};
+
+// The task as it was at `timestamp` (epoch ms), rebuilt from its history
+export async function taskAt(id: string, timestamp: number): Promise<Task> {
+  return invoke<Task>("task_at", { id, timestamp });
+}
+
+export async function queryHistory(query: HistoryQuery = {}): Promise<TaskUpdate[]> {
+  return invoke<TaskUpdate[]>("query_history", { query });
+}
+
+// Drops history past the retention window and thins out snapshots; also runs on startup
+export async function compactHistory(): Promise<CompactionReport> {
+  return invoke<CompactionReport>("compact_history");
+}